@@ -23,7 +23,7 @@ pub enum OutputMode {
 
 /// assign ids to advisories in a particular repo_path
 pub fn assign_ids(repo_path: &Path, output_mode: OutputMode) {
-    let db = rustsec::Database::open(repo_path).unwrap_or_else(|e| {
+    let db = rustsec::Database::open_strict(repo_path).unwrap_or_else(|e| {
         status_err!(
             "couldn't open advisory DB repo from {}: {}",
             repo_path.display(),
@@ -42,7 +42,7 @@ impl Linter {
         let repo_path = repo_path.into();
         let mut crates_index = crates_index::Index::new_cargo_default()?;
         crates_index.update()?;
-        let advisory_db = rustsec::Database::open(&repo_path)?;
+        let advisory_db = rustsec::Database::open_strict(&repo_path)?;
 
         Ok(Self {
             repo_path,
@@ -22,7 +22,7 @@ impl AffectedVersionLister {
         let repo_path = repo_path.into();
         let mut crates_index = crates_index::Index::new_cargo_default()?;
         crates_index.update()?;
-        let advisory_db = Database::open(&repo_path)?;
+        let advisory_db = Database::open_strict(&repo_path)?;
         Ok(Self {
             crates_index,
             advisory_db,
@@ -3,7 +3,7 @@
 use crate::{
     binary_format::BinaryFormat, config::AuditConfig, lockfile, prelude::*, presenter::Presenter,
 };
-use rustsec::{registry, report, Error, ErrorKind, Lockfile, Warning, WarningKind};
+use rustsec::{package::Package, registry, report, Error, ErrorKind, Lockfile};
 use std::{
     io::{self, Read},
     path::Path,
@@ -90,6 +90,10 @@ impl Auditor {
             })
         };
 
+        for (path, err) in database.load_errors() {
+            status_warn!("skipped advisory {}: {}", path.display(), err);
+        }
+
         if !config.output.is_quiet() {
             status_ok!(
                 "Loaded",
@@ -267,21 +271,15 @@ impl Auditor {
         }
 
         // Warn for yanked crates
-        let mut yanked = self.check_for_yanked_crates(lockfile);
-        if !yanked.is_empty() {
-            report
-                .warnings
-                .entry(WarningKind::Yanked)
-                .or_default()
-                .append(&mut yanked);
-        }
+        let yanked = self.check_for_yanked_crates(lockfile);
+        report.add_yanked(yanked);
 
         self.presenter.print_report(&report, lockfile, path);
 
         Ok(report)
     }
 
-    fn check_for_yanked_crates(&mut self, lockfile: &Lockfile) -> Vec<Warning> {
+    fn check_for_yanked_crates<'a>(&mut self, lockfile: &'a Lockfile) -> Vec<&'a Package> {
         let mut result = Vec::new();
         if let Some(index) = &mut self.registry_index {
             let pkgs_to_check: Vec<_> = lockfile
@@ -297,10 +295,7 @@ impl Auditor {
 
             for pkg in yanked {
                 match pkg {
-                    Ok(pkg) => {
-                        let warning = Warning::new(WarningKind::Yanked, pkg, None, None, None);
-                        result.push(warning);
-                    }
+                    Ok(pkg) => result.push(pkg),
                     Err(e) => status_err!("couldn't check if the package is yanked: {}", e),
                 }
             }
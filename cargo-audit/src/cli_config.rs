@@ -53,11 +53,10 @@ impl CliConfig {
         }
 
         for advisory_id in &self.ignore {
-            config.advisories.ignore.push(
-                advisory_id
-                    .parse()
-                    .map_err(|e| Context::new(FrameworkErrorKind::ParseError, Some(Box::new(e))))?,
-            );
+            let advisory_id: rustsec::advisory::Id = advisory_id
+                .parse()
+                .map_err(|e| Context::new(FrameworkErrorKind::ParseError, Some(Box::new(e))))?;
+            config.advisories.ignore.push(advisory_id.into());
         }
 
         config.advisories.ignore_source |= self.ignore_source;
@@ -75,9 +75,46 @@ impl Runnable for FixCommand {
             dry_run_info
         );
 
+        let plan = fixer.plan(&report.vulnerabilities.list);
+
+        for (_, e) in &plan.unfixable {
+            status_warn!("{}", e);
+        }
+
         for vulnerability in &report.vulnerabilities.list {
-            if let Err(e) = fixer.fix(vulnerability, dry_run) {
-                status_warn!("{}", e);
+            let package = &vulnerability.package.name;
+            if !plan.remediations.iter().any(|r| &r.package == package)
+                && !plan.unfixable.iter().any(|(name, _)| name == package)
+            {
+                status_warn!(
+                    "no fixed version available for {} ({})",
+                    vulnerability.package.name,
+                    vulnerability.advisory.id
+                );
+            }
+        }
+
+        for remediation in &plan.remediations {
+            if remediation.breaking {
+                status_warn!(
+                    "upgrading {} from {} to {} is a breaking change",
+                    remediation.package,
+                    remediation.from,
+                    remediation.to
+                );
+            }
+
+            match fixer.apply(remediation, dry_run) {
+                Ok(skipped) => {
+                    for manifest in skipped {
+                        status_warn!(
+                            "cannot auto-fix non-registry dependency {} in {}",
+                            remediation.package,
+                            manifest.display()
+                        );
+                    }
+                }
+                Err(e) => status_warn!("{}", e),
             }
         }
 
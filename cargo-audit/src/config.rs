@@ -94,9 +94,9 @@ impl AuditConfig {
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct AdvisoryConfig {
-    /// Ignore advisories for the given IDs
+    /// Ignore advisories for the given IDs, optionally until a given date
     #[serde(default)]
-    pub ignore: Vec<advisory::Id>,
+    pub ignore: Vec<report::IgnoredAdvisory>,
 
     /// Ignore the source of this advisory, matching any package of the same name.
     #[serde(default)]
@@ -273,6 +273,10 @@ impl Presenter {
             );
         }
 
+        if let Some(mitigation) = &vulnerability.advisory.mitigation {
+            self.print_attr(Red, "Mitigation: ", mitigation);
+        }
+
         self.print_tree(Red, &vulnerability.package, tree);
         println!();
     }
@@ -329,7 +333,7 @@ impl Presenter {
             self.print_attr(
                 color,
                 "Severity: ",
-                format!("{} ({})", cvss.score().value(), cvss.score().severity()),
+                format!("{} ({})", cvss.score(), cvss.severity()),
             );
         }
     }
@@ -0,0 +1,113 @@
+//! CVSS vectors of any supported version
+
+use crate::{v3, v4, Error, Result, PREFIX};
+use alloc::borrow::ToOwned;
+use core::{fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use {
+    alloc::string::{String, ToString},
+    serde::{de, ser, Deserialize, Serialize},
+};
+
+#[cfg(feature = "std")]
+use crate::Severity;
+
+/// CVSS vector of any supported version, as identified by the version in the
+/// vector string's prefix (e.g. `CVSS:3.1` or `CVSS:4.0`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Cvss {
+    /// CVSS v3.0 or v3.1 Base Metric Group
+    V3(v3::Base),
+
+    /// CVSS v4.0 vector
+    V4(v4::Vector),
+}
+
+impl Cvss {
+    /// Calculate the numeric score of the vector.
+    ///
+    /// For v3 this is the Base score, and for v4 the CVSS-BTE score.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn score(&self) -> f64 {
+        match self {
+            Cvss::V3(base) => base.score().value(),
+            Cvss::V4(vector) => vector.score().value(),
+        }
+    }
+
+    /// Calculate the `Severity` according to the
+    /// Qualitative Severity Rating Scale (i.e. Low / Medium / High / Critical)
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn severity(&self) -> Severity {
+        match self {
+            Cvss::V3(base) => base.severity(),
+            Cvss::V4(vector) => vector.severity(),
+        }
+    }
+}
+
+impl From<v3::Base> for Cvss {
+    fn from(base: v3::Base) -> Self {
+        Cvss::V3(base)
+    }
+}
+
+impl From<v4::Vector> for Cvss {
+    fn from(vector: v4::Vector) -> Self {
+        Cvss::V4(vector)
+    }
+}
+
+impl fmt::Display for Cvss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cvss::V3(base) => base.fmt(f),
+            Cvss::V4(vector) => vector.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Cvss {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let prefix = s.split('/').next().unwrap_or_default();
+
+        match prefix.split_once(':') {
+            Some((PREFIX, version)) if version.starts_with("3.") => s.parse().map(Cvss::V3),
+            Some((PREFIX, version)) if version.starts_with("4.") => s.parse().map(Cvss::V4),
+            Some((PREFIX, version)) => Err(Error::UnsupportedVersion {
+                version: version.to_owned(),
+            }),
+            _ => Err(Error::InvalidPrefix {
+                prefix: prefix.to_owned(),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for Cvss {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for Cvss {
+    fn serialize<S: ser::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
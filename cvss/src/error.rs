@@ -26,6 +26,30 @@ pub enum Error {
         value: String,
     },
 
+    /// Invalid CVSS v4.0 metric.
+    #[cfg(feature = "v4")]
+    InvalidMetricV4 {
+        /// The metric that was invalid.
+        metric_type: crate::v4::MetricType,
+
+        /// The value that was provided which is invalid.
+        value: String,
+    },
+
+    /// Metric which was specified more than once in a CVSS v4.0 vector.
+    #[cfg(feature = "v4")]
+    DuplicateMetricV4 {
+        /// The metric that was duplicated.
+        metric_type: crate::v4::MetricType,
+    },
+
+    /// Mandatory metric which is missing from a CVSS v4.0 vector.
+    #[cfg(feature = "v4")]
+    MissingMandatoryMetricV4 {
+        /// The metric that was missing.
+        metric_type: crate::v4::MetricType,
+    },
+
     /// Invalid CVSS string prefix.
     InvalidPrefix {
         /// Prefix which is invalid.
@@ -66,6 +90,34 @@ impl fmt::Display for Error {
                     value
                 )
             }
+            #[cfg(feature = "v4")]
+            Error::InvalidMetricV4 { metric_type, value } => {
+                write!(
+                    f,
+                    "invalid CVSS v4 {} ({}) metric: `{}`",
+                    metric_type.name(),
+                    metric_type.description(),
+                    value
+                )
+            }
+            #[cfg(feature = "v4")]
+            Error::DuplicateMetricV4 { metric_type } => {
+                write!(
+                    f,
+                    "duplicate CVSS v4 {} ({}) metric",
+                    metric_type.name(),
+                    metric_type.description()
+                )
+            }
+            #[cfg(feature = "v4")]
+            Error::MissingMandatoryMetricV4 { metric_type } => {
+                write!(
+                    f,
+                    "missing mandatory CVSS v4 {} ({}) metric",
+                    metric_type.name(),
+                    metric_type.description()
+                )
+            }
             Error::InvalidPrefix { prefix } => {
                 write!(f, "invalid CVSS string prefix: `{}`", prefix)
             }
@@ -7,16 +7,24 @@
 
 //! ## Usage
 //!
-//! The [`v3::Base`] type provides the main functionality currently implemented
-//! by this crate, namely: support for parsing, serializing, and scoring
-//! `CVSS:3.0` and `CVSS:3.1` Base Metric Group vector strings as described in
-//! the [CVSS v3.1 Specification].
+//! The [`v3::Base`] type provides support for parsing, serializing, and
+//! scoring `CVSS:3.0` and `CVSS:3.1` Base Metric Group vector strings as
+//! described in the [CVSS v3.1 Specification].
+//!
+//! The [`v4::Vector`] type does the same for `CVSS:4.0` vector strings,
+//! including the Threat, Environmental and Supplemental Metric Groups, as
+//! described in the [CVSS v4.0 Specification].
+//!
+//! The [`Cvss`] type parses vector strings of either version, based on their
+//! prefix.
+//!
+//! The [`v3::Environmental`] type holds the Temporal and Environmental Metric
+//! Groups of CVSS v3.1 separately, so they can be applied to Base vectors.
 //!
 //! Serde support is available through the optional `serde` Cargo feature.
 //!
 //! [CVSS v3.1 Specification]: https://www.first.org/cvss/specification-document
-
-// TODO(tarcieri): other CVSS versions, CVSS v3.1 Temporal and Environmental Groups
+//! [CVSS v4.0 Specification]: https://www.first.org/cvss/v4.0/specification-document
 
 extern crate alloc;
 
@@ -25,6 +33,11 @@ extern crate std;
 
 #[cfg(feature = "v3")]
 pub mod v3;
+#[cfg(feature = "v4")]
+pub mod v4;
+
+#[cfg(all(feature = "v3", feature = "v4"))]
+mod cvss;
 
 mod error;
 mod metric;
@@ -36,5 +49,8 @@ pub use crate::{
     severity::Severity,
 };
 
+#[cfg(all(feature = "v3", feature = "v4"))]
+pub use crate::cvss::Cvss;
+
 /// Prefix used by all CVSS strings
 pub const PREFIX: &str = "CVSS";
@@ -2,10 +2,9 @@
 //!
 //! <https://www.first.org/cvss/specification-document>
 
-// TODO(tarcieri): Environmental and Temporal Metrics
-
 pub mod base;
 
+mod environmental;
 mod score;
 
-pub use self::{base::Base, score::Score};
+pub use self::{base::Base, environmental::Environmental, score::Score};
@@ -0,0 +1,246 @@
+//! CVSS v3.1 Temporal and Environmental Metric Groups
+
+use crate::{Error, Result};
+use alloc::borrow::ToOwned;
+use core::{fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use {
+    alloc::string::{String, ToString},
+    serde::{de, ser, Deserialize, Serialize},
+};
+
+#[cfg(feature = "std")]
+use {
+    super::{Base, Score},
+    crate::{Metric, Severity},
+};
+
+/// Temporal and Environmental metrics with the values they can take, in the
+/// order they appear in vector strings. `X` means "Not Defined".
+const METRICS: [(&str, &[&str]); 14] = [
+    ("E", &["X", "U", "P", "F", "H"]),
+    ("RL", &["X", "O", "T", "W", "U"]),
+    ("RC", &["X", "U", "R", "C"]),
+    ("CR", &["X", "L", "M", "H"]),
+    ("IR", &["X", "L", "M", "H"]),
+    ("AR", &["X", "L", "M", "H"]),
+    ("MAV", &["X", "N", "A", "L", "P"]),
+    ("MAC", &["X", "L", "H"]),
+    ("MPR", &["X", "N", "L", "H"]),
+    ("MUI", &["X", "N", "R"]),
+    ("MS", &["X", "U", "C"]),
+    ("MC", &["X", "N", "L", "H"]),
+    ("MI", &["X", "N", "L", "H"]),
+    ("MA", &["X", "N", "L", "H"]),
+];
+
+/// CVSS v3.1 Temporal and Environmental Metric Groups
+///
+/// Described in CVSS v3.1 Specification: Sections 3 and 4:
+/// <https://www.first.org/cvss/specification-document#Temporal-Metrics>
+///
+/// > The Temporal metrics measure the current state of exploit techniques or
+/// > code availability, the existence of any patches or workarounds, or the
+/// > confidence in the description of a vulnerability.
+/// >
+/// > These metrics enable the analyst to customize the CVSS score depending on
+/// > the importance of the affected IT asset to a user’s organization,
+/// > measured in terms of complementary/alternative security controls in
+/// > place, Confidentiality, Integrity, and Availability.
+///
+/// These metrics are kept apart from the [`Base`](super::Base) vector they're applied to,
+/// e.g. to adjust the scores of third-party advisories to a particular
+/// environment. They're written like the metrics of a vector string, without
+/// the `CVSS:3.1` prefix, e.g. `E:P/MAV:L/CR:H`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Environmental {
+    values: [Option<&'static str>; METRICS.len()],
+}
+
+impl Environmental {
+    /// Get the value of the metric with the given name (e.g. `MAV`), if it
+    /// was specified.
+    pub fn get(&self, name: &str) -> Option<&'static str> {
+        METRICS
+            .iter()
+            .position(|&(metric, _)| metric == name)
+            .and_then(|index| self.values[index])
+    }
+
+    /// Are no metrics specified?
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(Option::is_none)
+    }
+
+    /// Calculate the Environmental score of the given Base vector, which also
+    /// takes the Temporal metrics into account. Metrics which aren't
+    /// specified, or are "Not Defined", leave the score unchanged.
+    ///
+    /// Described in CVSS v3.1 Specification: Section 7.3:
+    /// <https://www.first.org/cvss/specification-document#7-3-Environmental-Metrics-Equations>
+    ///
+    /// The CVSS v3.1 equations are used for `CVSS:3.0` vectors as well.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn score(&self, base: &Base) -> Score {
+        let scope_changed = self
+            .modified("MS", base.s)
+            .map(|s| s.is_changed())
+            .unwrap_or(false);
+
+        let pr_score = self
+            .modified("MPR", base.pr)
+            .map(|pr| pr.scoped_score(scope_changed))
+            .unwrap_or(0.0);
+        let exploitability = 8.22
+            * metric_score(self.modified("MAV", base.av))
+            * metric_score(self.modified("MAC", base.ac))
+            * pr_score
+            * metric_score(self.modified("MUI", base.ui));
+
+        let miss = (1.0
+            - (1.0 - self.multiplier("CR") * metric_score(self.modified("MC", base.c)))
+                * (1.0 - self.multiplier("IR") * metric_score(self.modified("MI", base.i)))
+                * (1.0 - self.multiplier("AR") * metric_score(self.modified("MA", base.a))))
+        .min(0.915);
+
+        let impact = if !scope_changed {
+            6.42 * miss
+        } else {
+            7.52 * (miss - 0.029) - 3.25 * (miss * 0.9731 - 0.02).powf(13.0)
+        };
+
+        if impact <= 0.0 {
+            return Score::new(0.0);
+        }
+
+        let score = if !scope_changed {
+            (impact + exploitability).min(10.0)
+        } else {
+            (1.08 * (impact + exploitability)).min(10.0)
+        };
+
+        Score::new(
+            Score::new(score).roundup().value()
+                * self.multiplier("E")
+                * self.multiplier("RL")
+                * self.multiplier("RC"),
+        )
+        .roundup()
+    }
+
+    /// Calculate the Environmental `Severity` of the given Base vector
+    /// according to the Qualitative Severity Rating Scale
+    /// (i.e. Low / Medium / High / Critical)
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn severity(&self, base: &Base) -> Severity {
+        self.score(base).severity()
+    }
+
+    /// Value of a Base metric as used for scoring: the modified value if one
+    /// was given, and the Base metric value otherwise
+    #[cfg(feature = "std")]
+    fn modified<M: Metric>(&self, name: &str, base: Option<M>) -> Option<M> {
+        match self.get(name) {
+            Some(value) if value != "X" => value.parse().ok(),
+            _ => base,
+        }
+    }
+
+    /// Multiplier of a Temporal metric or Security Requirement
+    #[cfg(feature = "std")]
+    fn multiplier(&self, name: &str) -> f64 {
+        match (name, self.get(name).unwrap_or_default()) {
+            ("E", "U") => 0.91,
+            ("E", "P") => 0.94,
+            ("E", "F") => 0.97,
+            ("RL", "O") => 0.95,
+            ("RL", "T") => 0.96,
+            ("RL", "W") => 0.97,
+            ("RC", "U") => 0.92,
+            ("RC", "R") => 0.96,
+            ("CR" | "IR" | "AR", "L") => 0.5,
+            ("CR" | "IR" | "AR", "H") => 1.5,
+            _ => 1.0,
+        }
+    }
+}
+
+impl fmt::Display for Environmental {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+
+        for ((name, _), value) in METRICS.iter().zip(&self.values) {
+            if let Some(value) = value {
+                write!(f, "{}{}:{}", separator, name, value)?;
+                separator = "/";
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Environmental {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut metrics = Self::default();
+
+        for component in s.split('/').filter(|component| !component.is_empty()) {
+            let invalid = || Error::InvalidComponent {
+                component: component.to_owned(),
+            };
+
+            let (name, value) = component.split_once(':').ok_or_else(invalid)?;
+            let index = METRICS
+                .iter()
+                .position(|&(metric, _)| metric == name)
+                .ok_or_else(|| Error::UnknownMetric {
+                    name: name.to_owned(),
+                })?;
+            let value = METRICS[index]
+                .1
+                .iter()
+                .find(|&&allowed| allowed == value)
+                .ok_or_else(invalid)?;
+
+            if metrics.values[index].replace(value).is_some() {
+                return Err(invalid());
+            }
+        }
+
+        Ok(metrics)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for Environmental {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for Environmental {
+    fn serialize<S: ser::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
+
+/// Score of the given metric, if any
+#[cfg(feature = "std")]
+fn metric_score<M: Metric>(metric: Option<M>) -> f64 {
+    metric.map(Metric::score).unwrap_or(0.0)
+}
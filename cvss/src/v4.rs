@@ -0,0 +1,14 @@
+//! Common Vulnerability Scoring System (v4.0)
+//!
+//! <https://www.first.org/cvss/v4.0/specification-document>
+
+mod metric;
+mod score;
+mod vector;
+
+#[cfg(feature = "std")]
+mod lookup;
+#[cfg(feature = "std")]
+mod scoring;
+
+pub use self::{metric::MetricType, score::Score, vector::Vector};
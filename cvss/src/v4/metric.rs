@@ -0,0 +1,298 @@
+//! CVSS v4.0 metrics
+
+use crate::{Error, Result};
+use alloc::borrow::ToOwned;
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Metrics of the CVSS v4.0 Base, Threat, Environmental and Supplemental
+/// Metric Groups.
+///
+/// Described in CVSS v4.0 Specification: Section 2:
+/// <https://www.first.org/cvss/v4.0/specification-document>
+///
+/// Metrics are ordered as they appear in vector strings.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum MetricType {
+    /// Attack Vector (AV)
+    AV,
+
+    /// Attack Complexity (AC)
+    AC,
+
+    /// Attack Requirements (AT)
+    AT,
+
+    /// Privileges Required (PR)
+    PR,
+
+    /// User Interaction (UI)
+    UI,
+
+    /// Vulnerable System Confidentiality Impact (VC)
+    VC,
+
+    /// Vulnerable System Integrity Impact (VI)
+    VI,
+
+    /// Vulnerable System Availability Impact (VA)
+    VA,
+
+    /// Subsequent System Confidentiality Impact (SC)
+    SC,
+
+    /// Subsequent System Integrity Impact (SI)
+    SI,
+
+    /// Subsequent System Availability Impact (SA)
+    SA,
+
+    /// Exploit Maturity (E)
+    E,
+
+    /// Confidentiality Requirement (CR)
+    CR,
+
+    /// Integrity Requirement (IR)
+    IR,
+
+    /// Availability Requirement (AR)
+    AR,
+
+    /// Modified Attack Vector (MAV)
+    MAV,
+
+    /// Modified Attack Complexity (MAC)
+    MAC,
+
+    /// Modified Attack Requirements (MAT)
+    MAT,
+
+    /// Modified Privileges Required (MPR)
+    MPR,
+
+    /// Modified User Interaction (MUI)
+    MUI,
+
+    /// Modified Vulnerable System Confidentiality Impact (MVC)
+    MVC,
+
+    /// Modified Vulnerable System Integrity Impact (MVI)
+    MVI,
+
+    /// Modified Vulnerable System Availability Impact (MVA)
+    MVA,
+
+    /// Modified Subsequent System Confidentiality Impact (MSC)
+    MSC,
+
+    /// Modified Subsequent System Integrity Impact (MSI)
+    MSI,
+
+    /// Modified Subsequent System Availability Impact (MSA)
+    MSA,
+
+    /// Safety (S)
+    S,
+
+    /// Automatable (AU)
+    AU,
+
+    /// Recovery (R)
+    R,
+
+    /// Value Density (V)
+    V,
+
+    /// Vulnerability Response Effort (RE)
+    RE,
+
+    /// Provider Urgency (U)
+    U,
+}
+
+impl MetricType {
+    /// All metrics, in the order they appear in vector strings
+    pub const ALL: &'static [MetricType] = &[
+        Self::AV,
+        Self::AC,
+        Self::AT,
+        Self::PR,
+        Self::UI,
+        Self::VC,
+        Self::VI,
+        Self::VA,
+        Self::SC,
+        Self::SI,
+        Self::SA,
+        Self::E,
+        Self::CR,
+        Self::IR,
+        Self::AR,
+        Self::MAV,
+        Self::MAC,
+        Self::MAT,
+        Self::MPR,
+        Self::MUI,
+        Self::MVC,
+        Self::MVI,
+        Self::MVA,
+        Self::MSC,
+        Self::MSI,
+        Self::MSA,
+        Self::S,
+        Self::AU,
+        Self::R,
+        Self::V,
+        Self::RE,
+        Self::U,
+    ];
+
+    /// Get the name of this metric (i.e. acronym)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::AV => "AV",
+            Self::AC => "AC",
+            Self::AT => "AT",
+            Self::PR => "PR",
+            Self::UI => "UI",
+            Self::VC => "VC",
+            Self::VI => "VI",
+            Self::VA => "VA",
+            Self::SC => "SC",
+            Self::SI => "SI",
+            Self::SA => "SA",
+            Self::E => "E",
+            Self::CR => "CR",
+            Self::IR => "IR",
+            Self::AR => "AR",
+            Self::MAV => "MAV",
+            Self::MAC => "MAC",
+            Self::MAT => "MAT",
+            Self::MPR => "MPR",
+            Self::MUI => "MUI",
+            Self::MVC => "MVC",
+            Self::MVI => "MVI",
+            Self::MVA => "MVA",
+            Self::MSC => "MSC",
+            Self::MSI => "MSI",
+            Self::MSA => "MSA",
+            Self::S => "S",
+            Self::AU => "AU",
+            Self::R => "R",
+            Self::V => "V",
+            Self::RE => "RE",
+            Self::U => "U",
+        }
+    }
+
+    /// Get a description of this metric.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::AV => "Attack Vector",
+            Self::AC => "Attack Complexity",
+            Self::AT => "Attack Requirements",
+            Self::PR => "Privileges Required",
+            Self::UI => "User Interaction",
+            Self::VC => "Vulnerable System Confidentiality Impact",
+            Self::VI => "Vulnerable System Integrity Impact",
+            Self::VA => "Vulnerable System Availability Impact",
+            Self::SC => "Subsequent System Confidentiality Impact",
+            Self::SI => "Subsequent System Integrity Impact",
+            Self::SA => "Subsequent System Availability Impact",
+            Self::E => "Exploit Maturity",
+            Self::CR => "Confidentiality Requirement",
+            Self::IR => "Integrity Requirement",
+            Self::AR => "Availability Requirement",
+            Self::MAV => "Modified Attack Vector",
+            Self::MAC => "Modified Attack Complexity",
+            Self::MAT => "Modified Attack Requirements",
+            Self::MPR => "Modified Privileges Required",
+            Self::MUI => "Modified User Interaction",
+            Self::MVC => "Modified Vulnerable System Confidentiality Impact",
+            Self::MVI => "Modified Vulnerable System Integrity Impact",
+            Self::MVA => "Modified Vulnerable System Availability Impact",
+            Self::MSC => "Modified Subsequent System Confidentiality Impact",
+            Self::MSI => "Modified Subsequent System Integrity Impact",
+            Self::MSA => "Modified Subsequent System Availability Impact",
+            Self::S => "Safety",
+            Self::AU => "Automatable",
+            Self::R => "Recovery",
+            Self::V => "Value Density",
+            Self::RE => "Vulnerability Response Effort",
+            Self::U => "Provider Urgency",
+        }
+    }
+
+    /// Is this a Base metric, which must be present in every vector?
+    pub fn is_mandatory(self) -> bool {
+        self <= Self::SA
+    }
+
+    /// Values this metric can take, where `X` means "Not Defined"
+    pub fn values(self) -> &'static [&'static str] {
+        match self {
+            Self::AV => &["N", "A", "L", "P"],
+            Self::AC => &["L", "H"],
+            Self::AT => &["N", "P"],
+            Self::PR => &["N", "L", "H"],
+            Self::UI => &["N", "P", "A"],
+            Self::VC | Self::VI | Self::VA | Self::SC | Self::SI | Self::SA => &["H", "L", "N"],
+            Self::E => &["X", "A", "P", "U"],
+            Self::CR | Self::IR | Self::AR => &["X", "H", "M", "L"],
+            Self::MAV => &["X", "N", "A", "L", "P"],
+            Self::MAC => &["X", "L", "H"],
+            Self::MAT => &["X", "N", "P"],
+            Self::MPR => &["X", "N", "L", "H"],
+            Self::MUI => &["X", "N", "P", "A"],
+            Self::MVC | Self::MVI | Self::MVA | Self::MSC => &["X", "H", "L", "N"],
+            Self::MSI | Self::MSA => &["X", "S", "H", "L", "N"],
+            Self::S => &["X", "N", "P"],
+            Self::AU => &["X", "N", "Y"],
+            Self::R => &["X", "A", "U", "I"],
+            Self::V => &["X", "D", "C"],
+            Self::RE => &["X", "L", "M", "H"],
+            Self::U => &["X", "Clear", "Green", "Amber", "Red"],
+        }
+    }
+
+    /// Environmental metric which overrides this Base metric, if any
+    #[cfg(feature = "std")]
+    pub(crate) fn modified(self) -> Option<Self> {
+        match self {
+            Self::AV => Some(Self::MAV),
+            Self::AC => Some(Self::MAC),
+            Self::AT => Some(Self::MAT),
+            Self::PR => Some(Self::MPR),
+            Self::UI => Some(Self::MUI),
+            Self::VC => Some(Self::MVC),
+            Self::VI => Some(Self::MVI),
+            Self::VA => Some(Self::MVA),
+            Self::SC => Some(Self::MSC),
+            Self::SI => Some(Self::MSI),
+            Self::SA => Some(Self::MSA),
+            _ => None,
+        }
+    }
+}
+
+impl Display for MetricType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for MetricType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|metric| metric.name() == s)
+            .copied()
+            .ok_or_else(|| Error::UnknownMetric { name: s.to_owned() })
+    }
+}
@@ -0,0 +1,57 @@
+//! CVSS v4.0 scores
+
+use crate::severity::Severity;
+
+/// CVSS v4.0 scores.
+///
+/// Described in CVSS v4.0 Specification: Section 8:
+/// <https://www.first.org/cvss/v4.0/specification-document#CVSS-v4-0-Scoring>
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Score(f64);
+
+impl Score {
+    /// Create a new score object
+    pub fn new(score: f64) -> Score {
+        Score(score)
+    }
+
+    /// Get the score as a floating point value
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    /// Convert the numeric score into a `Severity`
+    ///
+    /// CVSS v4.0 uses the same Qualitative Severity Rating Scale as v3.1.
+    pub fn severity(self) -> Severity {
+        if self.0 < 0.1 {
+            Severity::None
+        } else if self.0 < 4.0 {
+            Severity::Low
+        } else if self.0 < 7.0 {
+            Severity::Medium
+        } else if self.0 < 9.0 {
+            Severity::High
+        } else {
+            Severity::Critical
+        }
+    }
+}
+
+impl From<f64> for Score {
+    fn from(score: f64) -> Score {
+        Score(score)
+    }
+}
+
+impl From<Score> for f64 {
+    fn from(score: Score) -> f64 {
+        score.value()
+    }
+}
+
+impl From<Score> for Severity {
+    fn from(score: Score) -> Severity {
+        score.severity()
+    }
+}
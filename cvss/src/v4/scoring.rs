@@ -0,0 +1,323 @@
+//! CVSS v4.0 scoring algorithm
+//!
+//! Vectors are grouped into macro vectors by six equivalence sets (EQ1 to EQ6)
+//! whose scores were determined by experts. The score of a vector is the
+//! score of its macro vector, lowered by how far the vector is from the
+//! highest-severity vectors of that macro vector.
+//!
+//! This follows the reference implementation:
+//! <https://github.com/FIRSTdotorg/cvss-v4-calculator>
+
+use super::{lookup::MACRO_VECTOR_SCORES, MetricType, Vector};
+
+/// Metrics which are compared against the highest-severity vectors
+const DISTANCE_METRICS: [MetricType; 14] = [
+    MetricType::AV,
+    MetricType::PR,
+    MetricType::UI,
+    MetricType::AC,
+    MetricType::AT,
+    MetricType::VC,
+    MetricType::VI,
+    MetricType::VA,
+    MetricType::SC,
+    MetricType::SI,
+    MetricType::SA,
+    MetricType::CR,
+    MetricType::IR,
+    MetricType::AR,
+];
+
+/// Highest-severity vectors of each level of EQ1
+const MAX_COMPOSED_EQ1: [&[&str]; 3] = [
+    &["AV:N/PR:N/UI:N/"],
+    &["AV:A/PR:N/UI:N/", "AV:N/PR:L/UI:N/", "AV:N/PR:N/UI:P/"],
+    &["AV:P/PR:N/UI:N/", "AV:A/PR:L/UI:P/"],
+];
+
+/// Highest-severity vectors of each level of EQ2
+const MAX_COMPOSED_EQ2: [&[&str]; 2] = [&["AC:L/AT:N/"], &["AC:H/AT:N/", "AC:L/AT:P/"]];
+
+/// Highest-severity vectors of each level of EQ3 and EQ6 combined
+fn max_composed_eq3_eq6(eq3: u8, eq6: u8) -> &'static [&'static str] {
+    match (eq3, eq6) {
+        (0, 0) => &["VC:H/VI:H/VA:H/CR:H/IR:H/AR:H/"],
+        (0, _) => &[
+            "VC:H/VI:H/VA:L/CR:M/IR:M/AR:H/",
+            "VC:H/VI:H/VA:H/CR:M/IR:M/AR:M/",
+        ],
+        (1, 0) => &[
+            "VC:L/VI:H/VA:H/CR:H/IR:H/AR:H/",
+            "VC:H/VI:L/VA:H/CR:H/IR:H/AR:H/",
+        ],
+        (1, _) => &[
+            "VC:L/VI:H/VA:L/CR:H/IR:M/AR:H/",
+            "VC:L/VI:H/VA:H/CR:H/IR:M/AR:M/",
+            "VC:H/VI:L/VA:H/CR:M/IR:H/AR:M/",
+            "VC:H/VI:L/VA:L/CR:M/IR:H/AR:H/",
+            "VC:L/VI:L/VA:H/CR:H/IR:H/AR:M/",
+        ],
+        _ => &["VC:L/VI:L/VA:L/CR:H/IR:H/AR:H/"],
+    }
+}
+
+/// Highest-severity vectors of each level of EQ4
+const MAX_COMPOSED_EQ4: [&[&str]; 3] = [
+    &["SC:H/SI:S/SA:S/"],
+    &["SC:H/SI:H/SA:H/"],
+    &["SC:L/SI:L/SA:L/"],
+];
+
+/// Highest-severity vectors of each level of EQ5
+const MAX_COMPOSED_EQ5: [&[&str]; 3] = [&["E:A/"], &["E:P/"], &["E:U/"]];
+
+/// Severity distance between the highest and lowest severity vectors of each
+/// level of EQ1, in steps of 0.1
+const MAX_SEVERITY_EQ1: [u8; 3] = [1, 4, 5];
+
+/// Severity distance within each level of EQ2
+const MAX_SEVERITY_EQ2: [u8; 2] = [1, 2];
+
+/// Severity distance within each level of EQ3 and EQ6 combined
+fn max_severity_eq3_eq6(eq3: u8, eq6: u8) -> u8 {
+    match (eq3, eq6) {
+        (0, 0) => 7,
+        (0, _) => 6,
+        (1, _) => 8,
+        _ => 10,
+    }
+}
+
+/// Severity distance within each level of EQ4
+const MAX_SEVERITY_EQ4: [u8; 3] = [6, 5, 4];
+
+/// Calculate the score of the given vector
+pub(super) fn score(vector: &Vector) -> f64 {
+    let m = |metric| vector.effective(metric);
+
+    // Vectors without any impact always score 0.0
+    if [
+        MetricType::VC,
+        MetricType::VI,
+        MetricType::VA,
+        MetricType::SC,
+        MetricType::SI,
+        MetricType::SA,
+    ]
+    .iter()
+    .all(|&metric| m(metric) == "N")
+    {
+        return 0.0;
+    }
+
+    let macro_vector = macro_vector(vector);
+    let [eq1, eq2, eq3, eq4, eq5, eq6] = macro_vector;
+    let value = lookup(macro_vector).expect("all macro vectors have a score");
+
+    // Scores of the next lower macro vectors of each equivalence set
+    let lower = |eq: usize| {
+        let mut lower = macro_vector;
+        lower[eq] += 1;
+        lookup(lower)
+    };
+
+    let lower_eq3_eq6 = match (eq3, eq6) {
+        (0, 0) => match (lower(2), lower(5)) {
+            (Some(eq3), Some(eq6)) => Some(eq3.max(eq6)),
+            (eq3, eq6) => eq3.or(eq6),
+        },
+        (1, 0) => lower(5),
+        _ => lower(2),
+    };
+
+    // Find the first highest-severity vector of the macro vector which is at
+    // least as severe as the given vector in every metric
+    let mut distances = [0.0; 14];
+
+    'search: for eq1_max in MAX_COMPOSED_EQ1[eq1 as usize] {
+        for eq2_max in MAX_COMPOSED_EQ2[eq2 as usize] {
+            for eq3_eq6_max in max_composed_eq3_eq6(eq3, eq6) {
+                for eq4_max in MAX_COMPOSED_EQ4[eq4 as usize] {
+                    for eq5_max in MAX_COMPOSED_EQ5[eq5 as usize] {
+                        let max_vector = [*eq1_max, eq2_max, eq3_eq6_max, eq4_max, eq5_max];
+
+                        for (distance, &metric) in distances.iter_mut().zip(&DISTANCE_METRICS) {
+                            *distance = level(metric, m(metric))
+                                - level(metric, max_value(&max_vector, metric));
+                        }
+
+                        if distances.iter().all(|&distance| distance >= 0.0) {
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let distance = |metrics: &[MetricType]| -> f64 {
+        DISTANCE_METRICS
+            .iter()
+            .zip(&distances)
+            .filter(|(metric, _)| metrics.contains(metric))
+            .map(|(_, distance)| distance)
+            .sum()
+    };
+
+    let step = 0.1;
+    let eqs = [
+        (
+            lower(0),
+            distance(&[MetricType::AV, MetricType::PR, MetricType::UI]),
+            f64::from(MAX_SEVERITY_EQ1[eq1 as usize]) * step,
+        ),
+        (
+            lower(1),
+            distance(&[MetricType::AC, MetricType::AT]),
+            f64::from(MAX_SEVERITY_EQ2[eq2 as usize]) * step,
+        ),
+        (
+            lower_eq3_eq6,
+            distance(&[
+                MetricType::VC,
+                MetricType::VI,
+                MetricType::VA,
+                MetricType::CR,
+                MetricType::IR,
+                MetricType::AR,
+            ]),
+            f64::from(max_severity_eq3_eq6(eq3, eq6)) * step,
+        ),
+        (
+            lower(3),
+            distance(&[MetricType::SC, MetricType::SI, MetricType::SA]),
+            f64::from(MAX_SEVERITY_EQ4[eq4 as usize]) * step,
+        ),
+        // The vectors of each EQ5 level are all equally severe
+        (lower(4), 0.0, 1.0),
+    ];
+
+    let mut existing_lower = 0;
+    let mut normalized_distance = 0.0;
+
+    for (lower_value, distance, max_severity) in eqs {
+        if let Some(lower_value) = lower_value {
+            existing_lower += 1;
+            normalized_distance += (value - lower_value) * (distance / max_severity);
+        }
+    }
+
+    let mean_distance = if existing_lower == 0 {
+        0.0
+    } else {
+        normalized_distance / f64::from(existing_lower)
+    };
+
+    let score = (value - mean_distance).clamp(0.0, 10.0);
+    ((score + 1e-6) * 10.0).round() / 10.0
+}
+
+/// Levels of the equivalence sets EQ1 to EQ6 of the given vector
+fn macro_vector(vector: &Vector) -> [u8; 6] {
+    let m = |metric| vector.effective(metric);
+
+    let (av, pr, ui) = (m(MetricType::AV), m(MetricType::PR), m(MetricType::UI));
+    let eq1 = if av == "N" && pr == "N" && ui == "N" {
+        0
+    } else if (av == "N" || pr == "N" || ui == "N") && av != "P" {
+        1
+    } else {
+        2
+    };
+
+    let eq2 = if m(MetricType::AC) == "L" && m(MetricType::AT) == "N" {
+        0
+    } else {
+        1
+    };
+
+    let (vc, vi, va) = (m(MetricType::VC), m(MetricType::VI), m(MetricType::VA));
+    let eq3 = if vc == "H" && vi == "H" {
+        0
+    } else if vc == "H" || vi == "H" || va == "H" {
+        1
+    } else {
+        2
+    };
+
+    let (sc, si, sa) = (m(MetricType::SC), m(MetricType::SI), m(MetricType::SA));
+    let eq4 = if si == "S" || sa == "S" {
+        0
+    } else if sc == "H" || si == "H" || sa == "H" {
+        1
+    } else {
+        2
+    };
+
+    let eq5 = match m(MetricType::E) {
+        "A" => 0,
+        "P" => 1,
+        _ => 2,
+    };
+
+    let eq6 = if (m(MetricType::CR) == "H" && vc == "H")
+        || (m(MetricType::IR) == "H" && vi == "H")
+        || (m(MetricType::AR) == "H" && va == "H")
+    {
+        0
+    } else {
+        1
+    };
+
+    [eq1, eq2, eq3, eq4, eq5, eq6]
+}
+
+/// Score of the given macro vector, if it exists
+fn lookup(macro_vector: [u8; 6]) -> Option<f64> {
+    let key = macro_vector.map(|level| b'0' + level);
+
+    MACRO_VECTOR_SCORES
+        .binary_search_by(|(candidate, _)| candidate.as_bytes().cmp(&key[..]))
+        .ok()
+        .map(|index| MACRO_VECTOR_SCORES[index].1)
+}
+
+/// Value of a metric in one of the highest-severity vectors, which is given
+/// as parts of a vector string
+fn max_value(max_vector: &[&'static str], metric: MetricType) -> &'static str {
+    max_vector
+        .iter()
+        .flat_map(|part| part.split('/'))
+        .filter_map(|component| component.split_once(':'))
+        .find(|&(name, _)| name == metric.name())
+        .map(|(_, value)| value)
+        .expect("highest-severity vectors contain all distance metrics")
+}
+
+/// Severity level of a metric value, where lower is more severe
+fn level(metric: MetricType, value: &str) -> f64 {
+    let level = match (metric, value) {
+        (MetricType::AV, "N") => 0,
+        (MetricType::AV, "A") => 1,
+        (MetricType::AV, "L") => 2,
+        (MetricType::AV, _) => 3,
+        (MetricType::PR | MetricType::UI, "N") => 0,
+        (MetricType::PR, "L") | (MetricType::UI, "P") => 1,
+        (MetricType::PR | MetricType::UI, _) => 2,
+        (MetricType::AC, "L") | (MetricType::AT, "N") => 0,
+        (MetricType::AC | MetricType::AT, _) => 1,
+        (MetricType::VC | MetricType::VI | MetricType::VA, "H") => 0,
+        (MetricType::VC | MetricType::VI | MetricType::VA, "L") => 1,
+        (MetricType::VC | MetricType::VI | MetricType::VA, _) => 2,
+        (MetricType::SI | MetricType::SA, "S") => 0,
+        (MetricType::SC | MetricType::SI | MetricType::SA, "H") => 1,
+        (MetricType::SC | MetricType::SI | MetricType::SA, "L") => 2,
+        (MetricType::SC | MetricType::SI | MetricType::SA, _) => 3,
+        (MetricType::CR | MetricType::IR | MetricType::AR, "H") => 0,
+        (MetricType::CR | MetricType::IR | MetricType::AR, "M") => 1,
+        (MetricType::CR | MetricType::IR | MetricType::AR, _) => 2,
+        _ => 0,
+    };
+
+    f64::from(level) * 0.1
+}
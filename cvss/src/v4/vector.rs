@@ -0,0 +1,175 @@
+//! CVSS v4.0 vectors
+
+use super::MetricType;
+use crate::{Error, Result, PREFIX};
+use alloc::{borrow::ToOwned, collections::BTreeMap};
+use core::{fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use {
+    alloc::string::{String, ToString},
+    serde::{de, ser, Deserialize, Serialize},
+};
+
+#[cfg(feature = "std")]
+use {super::Score, crate::Severity};
+
+/// CVSS v4.0 vector
+///
+/// Described in CVSS v4.0 Specification: Section 7:
+/// <https://www.first.org/cvss/v4.0/specification-document#Vector-String>
+///
+/// Contains all metrics of the Base Metric Group, which are mandatory, and
+/// any Threat, Environmental and Supplemental metrics which were specified.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Vector {
+    metrics: BTreeMap<MetricType, &'static str>,
+}
+
+impl Vector {
+    /// Get the value of the given metric, if it was specified.
+    ///
+    /// Values are the abbreviations used in vector strings, e.g. `N` for the
+    /// `Network` Attack Vector.
+    pub fn get(&self, metric: MetricType) -> Option<&'static str> {
+        self.metrics.get(&metric).copied()
+    }
+
+    /// Iterate over the specified metrics and their values, in the order of
+    /// the vector string.
+    pub fn iter(&self) -> impl Iterator<Item = (MetricType, &'static str)> + '_ {
+        self.metrics.iter().map(|(&metric, &value)| (metric, value))
+    }
+
+    /// Calculate the CVSS-BTE score, taking all Threat and Environmental
+    /// metrics into account. Supplemental metrics don't affect the score.
+    ///
+    /// Described in CVSS v4.0 Specification: Section 8:
+    /// <https://www.first.org/cvss/v4.0/specification-document#CVSS-v4-0-Scoring>
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn score(&self) -> Score {
+        Score::new(super::scoring::score(self))
+    }
+
+    /// Calculate the CVSS `Severity` according to the
+    /// Qualitative Severity Rating Scale (i.e. Low / Medium / High / Critical)
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn severity(&self) -> Severity {
+        self.score().severity()
+    }
+
+    /// Value of a metric as used for scoring: the modified value if one was
+    /// given in the Environmental Metric Group, the worst case if the metric
+    /// is "Not Defined", and the Base metric value otherwise.
+    #[cfg(feature = "std")]
+    pub(super) fn effective(&self, metric: MetricType) -> &'static str {
+        let value = match metric.modified().and_then(|modified| self.get(modified)) {
+            Some(value) if value != "X" => Some(value),
+            _ => self.get(metric),
+        };
+
+        match (metric, value) {
+            (MetricType::E, None | Some("X")) => "A",
+            (MetricType::CR | MetricType::IR | MetricType::AR, None | Some("X")) => "H",
+            (_, Some(value)) => value,
+            (_, None) => unreachable!("mandatory metric {} is missing", metric),
+        }
+    }
+}
+
+impl fmt::Display for Vector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:4.0", PREFIX)?;
+
+        for (metric, value) in self.iter() {
+            write!(f, "/{}:{}", metric, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Vector {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut components = s.split('/');
+
+        let prefix = components.next().unwrap_or_default();
+        let version = match prefix.split_once(':') {
+            Some((PREFIX, version)) => version,
+            _ => {
+                return Err(Error::InvalidPrefix {
+                    prefix: prefix.to_owned(),
+                })
+            }
+        };
+
+        if version != "4.0" {
+            return Err(Error::UnsupportedVersion {
+                version: version.to_owned(),
+            });
+        }
+
+        let mut metrics = BTreeMap::new();
+
+        for component in components {
+            let (name, value) =
+                component
+                    .split_once(':')
+                    .ok_or_else(|| Error::InvalidComponent {
+                        component: component.to_owned(),
+                    })?;
+
+            let metric = name.parse::<MetricType>()?;
+            let value = metric
+                .values()
+                .iter()
+                .find(|&&allowed| allowed == value)
+                .ok_or_else(|| Error::InvalidMetricV4 {
+                    metric_type: metric,
+                    value: value.to_owned(),
+                })?;
+
+            if metrics.insert(metric, *value).is_some() {
+                return Err(Error::DuplicateMetricV4 {
+                    metric_type: metric,
+                });
+            }
+        }
+
+        if let Some(&metric_type) = MetricType::ALL
+            .iter()
+            .find(|metric| metric.is_mandatory() && !metrics.contains_key(metric))
+        {
+            return Err(Error::MissingMandatoryMetricV4 { metric_type });
+        }
+
+        Ok(Self { metrics })
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for Vector {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for Vector {
+    fn serialize<S: ser::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
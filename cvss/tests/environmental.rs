@@ -0,0 +1,80 @@
+//! CVSS v3.1 Temporal and Environmental Metrics tests
+#![cfg(feature = "v3")]
+
+use cvss::{
+    v3::{Base, Environmental},
+    Error, Severity,
+};
+
+/// Network-exploitable vulnerability with high impact
+const BASE: &str = "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H";
+
+fn score(base: &str, metrics: &str) -> f64 {
+    let base: Base = base.parse().unwrap();
+    let metrics: Environmental = metrics.parse().unwrap();
+    metrics.score(&base).value()
+}
+
+#[test]
+fn parse_and_serialize() {
+    let metrics: Environmental = "MAV:L/E:P/CR:H".parse().unwrap();
+    assert_eq!(metrics.get("MAV"), Some("L"));
+    assert_eq!(metrics.get("RL"), None);
+
+    // Metrics are serialized in the order of the specification
+    assert_eq!(metrics.to_string(), "E:P/CR:H/MAV:L");
+
+    let empty: Environmental = "".parse().unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.to_string(), "");
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        "MAV:Q".parse::<Environmental>(),
+        Err(Error::InvalidComponent {
+            component: "MAV:Q".to_owned()
+        })
+    );
+    assert_eq!(
+        "AV:L".parse::<Environmental>(),
+        Err(Error::UnknownMetric {
+            name: "AV".to_owned()
+        })
+    );
+    assert!("E:P/E:U".parse::<Environmental>().is_err());
+}
+
+#[test]
+fn unmodified_score() {
+    // Without any metrics, or with all of them "Not Defined", the
+    // Environmental score is the Base score
+    assert_eq!(score(BASE, ""), 9.8);
+    assert_eq!(score(BASE, "E:X/RL:X/RC:X/CR:X/MAV:X/MS:X"), 9.8);
+    assert_eq!(
+        score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N", ""),
+        6.1
+    );
+}
+
+#[test]
+fn temporal_score() {
+    assert_eq!(score(BASE, "E:U/RL:O/RC:U"), 7.8);
+    assert_eq!(score(BASE, "E:H/RL:U/RC:C"), 9.8);
+}
+
+#[test]
+fn environmental_score() {
+    let base: Base = BASE.parse().unwrap();
+
+    // Modified Base metrics are scored like the Base metrics they replace
+    let metrics: Environmental = "MAV:L".parse().unwrap();
+    assert_eq!(metrics.score(&base).value(), 8.4);
+    assert_eq!(metrics.severity(&base), Severity::High);
+    assert_eq!(base.severity(), Severity::Critical);
+
+    // Security Requirements weigh the impact
+    assert_eq!(score(BASE, "MAV:L/MC:L/MI:N/MA:N/CR:H"), 4.7);
+    assert_eq!(score(BASE, "MC:N/MI:N/MA:N"), 0.0);
+}
@@ -0,0 +1,165 @@
+//! CVSS v4.0 tests
+#![cfg(feature = "v4")]
+
+use cvss::{
+    v4::{MetricType, Vector},
+    Error, Severity,
+};
+
+/// Vector with high impact on the vulnerable system only
+const BASE: &str = "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N";
+
+fn score(vector: &str) -> f64 {
+    vector.parse::<Vector>().unwrap().score().value()
+}
+
+#[test]
+fn parse_and_serialize() {
+    let vector: Vector = BASE.parse().unwrap();
+    assert_eq!(vector.to_string(), BASE);
+    assert_eq!(vector.get(MetricType::AV), Some("N"));
+    assert_eq!(vector.get(MetricType::E), None);
+
+    // Metrics are serialized in the order of the specification
+    let vector: Vector =
+        "CVSS:4.0/U:Amber/AV:N/AC:L/AT:N/PR:N/UI:N/E:P/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N"
+            .parse()
+            .unwrap();
+    assert_eq!(vector.to_string(), format!("{}/E:P/U:Amber", BASE));
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        "AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N".parse::<Vector>(),
+        Err(Error::InvalidPrefix {
+            prefix: "AV:N".to_owned()
+        })
+    );
+    assert_eq!(
+        BASE.replace("4.0", "3.1").parse::<Vector>(),
+        Err(Error::UnsupportedVersion {
+            version: "3.1".to_owned()
+        })
+    );
+    assert_eq!(
+        BASE.replace("AV:N", "AV:X").parse::<Vector>(),
+        Err(Error::InvalidMetricV4 {
+            metric_type: MetricType::AV,
+            value: "X".to_owned()
+        })
+    );
+    assert_eq!(
+        BASE.replace("/SA:N", "").parse::<Vector>(),
+        Err(Error::MissingMandatoryMetricV4 {
+            metric_type: MetricType::SA
+        })
+    );
+    assert_eq!(
+        format!("{}/AV:L", BASE).parse::<Vector>(),
+        Err(Error::DuplicateMetricV4 {
+            metric_type: MetricType::AV
+        })
+    );
+    assert_eq!(
+        format!("{}/XX:L", BASE).parse::<Vector>(),
+        Err(Error::UnknownMetric {
+            name: "XX".to_owned()
+        })
+    );
+    assert!(format!("{}/E", BASE).parse::<Vector>().is_err());
+}
+
+#[test]
+fn base_scores() {
+    assert_eq!(score(BASE), 9.3);
+    assert_eq!(score(&BASE.replace("PR:N", "PR:L")), 8.7);
+    assert_eq!(
+        score(&BASE.replace("AV:N", "AV:L").replace("PR:N", "PR:L")),
+        8.5
+    );
+    assert_eq!(score(&BASE.replace("VC:H/VI:H", "VC:N/VI:N")), 8.7);
+    assert_eq!(
+        score("CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:A/VC:N/VI:N/VA:N/SC:L/SI:L/SA:N"),
+        5.1
+    );
+    assert_eq!(
+        score(&BASE.replace("/SC:N/SI:N/SA:N", "/SC:H/SI:H/SA:H")),
+        10.0
+    );
+}
+
+#[test]
+fn no_impact() {
+    let vector: Vector = "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:N/VI:N/VA:N/SC:N/SI:N/SA:N"
+        .parse()
+        .unwrap();
+    assert_eq!(vector.score().value(), 0.0);
+    assert_eq!(vector.severity(), Severity::None);
+}
+
+#[test]
+fn threat_metrics() {
+    assert_eq!(score(&format!("{}/E:A", BASE)), 9.3);
+    assert_eq!(score(&format!("{}/E:X", BASE)), 9.3);
+    assert_eq!(score(&format!("{}/E:U", BASE)), 8.1);
+}
+
+#[test]
+fn environmental_metrics() {
+    // Safety impact on subsequent systems
+    assert_eq!(score(&format!("{}/MSI:S", BASE)), 10.0);
+
+    // Modified metrics override the Base metrics
+    assert_eq!(
+        score(&format!("{}/MPR:L", BASE)),
+        score(&BASE.replace("PR:N", "PR:L"))
+    );
+    assert_eq!(score(&format!("{}/MAV:X", BASE)), 9.3);
+
+    // Lower security requirements decrease the score
+    let lowered = score(&format!("{}/CR:L/IR:L/AR:L", BASE));
+    assert!(lowered < 9.3, "{}", lowered);
+
+    // No impact at all after modification
+    assert_eq!(score(&format!("{}/MVC:N/MVI:N/MVA:N", BASE)), 0.0);
+}
+
+#[test]
+fn supplemental_metrics() {
+    assert_eq!(score(&format!("{}/S:P/AU:Y/R:I/V:C/RE:H/U:Red", BASE)), 9.3);
+}
+
+#[test]
+fn severity() {
+    let vector: Vector = BASE.parse().unwrap();
+    assert_eq!(vector.severity(), Severity::Critical);
+
+    let vector: Vector = format!("{}/E:U", BASE).parse().unwrap();
+    assert_eq!(vector.severity(), Severity::High);
+}
+
+#[cfg(feature = "v3")]
+#[test]
+fn any_version() {
+    use cvss::Cvss;
+
+    let v3 = "CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:C/C:H/I:H/A:H";
+    let cvss: Cvss = v3.parse().unwrap();
+    assert!(matches!(cvss, Cvss::V3(_)));
+    assert_eq!(cvss.to_string(), v3);
+    assert_eq!(cvss.score(), 9.9);
+
+    let cvss: Cvss = BASE.parse().unwrap();
+    assert!(matches!(cvss, Cvss::V4(_)));
+    assert_eq!(cvss.to_string(), BASE);
+    assert_eq!(cvss.score(), 9.3);
+    assert_eq!(cvss.severity(), Severity::Critical);
+
+    assert_eq!(
+        "CVSS:2.0/AV:N".parse::<Cvss>(),
+        Err(Error::UnsupportedVersion {
+            version: "2.0".to_owned()
+        })
+    );
+}
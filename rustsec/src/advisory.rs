@@ -2,6 +2,7 @@
 
 pub mod affected;
 mod category;
+mod cwe;
 mod date;
 mod id;
 mod informational;
@@ -10,26 +11,30 @@ mod license;
 pub mod linter;
 mod metadata;
 mod parts;
+mod reference;
 pub(crate) mod versions;
 
 pub use self::{
     affected::Affected,
     category::Category,
+    cwe::Cwe,
     date::Date,
     id::{Id, IdKind},
     informational::Informational,
     keyword::Keyword,
     license::License,
-    linter::Linter,
+    linter::{lint, LintIssue, LintSeverity, Linter},
     metadata::Metadata,
     parts::Parts,
-    versions::Versions,
+    reference::{Reference, ReferenceKind},
+    versions::{parse_rust_version, VersionStatus, Versions},
 };
 pub use cvss::Severity;
 
 use crate::{
+    collection::Collection,
     error::{Error, ErrorKind},
-    fs,
+    fs, SourceId, Version,
 };
 use serde::{Deserialize, Serialize};
 use std::{path::Path, str::FromStr};
@@ -46,6 +51,16 @@ pub struct Advisory {
 
     /// Versions related to this advisory which are patched or unaffected.
     pub versions: Versions,
+
+    /// TOML front matter the advisory was parsed from, if it was retained,
+    /// see [`Advisory::parse_with_raw_toml`]
+    #[serde(skip)]
+    pub(crate) raw_toml: Option<String>,
+
+    /// Directory of the database the advisory was loaded from, if it's not
+    /// the one of a known collection, see [`Advisory::collection_dir`]
+    #[serde(skip)]
+    pub(crate) collection_dir: Option<String>,
 }
 
 impl Advisory {
@@ -61,11 +76,66 @@ impl Advisory {
             .map_err(|e| format_err!(ErrorKind::Parse, "error parsing {}: {}", path.display(), e))
     }
 
+    /// Load an advisory from a file like [`Advisory::load_file`], retaining
+    /// its TOML front matter, see [`Advisory::raw_toml`]
+    pub fn load_file_with_raw_toml(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        let advisory_data = fs::read_to_string(path)
+            .map_err(|e| format_err!(ErrorKind::Io, "couldn't open {}: {}", path.display(), e))?;
+
+        Self::parse_with_raw_toml(&advisory_data)
+            .map_err(|e| format_err!(ErrorKind::Parse, "error parsing {}: {}", path.display(), e))
+    }
+
+    /// Parse an advisory, retaining its TOML front matter verbatim, including
+    /// comments and formatting (apart from surrounding whitespace), so that it
+    /// can be edited precisely.
+    ///
+    /// This is opt-in, as keeping the source of every advisory in a database
+    /// would take up memory for nothing in most cases.
+    pub fn parse_with_raw_toml(advisory_data: &str) -> Result<Self, Error> {
+        let mut advisory: Self = advisory_data.parse()?;
+        advisory.raw_toml = Some(parts::Parts::parse(advisory_data)?.front_matter.to_owned());
+        Ok(advisory)
+    }
+
+    /// Get the TOML front matter the advisory was parsed from, if it was
+    /// parsed with [`Advisory::parse_with_raw_toml`] or loaded with
+    /// [`Advisory::load_file_with_raw_toml`]
+    pub fn raw_toml(&self) -> Option<&str> {
+        self.raw_toml.as_deref()
+    }
+
+    /// Get the name of the collection directory within the database this
+    /// advisory belongs in, if its collection is known.
+    ///
+    /// For advisories of [`Collection::Other`] this is the directory they were
+    /// loaded from, and `None` if they weren't loaded from a database.
+    pub fn collection_dir(&self) -> Option<&str> {
+        match self.metadata.collection.as_ref()? {
+            Collection::Other => self.collection_dir.as_deref(),
+            collection => Some(collection.as_str()),
+        }
+    }
+
     /// Get advisory ID
     pub fn id(&self) -> &Id {
         &self.metadata.id
     }
 
+    /// Get the collection the advisory belongs to, which is only known for
+    /// advisories loaded from a database or which declare it themselves
+    pub fn collection(&self) -> Option<Collection> {
+        self.metadata.collection
+    }
+
+    /// Get the source the affected package is published to, which is
+    /// crates.io unless the advisory says otherwise
+    pub fn source(&self) -> SourceId {
+        self.metadata.source.clone().unwrap_or_default()
+    }
+
     /// Get advisory title
     pub fn title(&self) -> &str {
         self.metadata.title.as_ref()
@@ -81,11 +151,33 @@ impl Advisory {
         &self.metadata.date
     }
 
-    /// Get the severity of this advisory if it has a CVSS v3 associated
+    /// Get the severity of this advisory if it has a CVSS v3 or v4 vector
     pub fn severity(&self) -> Option<Severity> {
         self.metadata.cvss.as_ref().map(|cvss| cvss.severity())
     }
 
+    /// Get the URLs of the GitHub Security Advisories this advisory is also
+    /// known as, in the order of its aliases
+    pub fn ghsa_urls(&self) -> Vec<String> {
+        self.metadata.ghsa_aliases().filter_map(Id::url).collect()
+    }
+
+    /// Get the URLs of the CVE entries this advisory is also known as, in the
+    /// order of its aliases
+    pub fn cve_urls(&self) -> Vec<String> {
+        self.metadata.cve_aliases().filter_map(Id::url).collect()
+    }
+
+    /// Classify each of the given versions of the affected package, e.g. all
+    /// of its published versions, by whether it's affected, unaffected or
+    /// patched, see [`Versions::status`]
+    pub fn classify_versions(&self, versions: &[Version]) -> Vec<(Version, VersionStatus)> {
+        versions
+            .iter()
+            .map(|version| (version.clone(), self.versions.status(version)))
+            .collect()
+    }
+
     /// Whether the advisory has been withdrawn, i.e. soft-deleted
     pub fn withdrawn(&self) -> bool {
         self.metadata.withdrawn.is_some()
@@ -0,0 +1,88 @@
+//! Common Weakness Enumeration (CWE) identifiers
+
+use crate::error::{Error, ErrorKind};
+use serde::{de, ser, Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Prefix of CWE identifiers
+const PREFIX: &str = "CWE-";
+
+/// Common Weakness Enumeration (CWE) identifier, e.g. `CWE-416` (use after free)
+///
+/// See <https://cwe.mitre.org/> for the list of weaknesses.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Cwe(u32);
+
+impl Cwe {
+    /// Create a CWE identifier from its number
+    pub const fn new(id: u32) -> Self {
+        Cwe(id)
+    }
+
+    /// Get the number of this CWE identifier
+    pub fn id(self) -> u32 {
+        self.0
+    }
+
+    /// Get the URL of the MITRE page describing this weakness
+    pub fn url(self) -> String {
+        format!("https://cwe.mitre.org/data/definitions/{}.html", self.0)
+    }
+}
+
+impl From<u32> for Cwe {
+    fn from(id: u32) -> Self {
+        Cwe(id)
+    }
+}
+
+impl fmt::Display for Cwe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", PREFIX, self.0)
+    }
+}
+
+impl FromStr for Cwe {
+    type Err = Error;
+
+    /// Parse a CWE identifier of the form `CWE-NNN`
+    fn from_str(s: &str) -> Result<Self, Error> {
+        s.strip_prefix(PREFIX)
+            .and_then(|id| id.parse().ok())
+            .map(Cwe)
+            .ok_or_else(|| format_err!(ErrorKind::Parse, "invalid CWE identifier: {}", s))
+    }
+}
+
+impl<'de> Deserialize<'de> for Cwe {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use de::Error;
+        let string = String::deserialize(deserializer)?;
+        string.parse().map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Cwe {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cwe;
+
+    #[test]
+    fn round_trip() {
+        let cwe: Cwe = "CWE-416".parse().unwrap();
+        assert_eq!(cwe, Cwe::new(416));
+        assert_eq!(cwe.to_string(), "CWE-416");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("416".parse::<Cwe>().is_err());
+        assert!("CWE-".parse::<Cwe>().is_err());
+        assert!("CWE-abc".parse::<Cwe>().is_err());
+    }
+}
@@ -3,8 +3,10 @@
 use crate::error::{Error, ErrorKind};
 use serde::{de, Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
     fmt::{self, Display},
     str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 /// Minimum allowed year on advisory dates
@@ -13,11 +15,36 @@ pub(crate) const YEAR_MIN: u32 = 2000;
 /// Maximum allowed year on advisory dates
 pub(crate) const YEAR_MAX: u32 = YEAR_MIN + 100;
 
-/// Dates on advisories (RFC 3339)
-#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize)]
+/// Length of the `YYYY-MM-DD` calendar date at the start of every date
+const DATE_LEN: usize = 10;
+
+/// Dates on advisories (RFC 3339).
+///
+/// These are usually calendar dates like `2001-02-03`, but may also be full
+/// timestamps with a time of day and UTC offset like `2001-02-03T04:05:06Z`,
+/// which are kept as they are.
+///
+/// Dates are ordered by the point in time they refer to, in UTC. A calendar
+/// date comes before all timestamps on the same day, so that e.g. an ignore
+/// expiring on `2001-02-03T00:00:00Z` has not expired yet on `2001-02-03`.
+/// Timestamps referring to the same point in time with different UTC offsets
+/// are ordered by their strings, to stay consistent with equality.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
 pub struct Date(String);
 
 impl Date {
+    /// Get the current date (in UTC)
+    pub fn today() -> Self {
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before Unix epoch")
+            .as_secs()
+            / 86_400;
+
+        let (year, month, day) = civil_from_days(days);
+        Date(format!("{:04}-{:02}-{:02}", year, month, day))
+    }
+
     /// Get the year for this date
     pub fn year(&self) -> u32 {
         self.component(0).expect("has year")
@@ -33,6 +60,17 @@ impl Date {
         self.component(2).expect("has day")
     }
 
+    /// Get the time of day and UTC offset of this date if it's a full
+    /// timestamp, e.g. `04:05:06Z`
+    pub fn time(&self) -> Option<&str> {
+        self.0.get(DATE_LEN + 1..).filter(|time| !time.is_empty())
+    }
+
+    /// Get this date without its time of day, if it has any
+    pub fn without_time(&self) -> Self {
+        Date(self.0[..DATE_LEN].to_owned())
+    }
+
     /// Borrow this date as a string reference
     pub fn as_str(&self) -> &str {
         self.0.as_ref()
@@ -40,11 +78,61 @@ impl Date {
 
     /// Get a specific component of the date by numerical offset
     fn component(&self, index: usize) -> Option<u32> {
-        self.0
+        self.0[..DATE_LEN]
             .split('-')
             .nth(index)
             .map(|cmp| cmp.parse().expect("numerical date components"))
     }
+
+    /// Key ordering dates by the point in time they refer to: the day in UTC
+    /// as days since the Unix epoch, and the second of that day and fractional
+    /// seconds (without trailing zeros) if it's a timestamp
+    fn utc_key(&self) -> (i64, Option<(i64, &str)>) {
+        let days = days_from_civil(
+            i64::from(self.year()),
+            i64::from(self.month()),
+            i64::from(self.day()),
+        );
+
+        let time = match self.time() {
+            Some(time) => time,
+            None => return (days, None),
+        };
+
+        // The time has been validated when parsing, so its layout is fixed
+        let number = |part: &str| -> i64 { part.parse().expect("numerical time components") };
+        let seconds = number(&time[..2]) * 3600 + number(&time[3..5]) * 60 + number(&time[6..8]);
+        let (fraction, offset) = match time[8..].find(['Z', 'z', '+', '-']) {
+            Some(pos) => time[8..].split_at(pos),
+            None => unreachable!("validated timestamps have a UTC offset"),
+        };
+        let fraction = fraction.trim_start_matches('.').trim_end_matches('0');
+        let offset = match offset.as_bytes()[0] {
+            b'+' => number(&offset[1..3]) * 3600 + number(&offset[4..6]) * 60,
+            b'-' => -(number(&offset[1..3]) * 3600 + number(&offset[4..6]) * 60),
+            _ => 0,
+        };
+
+        let utc = days * 86_400 + seconds - offset;
+        (
+            utc.div_euclid(86_400),
+            Some((utc.rem_euclid(86_400), fraction)),
+        )
+    }
+}
+
+impl Ord for Date {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.utc_key()
+            .cmp(&other.utc_key())
+            .then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for Date {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl AsRef<str> for Date {
@@ -62,9 +150,16 @@ impl Display for Date {
 impl FromStr for Date {
     type Err = Error;
 
-    /// Create a `Date` from the given RFC 3339 date string
+    /// Create a `Date` from the given RFC 3339 date or timestamp string
     fn from_str(rfc3339_date: &str) -> Result<Self, Error> {
-        validate_date(rfc3339_date)?;
+        match rfc3339_date.split_once(['T', 't']) {
+            Some((date, time)) => {
+                validate_date(date)?;
+                validate_time(rfc3339_date, time)?;
+            }
+            None => validate_date(rfc3339_date)?,
+        }
+
         Ok(Date(rfc3339_date.into()))
     }
 }
@@ -95,6 +190,46 @@ macro_rules! check_date_part {
     };
 }
 
+/// Convert a number of days since the Unix epoch into a `(year, month, day)`
+/// date in the proleptic Gregorian calendar.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // Shift the epoch to 0000-03-01, the start of a 400-year era
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Convert a date in the proleptic Gregorian calendar into the number of
+/// days since the Unix epoch, the inverse of [`civil_from_days`].
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Shift the start of the year to March, so that leap days come last
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
 /// Validate that a date is well-formed
 fn validate_date(string: &str) -> Result<(), Error> {
     let mut parts = string.split('-');
@@ -111,9 +246,50 @@ fn validate_date(string: &str) -> Result<(), Error> {
     Ok(())
 }
 
+/// Validate that the time of day and UTC offset of a timestamp are well-formed
+fn validate_time(string: &str, time: &str) -> Result<(), Error> {
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(pos) => time.split_at(pos),
+        None => fail!(ErrorKind::Parse, "missing UTC offset: {}", string),
+    };
+
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction)),
+        None => (time, None),
+    };
+
+    let mut parts = time.split(':');
+    check_date_part!("hour", string, parts, 2, 0, 23);
+    check_date_part!("minute", string, parts, 2, 0, 59);
+    // Allow for leap seconds
+    check_date_part!("second", string, parts, 2, 0, 60);
+
+    if parts.next().is_some() {
+        fail!(ErrorKind::Parse, "invalid time: {}", string)
+    }
+
+    if let Some(fraction) = fraction {
+        if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            fail!(ErrorKind::Parse, "malformed fractional seconds: {}", string)
+        }
+    }
+
+    if !matches!(offset, "Z" | "z") {
+        let mut parts = offset[1..].split(':');
+        check_date_part!("offset hour", string, parts, 2, 0, 23);
+        check_date_part!("offset minute", string, parts, 2, 0, 59);
+
+        if parts.next().is_some() {
+            fail!(ErrorKind::Parse, "invalid UTC offset: {}", string)
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Date;
+    use super::{civil_from_days, days_from_civil, Date};
     use std::str::FromStr;
 
     #[test]
@@ -135,11 +311,80 @@ mod tests {
         assert!(Date::from_str("2017-01-01-01").is_err());
     }
 
+    #[test]
+    fn from_str_timestamp_test() {
+        // Valid timestamps
+        assert!(Date::from_str("2017-01-01T00:00:00Z").is_ok());
+        assert!(Date::from_str("2017-01-01T23:59:60z").is_ok());
+        assert!(Date::from_str("2017-01-01t12:34:56.789+01:00").is_ok());
+        assert!(Date::from_str("2017-01-01T12:34:56-08:30").is_ok());
+
+        // Invalid timestamps
+        assert!(Date::from_str("2017-01-01T").is_err());
+        assert!(Date::from_str("2017-01-01T12:34:56").is_err());
+        assert!(Date::from_str("2017-01-01T24:00:00Z").is_err());
+        assert!(Date::from_str("2017-01-01T12:34Z").is_err());
+        assert!(Date::from_str("2017-01-01T12:34:56.Z").is_err());
+        assert!(Date::from_str("2017-01-01T12:34:56+0100").is_err());
+        assert!(Date::from_str("2017-01-32T12:34:56Z").is_err());
+    }
+
     #[test]
     fn date_components_test() {
         let date = Date::from_str("2000-01-02").unwrap();
         assert_eq!(date.year(), 2000);
         assert_eq!(date.month(), 1);
         assert_eq!(date.day(), 2);
+        assert_eq!(date.time(), None);
+
+        let date = Date::from_str("2000-01-02T03:04:05-06:00").unwrap();
+        assert_eq!(date.year(), 2000);
+        assert_eq!(date.month(), 1);
+        assert_eq!(date.day(), 2);
+        assert_eq!(date.time(), Some("03:04:05-06:00"));
+        assert_eq!(date.without_time().as_str(), "2000-01-02");
+    }
+
+    #[test]
+    fn civil_from_days_test() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        assert!(Date::today().year() >= 2024);
+
+        for days in [0, 11_016, 19_782, 19_783, 47_540] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(
+                days_from_civil(year as i64, month as i64, day as i64),
+                days as i64
+            );
+        }
+    }
+
+    #[test]
+    fn ordering_test() {
+        let date = |s: &str| Date::from_str(s).unwrap();
+
+        assert!(date("2030-01-01") < date("2030-01-02"));
+        assert!(date("2030-01-01") < date("2030-01-01T00:00:00Z"));
+        assert!(date("2030-01-01T23:59:59Z") < date("2030-01-02"));
+        assert!(date("2030-01-01T12:00:00Z") < date("2030-01-01T12:00:00.5Z"));
+        assert!(date("2030-01-01T12:00:00.49Z") < date("2030-01-01T12:00:00.5Z"));
+
+        // UTC offsets are taken into account, also across days and months
+        assert!(date("2030-01-01T10:00:00+05:00") < date("2030-01-01T06:00:00Z"));
+        assert!(date("2030-01-02T03:00:00+05:00") < date("2030-01-02"));
+        assert!(date("2030-01-31T22:00:00-05:00") > date("2030-02-01"));
+        assert!(date("2030-02-28T23:00:00-02:00") > date("2030-03-01"));
+
+        // Equal points in time are still ordered consistently with equality
+        let (a, b) = (
+            date("2030-01-01T12:00:00Z"),
+            date("2030-01-01T13:00:00+01:00"),
+        );
+        assert_ne!(a.cmp(&b), std::cmp::Ordering::Equal);
+        assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+        assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
     }
 }
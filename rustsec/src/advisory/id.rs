@@ -313,4 +313,25 @@ mod tests {
         assert!(other_id.url().is_none());
         assert_eq!(other_id.numerical_part().unwrap(), 42);
     }
+
+    #[test]
+    fn round_trip_test() {
+        for id in [
+            EXAMPLE_RUSTSEC_ID,
+            EXAMPLE_CVE_ID,
+            EXAMPLE_GHSA_ID,
+            EXAMPLE_TALOS_ID,
+            EXAMPLE_UNKNOWN_ID,
+            "OSV-2020-111",
+        ] {
+            assert_eq!(id.parse::<Id>().unwrap().to_string(), id);
+        }
+    }
+
+    #[test]
+    fn malformed_id_test() {
+        assert!("CVE-17-1000168".parse::<Id>().is_err());
+        assert!("CVE-2017".parse::<Id>().is_err());
+        assert!("RUSTSEC-2018-abcd".parse::<Id>().is_err());
+    }
 }
@@ -26,6 +26,10 @@ pub enum Informational {
 
     /// Other types of informational advisories: left open-ended to add
     /// more of them in the future.
+    ///
+    /// Unrecognized values parse into this variant instead of failing, so an
+    /// advisory with an unknown kind doesn't prevent loading the database.
+    /// The [`Linter`](crate::advisory::Linter) reports them as errors.
     Other(String),
 }
 
@@ -3,7 +3,7 @@
 //!
 //! This is run in CI at the time advisories are submitted.
 
-use super::{parts, Advisory, Category};
+use super::{parts, Advisory, Category, Date, IdKind};
 use crate::advisory::license::License;
 use crate::fs;
 use std::str::FromStr;
@@ -216,8 +216,22 @@ impl Linter {
                             }
                         }
                     }
-                    "aliases" | "cvss" | "keywords" | "package" | "references" | "related"
-                    | "title" | "withdrawn" | "description" => (),
+                    "related" => {
+                        for related in value.as_array().into_iter().flatten() {
+                            if related
+                                .as_str()
+                                .map_or(false, |id| IdKind::detect(id) == IdKind::Other)
+                            {
+                                self.errors.push(Error {
+                                    kind: ErrorKind::value("related", related.to_string()),
+                                    section: Some("advisory"),
+                                    message: Some("unknown advisory ID type"),
+                                });
+                            }
+                        }
+                    }
+                    "aliases" | "cvss" | "cwe" | "keywords" | "package" | "references"
+                    | "source" | "title" | "withdrawn" | "description" | "mitigation" => (),
                     _ => self.errors.push(Error {
                         kind: ErrorKind::key(key),
                         section: Some("advisory"),
@@ -381,3 +395,191 @@ impl fmt::Display for ErrorKind {
         }
     }
 }
+
+/// Check the given advisory (Markdown with TOML front matter) for problems
+/// contributors should fix before submitting it.
+///
+/// Unlike parsing it, this doesn't stop at the first problem, and also
+/// reports problems which don't prevent the advisory from parsing, e.g. a
+/// missing description. An advisory without any issues of
+/// [`LintSeverity::Error`] parses successfully.
+pub fn lint(advisory_data: &str) -> Vec<LintIssue> {
+    let mut issues = vec![];
+
+    let parts = match parts::Parts::parse(advisory_data) {
+        Ok(parts) => parts,
+        Err(err) => {
+            issues.push(LintIssue::error(err.to_string()));
+            return issues;
+        }
+    };
+
+    // V4 advisories omit the leading `[advisory]` TOML table
+    let front_matter = if parts.front_matter.starts_with("[advisory]") {
+        parts.front_matter.to_owned()
+    } else {
+        String::from("[advisory]\n") + parts.front_matter
+    };
+
+    let front_matter = match front_matter.parse::<toml::Value>() {
+        Ok(front_matter) => front_matter,
+        Err(err) => {
+            issues.push(LintIssue::error(format!(
+                "couldn't parse TOML front matter: {}",
+                err
+            )));
+            return issues;
+        }
+    };
+
+    if parts.title.is_empty() {
+        issues.push(LintIssue::error("advisory has no title"));
+    }
+
+    if parts.description.is_empty() {
+        issues.push(LintIssue::warning("advisory has no description"));
+    }
+
+    let metadata = front_matter.get("advisory");
+
+    for key in ["id", "package", "date"] {
+        match metadata.and_then(|metadata| metadata.get(key)) {
+            Some(toml::Value::String(_)) => (),
+            Some(_) => issues.push(LintIssue::error(format!("`{}` must be a string", key))),
+            None => issues.push(LintIssue::error(format!(
+                "missing required field `{}`",
+                key
+            ))),
+        }
+    }
+
+    if let Some(date) = metadata
+        .and_then(|metadata| metadata.get("date"))
+        .and_then(toml::Value::as_str)
+    {
+        if let Err(err) = date.parse::<Date>() {
+            issues.push(LintIssue::error(format!(
+                "invalid date `{}`: {}",
+                date, err
+            )));
+        }
+    }
+
+    if let Some(metadata) = metadata {
+        if metadata.get("informational").is_some() && metadata.get("cvss").is_some() {
+            issues.push(LintIssue::warning(
+                "informational advisories shouldn't have a CVSS score",
+            ));
+        }
+    }
+
+    match front_matter.get("versions") {
+        Some(versions) => {
+            if versions.get("patched").is_none() {
+                issues.push(LintIssue::error("missing required field `patched`"));
+            }
+
+            for key in ["patched", "unaffected"] {
+                for range in versions
+                    .get(key)
+                    .and_then(toml::Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    match range.as_str() {
+                        Some(range) => {
+                            if let Err(err) = range.parse::<semver::VersionReq>() {
+                                issues.push(LintIssue::error(format!(
+                                    "`{}` range `{}` isn't a valid semver requirement: {}",
+                                    key, range, err
+                                )));
+                            }
+                        }
+                        None => issues.push(LintIssue::error(format!(
+                            "`{}` ranges must be strings",
+                            key
+                        ))),
+                    }
+                }
+            }
+        }
+        None => issues.push(LintIssue::error("missing `[versions]` section")),
+    }
+
+    // Report anything else which prevents the advisory from parsing
+    if !issues.iter().any(LintIssue::is_error) {
+        if let Err(err) = advisory_data.parse::<Advisory>() {
+            issues.push(LintIssue::error(err.to_string()));
+        }
+    }
+
+    issues
+}
+
+/// Problem with an advisory found by [`lint`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LintIssue {
+    /// How serious the problem is
+    severity: LintSeverity,
+
+    /// Description of the problem
+    message: String,
+}
+
+impl LintIssue {
+    /// Create an issue which prevents the advisory from being accepted
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: LintSeverity::Error,
+            message: message.into(),
+        }
+    }
+
+    /// Create an issue which should be fixed, but doesn't make the advisory invalid
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: LintSeverity::Warning,
+            message: message.into(),
+        }
+    }
+
+    /// Get how serious the problem is
+    pub fn severity(&self) -> LintSeverity {
+        self.severity
+    }
+
+    /// Get the description of the problem
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Does this issue make the advisory invalid?
+    pub fn is_error(&self) -> bool {
+        self.severity == LintSeverity::Error
+    }
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Severity of a [`LintIssue`]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    /// The advisory should be improved, but is valid
+    Warning,
+
+    /// The advisory is invalid
+    Error,
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintSeverity::Warning => "warning",
+            LintSeverity::Error => "error",
+        })
+    }
+}
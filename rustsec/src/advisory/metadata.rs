@@ -1,15 +1,21 @@
 //! Advisory information (i.e. the `[advisory]` section)
 
 use super::{
-    category::Category, date::Date, id::Id, informational::Informational, keyword::Keyword,
+    category::Category,
+    cwe::Cwe,
+    date::Date,
+    id::{Id, IdKind},
+    informational::Informational,
+    keyword::Keyword,
+    reference::Reference,
 };
 use crate::advisory::license::License;
-use crate::{collection::Collection, package, SourceId};
-use serde::{Deserialize, Serialize};
+use crate::{collection::Collection, package, Map, SourceId};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use url::Url;
 
 /// The `[advisory]` section of a RustSec security advisory
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Metadata {
     /// Security advisory ID (e.g. RUSTSEC-YYYY-NNNN)
     pub id: Id,
@@ -25,6 +31,11 @@ pub struct Metadata {
     #[serde(default)]
     pub description: String,
 
+    /// Workaround or other mitigation for users who can't upgrade, e.g.
+    /// because no patched version is available yet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mitigation: Option<String>,
+
     /// Date the underlying issue was reported
     pub date: Date,
 
@@ -47,26 +58,33 @@ pub struct Metadata {
     #[serde(default)]
     pub categories: Vec<Category>,
 
+    /// Common Weakness Enumeration (CWE) identifiers of the weaknesses
+    /// underlying this vulnerability (e.g. `CWE-416` for use after free)
+    #[serde(default)]
+    pub cwe: Vec<Cwe>,
+
     /// Freeform keywords which succinctly describe this vulnerability (e.g. "ssl", "rce", "xss")
     #[serde(default)]
     pub keywords: Vec<Keyword>,
 
-    /// CVSS v3.1 Base Metrics vector string containing severity information.
+    /// CVSS v3.1 Base Metrics or CVSS v4.0 vector string containing severity
+    /// information.
     ///
-    /// Example:
+    /// Examples:
     ///
     /// ```text
     /// CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N
+    /// CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:P/VC:L/VI:L/VA:N/SC:L/SI:L/SA:N
     /// ```
-    pub cvss: Option<cvss::v3::Base>,
+    pub cvss: Option<cvss::Cvss>,
 
     /// Informational advisories can be used to warn users about issues
     /// affecting a particular crate without failing the build.
     pub informational: Option<Informational>,
 
-    /// Additional reference URLs with more information related to this advisory
+    /// Additional references with more information related to this advisory
     #[serde(default)]
-    pub references: Vec<Url>,
+    pub references: Vec<Reference>,
 
     /// Source URL where the vulnerable package is located/published.
     ///
@@ -86,4 +104,99 @@ pub struct Metadata {
     /// License under which the advisory content is available
     #[serde(default)]
     pub license: License,
+
+    /// Keys this version of the crate doesn't know about, e.g. fields added
+    /// to the advisory format later on, which are kept rather than rejected
+    /// so that loading the advisory doesn't fail.
+    ///
+    /// The [`Linter`](super::Linter) still reports them as errors.
+    ///
+    /// Non-finite floats (i.e. `nan` and `inf`) are rejected when parsing, since
+    /// NaN is unequal to itself, which would break the [`Eq`] impl of `Metadata`.
+    #[serde(flatten, deserialize_with = "deserialize_extra")]
+    pub extra: Map<String, toml::Value>,
+}
+
+// TOML values are only unequal to themselves if they're NaN floats, which
+// `extra` rejects when parsing
+impl Eq for Metadata {}
+
+impl Metadata {
+    /// Iterate over the aliases of this advisory of the given kind
+    pub fn aliases_of_kind(&self, kind: IdKind) -> impl Iterator<Item = &Id> {
+        self.aliases
+            .iter()
+            .filter(move |alias| alias.kind() == kind)
+    }
+
+    /// Iterate over the CVE IDs this advisory is also known as
+    pub fn cve_aliases(&self) -> impl Iterator<Item = &Id> {
+        self.aliases_of_kind(IdKind::Cve)
+    }
+
+    /// Iterate over the GitHub Security Advisory IDs this advisory is also known as
+    pub fn ghsa_aliases(&self) -> impl Iterator<Item = &Id> {
+        self.aliases_of_kind(IdKind::Ghsa)
+    }
+
+    /// Iterate over all references of this advisory, starting with its
+    /// [`url`](Metadata::url), whose kind is guessed from the URL (typically
+    /// [`ReferenceKind::Advisory`](super::ReferenceKind::Advisory) or
+    /// [`ReferenceKind::Web`](super::ReferenceKind::Web))
+    pub fn all_references(&self) -> impl Iterator<Item = Reference> + '_ {
+        self.url
+            .iter()
+            .cloned()
+            .map(Reference::from)
+            .chain(self.references.iter().cloned())
+    }
+
+    /// Get the CVSS v3 Base Metrics of this advisory, if it has a v3 vector.
+    ///
+    /// Individual metrics (e.g. the attack vector) explain how the overall
+    /// severity came about, and the exploitability and impact subscores can
+    /// be calculated from them.
+    pub fn cvss_v3(&self) -> Option<&cvss::v3::Base> {
+        match &self.cvss {
+            Some(cvss::Cvss::V3(base)) => Some(base),
+            _ => None,
+        }
+    }
+
+    /// Get the CVSS v4 vector of this advisory, if it has a v4 vector, whose
+    /// individual metrics can be inspected with [`cvss::v4::Vector::get`]
+    pub fn cvss_v4(&self) -> Option<&cvss::v4::Vector> {
+        match &self.cvss {
+            Some(cvss::Cvss::V4(vector)) => Some(vector),
+            _ => None,
+        }
+    }
+}
+
+/// Deserialize keys unknown to this crate, rejecting non-finite floats
+fn deserialize_extra<'de, D>(deserializer: D) -> Result<Map<String, toml::Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use de::Error;
+
+    let extra = Map::<String, toml::Value>::deserialize(deserializer)?;
+
+    match extra.iter().find(|(_, value)| has_non_finite_float(value)) {
+        Some((key, _)) => Err(D::Error::custom(format!(
+            "non-finite float in advisory key `{}`",
+            key
+        ))),
+        None => Ok(extra),
+    }
+}
+
+/// Is the given value, or any value nested in it, a non-finite float?
+fn has_non_finite_float(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::Float(float) => !float.is_finite(),
+        toml::Value::Array(values) => values.iter().any(has_non_finite_float),
+        toml::Value::Table(table) => table.values().any(has_non_finite_float),
+        _ => false,
+    }
 }
@@ -0,0 +1,193 @@
+//! References to further information about an advisory
+
+use crate::error::{Error, ErrorKind};
+use serde::{de, ser, Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use url::Url;
+
+/// Reference URL with more information related to an advisory.
+///
+/// In advisories, references are either written as a plain URL, whose kind is
+/// then guessed from the URL itself, or as a table with an explicit kind:
+///
+/// ```toml
+/// references = [
+///     "https://github.com/example/example/issues/1",
+///     { type = "FIX", url = "https://github.com/example/example/pull/2" },
+/// ]
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Reference {
+    /// Kind of information found at the URL
+    pub kind: ReferenceKind,
+
+    /// URL of the reference
+    pub url: Url,
+}
+
+impl Reference {
+    /// Create a reference of the given kind
+    pub fn new(kind: ReferenceKind, url: Url) -> Self {
+        Self { kind, url }
+    }
+}
+
+impl From<Url> for Reference {
+    /// Create a reference whose kind is guessed from the URL
+    fn from(url: Url) -> Self {
+        Self {
+            kind: ReferenceKind::guess(&url),
+            url,
+        }
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.url.fmt(f)
+    }
+}
+
+impl FromStr for Reference {
+    type Err = Error;
+
+    /// Parse a plain URL, guessing the kind of the reference from it
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let url = s
+            .parse::<Url>()
+            .map_err(|e| format_err!(ErrorKind::Parse, "invalid reference URL {}: {}", s, e))?;
+        Ok(url.into())
+    }
+}
+
+/// Representations of references in advisories
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum ReferenceRepr {
+    /// Plain URL
+    Url(Url),
+
+    /// URL with an explicit kind
+    Typed {
+        #[serde(rename = "type")]
+        kind: ReferenceKind,
+        url: Url,
+    },
+}
+
+impl<'de> Deserialize<'de> for Reference {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match ReferenceRepr::deserialize(deserializer)? {
+            ReferenceRepr::Url(url) => url.into(),
+            ReferenceRepr::Typed { kind, url } => Self { kind, url },
+        })
+    }
+}
+
+impl Serialize for Reference {
+    /// References whose kind would be guessed from their URL anyway are
+    /// serialized as plain URLs
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = if self.kind == ReferenceKind::guess(&self.url) {
+            ReferenceRepr::Url(self.url.clone())
+        } else {
+            ReferenceRepr::Typed {
+                kind: self.kind,
+                url: self.url.clone(),
+            }
+        };
+
+        repr.serialize(serializer)
+    }
+}
+
+/// Kinds of references, matching the reference types of
+/// [OSV](https://ossf.github.io/osv-schema/#references-field)
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum ReferenceKind {
+    /// Published security advisory, e.g. on rustsec.org or GitHub
+    Advisory,
+
+    /// Article or blog post describing the vulnerability
+    Article,
+
+    /// Report of the vulnerability, e.g. an issue on the crate's repository
+    Report,
+
+    /// Source code change fixing the vulnerability, e.g. a pull request
+    Fix,
+
+    /// Home page of the affected package
+    Package,
+
+    /// Web page of some other kind
+    Web,
+}
+
+impl ReferenceKind {
+    /// Guess the kind of reference a URL points to
+    pub fn guess(url: &Url) -> Self {
+        let str = url.as_str();
+        if (str.contains("://github.com/") || str.contains("://gitlab."))
+            && str.contains("/issues/")
+        {
+            Self::Report
+        // the check for "/advisories/" matches both RustSec and GHSA URLs
+        } else if str.contains("/advisories/") || str.contains("://cve.mitre.org/") {
+            Self::Advisory
+        } else if str.contains("://crates.io/crates/") {
+            Self::Package
+        } else {
+            Self::Web
+        }
+    }
+
+    /// Get a `str` representing this kind of reference, as used by OSV
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Advisory => "ADVISORY",
+            Self::Article => "ARTICLE",
+            Self::Report => "REPORT",
+            Self::Fix => "FIX",
+            Self::Package => "PACKAGE",
+            Self::Web => "WEB",
+        }
+    }
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReferenceKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Ok(match s {
+            "ADVISORY" => Self::Advisory,
+            "ARTICLE" => Self::Article,
+            "REPORT" => Self::Report,
+            "FIX" => Self::Fix,
+            "PACKAGE" => Self::Package,
+            "WEB" => Self::Web,
+            other => fail!(ErrorKind::Parse, "invalid reference type: {}", other),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ReferenceKind {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use de::Error;
+        let string = String::deserialize(deserializer)?;
+        string.parse().map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for ReferenceKind {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_str().serialize(serializer)
+    }
+}
@@ -1,6 +1,9 @@
 //! The `[versions]` subsection of an advisory.
 
-use crate::{osv, Error};
+use crate::{
+    error::{Error, ErrorKind},
+    osv::{self, UnaffectedRange},
+};
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 
@@ -17,6 +20,20 @@ pub struct Versions {
     unaffected: Vec<VersionReq>,
 }
 
+/// Whether a version of a package is affected by an advisory, see
+/// [`Versions::status`]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum VersionStatus {
+    /// The version is affected, i.e. neither patched nor unaffected
+    Affected,
+
+    /// The version was never affected in the first place
+    Unaffected,
+
+    /// The version is patched
+    Patched,
+}
+
 impl Versions {
     /// Is the given version of a package vulnerable?
     pub fn is_vulnerable(&self, version: &Version) -> bool {
@@ -28,6 +45,100 @@ impl Versions {
         false
     }
 
+    /// Is the given version affected, i.e. neither patched nor unaffected?
+    ///
+    /// Unlike [`VersionReq::matches`], this considers pre-releases to be within
+    /// the ranges they are ordered in, e.g. `2.0.0-beta` is patched by `>= 1.2.3`.
+    ///
+    /// Following semver precedence, a pre-release comes before the release it
+    /// leads up to: `2.0.0-rc.1` is *not* patched by `>= 2.0.0`, but it is by
+    /// `>= 2.0.0-rc.1`. See [`Versions::is_affected_with_prereleases`] to
+    /// treat pre-releases the way Cargo does instead.
+    pub fn is_affected(&self, version: &Version) -> bool {
+        self.is_affected_with_prereleases(version, true)
+    }
+
+    /// Is the given version affected, optionally excluding pre-releases from
+    /// the patched and unaffected ranges they're ordered in?
+    ///
+    /// With `include_prereleases` this is the same as [`Versions::is_affected`].
+    /// Without it, a pre-release only counts as patched or unaffected if a
+    /// range explicitly allows pre-releases of the same version, like Cargo
+    /// requires: `2.1.0-rc.1` is then affected despite `>= 2.0.0` being
+    /// patched, but not if `>= 2.1.0-rc.1` is.
+    pub fn is_affected_with_prereleases(
+        &self,
+        version: &Version,
+        include_prereleases: bool,
+    ) -> bool {
+        !self.patched.iter().chain(&self.unaffected).any(|req| {
+            UnaffectedRange::try_from(req)
+                .expect("version ranges are validated on construction")
+                .contains(version)
+                && (include_prereleases || version.pre.is_empty() || req.matches(version))
+        })
+    }
+
+    /// Is the given version affected, unaffected or patched?
+    ///
+    /// Pre-releases are considered to be within the ranges they are ordered
+    /// in, like with [`Versions::is_affected`].
+    pub fn status(&self, version: &Version) -> VersionStatus {
+        let contains = |reqs: &[VersionReq]| {
+            reqs.iter().any(|req| {
+                UnaffectedRange::try_from(req)
+                    .expect("version ranges are validated on construction")
+                    .contains(version)
+            })
+        };
+
+        if contains(&self.unaffected) {
+            VersionStatus::Unaffected
+        } else if contains(&self.patched) {
+            VersionStatus::Patched
+        } else {
+            VersionStatus::Affected
+        }
+    }
+
+    /// Parse the given version and check whether it is affected.
+    ///
+    /// Registries can contain versions which aren't valid semver, see
+    /// <https://github.com/rustsec/rustsec/issues/759>, in which case this
+    /// returns an error of kind [`ErrorKind::Version`].
+    pub fn is_affected_str(&self, version: &str) -> Result<bool, Error> {
+        let version = Version::parse(version)
+            .map_err(|e| format_err!(ErrorKind::Version, "invalid version '{}': {}", version, e))?;
+        Ok(self.is_affected(&version))
+    }
+
+    /// Find the lowest of the available versions which is patched (or
+    /// unaffected) and can be upgraded to from `current` without breaking
+    /// changes, e.g. to fix a vulnerability conservatively.
+    ///
+    /// Compatibility follows Cargo's flavor of semver: versions must have the
+    /// same major version as `current`, or the same minor version for `0.x`
+    /// releases (and the same patch version for `0.0.x` ones). Returns `None`
+    /// if only a breaking upgrade would fix the vulnerability. `current` is
+    /// returned if it's available and not affected. Pre-releases are only
+    /// considered when `current` is a pre-release.
+    pub fn minimal_compatible_fix(
+        &self,
+        current: &Version,
+        patched_available: &[Version],
+    ) -> Option<Version> {
+        patched_available
+            .iter()
+            .filter(|version| {
+                *version >= current
+                    && (version.pre.is_empty() || !current.pre.is_empty())
+                    && is_compatible(current, version)
+                    && !self.is_affected(version)
+            })
+            .min()
+            .cloned()
+    }
+
     /// Creates a new `[versions]` entry.
     /// Checks consistency of the passed version requirements.
     pub fn new(patched: Vec<VersionReq>, unaffected: Vec<VersionReq>) -> Result<Self, Error> {
@@ -70,6 +181,54 @@ pub(crate) struct RawVersions {
     pub unaffected: Vec<VersionReq>,
 }
 
+/// Can `from` be upgraded to `to` without breaking changes, according to
+/// Cargo's flavor of semver?
+pub(crate) fn is_compatible(from: &Version, to: &Version) -> bool {
+    match (from.major, from.minor) {
+        (0, 0) => to.major == 0 && to.minor == 0 && to.patch == from.patch,
+        (0, minor) => to.major == 0 && to.minor == minor,
+        (major, _) => to.major == major,
+    }
+}
+
+/// Parse a version of the Rust toolchain, e.g. to look up the advisories in
+/// the [`Collection::Rust`](crate::Collection::Rust) collection affecting it.
+///
+/// Besides full semver versions such as `1.70.0` or `1.72.0-nightly`, this
+/// accepts versions without a patch component (`1.70`, i.e. `1.70.0`) and
+/// the output of `rustc --version`, e.g. `rustc 1.70.0 (90c541806 2023-05-31)`.
+pub fn parse_rust_version(version: &str) -> Result<Version, Error> {
+    let version = version.trim();
+    let version = version
+        .strip_prefix("rustc ")
+        .unwrap_or(version)
+        .trim_start();
+    let version = version.split_whitespace().next().unwrap_or_default();
+
+    let (release, pre) = match version.split_once('-') {
+        Some((release, pre)) => (release, Some(pre)),
+        None => (version, None),
+    };
+
+    let mut full = match release.split('.').count() {
+        2 => format!("{}.0", release),
+        _ => release.to_owned(),
+    };
+    if let Some(pre) = pre {
+        full.push('-');
+        full.push_str(pre);
+    }
+
+    full.parse().map_err(|e| {
+        format_err!(
+            ErrorKind::Version,
+            "invalid Rust version {:?}: {}",
+            version,
+            e
+        )
+    })
+}
+
 fn validate_ranges(versions: &RawVersions) -> Result<(), Error> {
     let _ = osv::ranges_for_unvalidated_advisory(versions)?;
     Ok(())
@@ -1,31 +1,95 @@
 //! An efficient way to check whether a given package has been yanked
+
+mod builder;
+mod cache_file;
+mod progress;
+mod retry;
+mod snapshot;
+mod yanked_checker;
+
 use std::{
     collections::{BTreeSet, HashMap},
-    time::Duration,
+    io,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+pub use self::{
+    builder::CachedIndexBuilder,
+    progress::{ProgressCallback, ProgressEvent},
+    retry::RetryPolicy,
+    snapshot::IndexSnapshot,
+    yanked_checker::YankedChecker,
 };
 
+use self::cache_file::{CacheFile, CrateEntry};
+#[cfg(feature = "git-index")]
+use self::{progress::CallbackProgress, snapshot::PinnedCommit};
 use crate::{
     error::{Error, ErrorKind},
+    fs,
     package::{self, Package},
+    Lockfile, SourceId,
 };
 
+use dashmap::DashMap;
+#[cfg(feature = "git-index")]
+use std::sync::Mutex;
+#[cfg(feature = "git-index")]
 use tame_index::external::gix;
 pub use tame_index::external::reqwest::ClientBuilder;
 
+/// Default timeout per individual crate. If a crate fails to be
+/// requested for a retriable reason then it will be retried until
+/// this time limit is reached
+const DEFAULT_REQUEST_TIMEOUT: Option<Duration> = Some(Duration::from_secs(10));
+
 enum Index {
-    Git(tame_index::index::RemoteGitIndex),
+    /// The git index is backed by a `gix::Repository`, which is not `Sync`,
+    /// so lookups against it are serialized
+    #[cfg(feature = "git-index")]
+    Git(Mutex<tame_index::index::RemoteGitIndex>),
+    /// Git index pinned to a commit, see [`CachedIndex::open_pinned`]
+    #[cfg(feature = "git-index")]
+    GitPinned(PinnedCommit),
     SparseCached(tame_index::index::SparseIndex),
     SparseRemote(tame_index::index::AsyncRemoteSparseIndex),
+    /// Sparse index pinned to a saved cache, which only contains the crates
+    /// loaded from it, see [`CachedIndex::open_pinned`]
+    SparsePinned,
 }
 
 impl Index {
+    fn kind(&self) -> IndexKind {
+        match self {
+            #[cfg(feature = "git-index")]
+            Self::Git(_) => IndexKind::Git,
+            #[cfg(feature = "git-index")]
+            Self::GitPinned(_) => IndexKind::Git,
+            Self::SparseCached(_) | Self::SparsePinned => IndexKind::SparseCached,
+            Self::SparseRemote(_) => IndexKind::SparseRemote,
+        }
+    }
+
     #[inline]
     fn krate(&self, name: &package::Name) -> Result<Option<tame_index::IndexKrate>, Error> {
-        let name = name.as_str().try_into().map_err(Error::from_tame)?;
+        let krate_name = name.as_str().try_into().map_err(Error::from_tame)?;
         let res = match self {
-            Self::Git(gi) => gi.krate(name, true),
-            Self::SparseCached(si) => si.cached_krate(name),
-            Self::SparseRemote(rsi) => rsi.cached_krate(name),
+            #[cfg(feature = "git-index")]
+            Self::Git(gi) => gi
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .krate(krate_name, true),
+            #[cfg(feature = "git-index")]
+            Self::GitPinned(pinned) => return pinned.krate(name),
+            Self::SparseCached(si) => si.cached_krate(krate_name),
+            Self::SparseRemote(rsi) => rsi.cached_krate(krate_name),
+            Self::SparsePinned => fail!(
+                ErrorKind::NotCached,
+                "{} is not in the pinned index snapshot; save a new snapshot to look it up",
+                name
+            ),
         }
         .map_err(Error::from_tame)?;
 
@@ -33,18 +97,125 @@ impl Index {
     }
 }
 
+/// Kind of registry index a [`CachedIndex`] reads from
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum IndexKind {
+    /// Git index, which contains every crate as of the last fetch.
+    /// Large git indices such as the one of crates.io are slow to fetch.
+    ///
+    /// Only available with the `git-index` feature.
+    Git,
+
+    /// Local cache of a sparse index, containing only crates which were
+    /// previously downloaded (see [`CachedIndex::open`])
+    SparseCached,
+
+    /// Sparse index whose crates are downloaded on demand
+    /// (see [`CachedIndex::fetch`])
+    SparseRemote,
+}
+
+/// Hook invoked whenever a crate is fetched from a remote index over the
+/// network, see [`CachedIndex::set_fetch_hook`]
+pub type FetchHook = Arc<dyn Fn(&package::Name, FetchOutcome) + Send + Sync>;
+
+/// Outcome of fetching a crate from a remote index over the network
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum FetchOutcome {
+    /// The crate was found in the index
+    Found,
+
+    /// There is no such crate in the index
+    NotFound,
+
+    /// The crate couldn't be fetched, e.g. because of a network error
+    Failed,
+}
+
+/// Yanked status of packages looked up by [`CachedIndex::find_yanked_detailed`]
+#[derive(Debug)]
+pub struct YankReport<'a> {
+    /// Packages which have been yanked
+    pub yanked: Vec<&'a Package>,
+
+    /// Packages which haven't been yanked
+    pub not_yanked: Vec<&'a Package>,
+
+    /// Packages whose yanked status couldn't be determined, e.g. because they
+    /// are missing from the index or couldn't be retrieved from it
+    pub unknown: Vec<(&'a Package, Error)>,
+
+    /// Error of kind [`ErrorKind::IndexUnavailable`] if the index itself
+    /// couldn't be accessed, in which case the status of packages may be stale
+    pub index_error: Option<Error>,
+}
+
+/// Crate found by [`CachedIndex::versions_lenient`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LenientMatch {
+    /// Name the crate was found under in the index
+    pub name: package::Name,
+
+    /// Was the crate only found with hyphens and underscores swapped, rather
+    /// than under the name it was looked up by?
+    pub fuzzy: bool,
+
+    /// Known versions of the crate, and whether each of them is yanked
+    pub versions: HashMap<String, bool>,
+}
+
+/// Information about a crate cached in memory
+struct CacheEntry {
+    /// The inner hash map is logically HashMap<Version, IsYanked>
+    /// but we don't parse semver because crates.io registry contains invalid semver:
+    /// <https://github.com/rustsec/rustsec/issues/759>
+    ///
+    /// `Ok(None)` means there is no such crate, which is cached for good, while
+    /// errors may be transient and are only kept until the crate is looked up again.
+    krate: Result<Option<HashMap<String, bool>>, Error>,
+    /// When the crate was retrieved from the index
+    fetched_at: SystemTime,
+}
+
 /// Provides an efficient way to check if the given package has been yanked.
 ///
+/// This is usually the crates.io index, but alternate registries are supported
+/// via [`CachedIndex::fetch_with_url`] and [`CachedIndex::open_with_url`].
+///
 /// Operations on crates.io index are rather slow.
 /// Instead of peforming an index lookup for every version of every crate,
 /// this implementation looks up each crate only once and caches the result in memory.
+///
+/// All lookups take `&self`, so a single `CachedIndex` can be shared between threads
+/// (e.g. behind an [`Arc`](std::sync::Arc)). Reads of crates which are already cached
+/// do not block each other.
 pub struct CachedIndex {
     index: Index,
-    /// The inner hash map is logically HashMap<Version, IsYanked>
-    /// but we don't parse semver because crates.io registry contains invalid semver:
-    /// <https://github.com/rustsec/rustsec/issues/759>
-    // The outer map can later be changed to DashMap or some such for thread safety.
-    cache: HashMap<package::Name, Result<Option<HashMap<String, bool>>, Error>>,
+    /// Source of the packages this index contains
+    source: SourceId,
+    cache: DashMap<package::Name, CacheEntry>,
+    /// Timeout for requesting an individual crate from the remote sparse index
+    request_timeout: Option<Duration>,
+    /// Maximum number of crates requested from the remote sparse index at once
+    max_concurrent_requests: Option<usize>,
+    /// Report crates missing from the local sparse index cache as not cached
+    /// rather than not found
+    offline_strict: bool,
+    /// URL of the git index, for opening additional handles to it
+    #[cfg(feature = "parallel-index")]
+    git_url: Option<String>,
+    /// Directory the index is kept in, if not the default one
+    #[cfg(feature = "parallel-index")]
+    root: Option<tame_index::PathBuf>,
+    /// Look up crates in the git index from several threads at once
+    #[cfg(feature = "parallel-index")]
+    parallel_git_lookups: bool,
+    /// Hook invoked on each network fetch of a crate
+    fetch_hook: Option<FetchHook>,
+    /// Local directory of the index
+    path: tame_index::PathBuf,
 }
 
 impl CachedIndex {
@@ -58,7 +229,7 @@ impl CachedIndex {
     /// ## Locking
     ///
     /// This function will wait for up to `lock_timeout` for the filesystem lock on the repository.
-    /// It will fail with [`rustsec::Error::LockTimeout`](Error) if the lock is still held
+    /// It will fail with an error of kind [`ErrorKind::LockTimeout`] if the lock is still held
     /// after that time.
     ///
     /// If `lock_timeout` is set to `std::time::Duration::from_secs(0)`, it will not wait at all,
@@ -67,42 +238,129 @@ impl CachedIndex {
     /// Regardless of the timeout, this function relies on `panic = unwind` to avoid leaving stale locks
     /// if the process is interrupted with Ctrl+C. To support `panic = abort` you also need to register
     /// the `gix` signal handler to clean up the locks, see [`gix::interrupt::init_handler`].
+    ///
+    /// A failed git fetch is retried according to [`RetryPolicy::default`].
+    ///
+    /// The commit of a git index which was fetched is returned by [`CachedIndex::commit`],
+    /// so that later audits can be pinned to it with [`CachedIndex::open_pinned`].
     pub fn fetch(client: Option<ClientBuilder>, lock_timeout: Duration) -> Result<Self, Error> {
-        Self::fetch_inner(client, lock_timeout).map_err(Error::from_tame)
+        CachedIndexBuilder {
+            client,
+            ..Default::default()
+        }
+        .fetch(lock_timeout)
+    }
+
+    /// Create a builder for fetching an index with further options, such as the
+    /// URL of an alternate registry or the directory the index is kept in
+    pub fn builder() -> CachedIndexBuilder {
+        CachedIndexBuilder::new()
     }
 
-    fn fetch_inner(
+    /// Open the local copy of the registry index located at the given URL
+    ///
+    /// The URL is the one used in `Cargo.lock`, without the `registry+` prefix:
+    /// sparse indices must be prefixed with `sparse+`, anything else is treated
+    /// as a git index. Only packages published to this registry are checked by
+    /// [`CachedIndex::find_yanked`]; packages from other sources are skipped.
+    ///
+    /// Unlike crates.io, the registry is not assumed to support HTTP/2, see
+    /// [`CachedIndexBuilder::http2_prior_knowledge`].
+    ///
+    /// Otherwise this behaves like [`CachedIndex::fetch`], including locking.
+    pub fn fetch_with_url(
+        url: &str,
         client: Option<ClientBuilder>,
         lock_timeout: Duration,
-    ) -> Result<Self, tame_index::Error> {
-        let index = tame_index::index::ComboIndexCache::new(tame_index::IndexLocation::new(
-            tame_index::IndexUrl::crates_io(None, None, None)?,
-        ))?;
+    ) -> Result<Self, Error> {
+        CachedIndexBuilder {
+            client,
+            ..Default::default()
+        }
+        .url(url)
+        .fetch(lock_timeout)
+    }
+
+    #[cfg_attr(not(feature = "git-index"), allow(unused_variables))]
+    fn fetch_inner(options: CachedIndexBuilder, lock_timeout: Duration) -> Result<Self, Error> {
+        let CachedIndexBuilder {
+            url,
+            cache_dir,
+            client,
+            retry,
+            http2_prior_knowledge,
+            progress,
+        } = options;
+        let (url, source) = index_url_and_source(url.as_deref())?;
+        let root = cache_dir.as_deref().map(index_root).transpose()?;
+        let http2_prior_knowledge =
+            http2_prior_knowledge.unwrap_or_else(|| source.is_default_registry());
+
+        #[cfg(feature = "parallel-index")]
+        let git_url = (!url.is_sparse()).then(|| url.as_str().to_owned());
+        let path = index_path(url.as_str(), root.clone())?;
+        let index = tame_index::index::ComboIndexCache::new(
+            tame_index::IndexLocation::new(url).with_root(root.clone()),
+        )
+        .map_err(Error::from_tame)?;
 
         let index = match index {
+            #[cfg(feature = "git-index")]
             tame_index::index::ComboIndexCache::Git(gi) => {
-                let mut rgi = new_remote_git_index(gi, lock_timeout)?;
-                rgi.fetch()?;
-                Index::Git(rgi)
+                // Progress is discarded unless there is a callback for it
+                let git_progress = || {
+                    gix::progress::DoOrDiscard::from(progress.clone().map(CallbackProgress::new))
+                };
+
+                let mut rgi = new_remote_git_index(gi, lock_timeout, git_progress())
+                    .map_err(Error::from_tame)?;
+                // Network failures are often transient, but waiting on the lock again is pointless
+                retry
+                    .retry(
+                        || rgi.fetch_with_options(git_progress(), &gix::interrupt::IS_INTERRUPTED),
+                        |err| {
+                            !matches!(
+                                err,
+                                tame_index::Error::Git(tame_index::error::GitError::Lock(_))
+                            ) && !crate::error::is_lock_timeout(err)
+                        },
+                    )
+                    .map_err(Error::from_tame)?;
+                Index::Git(Mutex::new(rgi))
             }
+            #[cfg(not(feature = "git-index"))]
+            tame_index::index::ComboIndexCache::Git(_) => return Err(git_index_unsupported()),
             tame_index::index::ComboIndexCache::Sparse(si) => {
-                let client_builder = client.unwrap_or_default();
-                // note: this would need to change if rustsec ever adds the capability
-                // to query other indices that _might_ not support HTTP/2, but
-                // hopefully that would never need to happen
+                let mut client_builder = client.unwrap_or_default();
+                // crates.io is known to support HTTP/2, but alternate registries
+                // (e.g. mirrors behind a proxy) might not, so let them negotiate
+                if http2_prior_knowledge {
+                    client_builder = client_builder.http2_prior_knowledge();
+                }
                 let client = client_builder
-                    .http2_prior_knowledge()
                     .build()
-                    .map_err(tame_index::Error::from)?;
+                    .map_err(|err| Error::from_tame(err.into()))?;
 
                 Index::SparseRemote(tame_index::index::AsyncRemoteSparseIndex::new(si, client))
             }
-            _ => panic!("Unsupported crates.io index type"),
+            _ => panic!("Unsupported index type"),
         };
 
         Ok(CachedIndex {
             index,
+            source,
             cache: Default::default(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            max_concurrent_requests: None,
+            offline_strict: false,
+            #[cfg(feature = "parallel-index")]
+            git_url,
+            #[cfg(feature = "parallel-index")]
+            root,
+            #[cfg(feature = "parallel-index")]
+            parallel_git_lookups: true,
+            fetch_hook: None,
+            path,
         })
     }
 
@@ -115,7 +373,7 @@ impl CachedIndex {
     /// ## Locking
     ///
     /// This function will wait for up to `lock_timeout` for the filesystem lock on the repository.
-    /// It will fail with [`rustsec::Error::LockTimeout`](Error) if the lock is still held
+    /// It will fail with an error of kind [`ErrorKind::LockTimeout`] if the lock is still held
     /// after that time.
     ///
     /// If `lock_timeout` is set to `std::time::Duration::from_secs(0)`, it will not wait at all,
@@ -125,75 +383,422 @@ impl CachedIndex {
     /// if the process is interrupted with Ctrl+C. To support `panic = abort` you also need to register
     /// the `gix` signal handler to clean up the locks, see [`gix::interrupt::init_handler`].
     pub fn open(lock_timeout: Duration) -> Result<Self, Error> {
-        Self::open_inner(lock_timeout).map_err(Error::from_tame)
+        let url = tame_index::IndexUrl::crates_io(None, None, None).map_err(Error::from_tame)?;
+        Self::open_inner(url, SourceId::default(), None, lock_timeout)
     }
 
-    fn open_inner(lock_timeout: Duration) -> Result<Self, tame_index::Error> {
-        let index = tame_index::index::ComboIndexCache::new(tame_index::IndexLocation::new(
-            tame_index::IndexUrl::crates_io(None, None, None)?,
-        ))?;
+    /// Open the local copy of the registry index located at the given URL
+    ///
+    /// See [`CachedIndex::fetch_with_url`] for the URL format; otherwise this
+    /// behaves like [`CachedIndex::open`], including locking.
+    pub fn open_with_url(url: &str, lock_timeout: Duration) -> Result<Self, Error> {
+        let source = registry_source_id(url)?;
+        Self::open_inner(url.into(), source, None, lock_timeout)
+    }
+
+    /// Open the local copy of the registry index located at the given URL, or of
+    /// the crates.io index if `url` is `None`, which is kept in `cache_dir`
+    ///
+    /// See [`CachedIndexBuilder::cache_dir`] for how the directory is used;
+    /// otherwise this behaves like [`CachedIndex::open`], including locking.
+    pub fn open_with_cache_dir(
+        url: Option<&str>,
+        cache_dir: &Path,
+        lock_timeout: Duration,
+    ) -> Result<Self, Error> {
+        let (url, source) = index_url_and_source(url)?;
+        Self::open_inner(url, source, Some(index_root(cache_dir)?), lock_timeout)
+    }
+
+    /// Open the local copy of the registry index located at the given URL, or of
+    /// the crates.io index if `url` is `None`, answering all lookups from the given
+    /// snapshot of it, so that later audits produce the same results.
+    ///
+    /// A git index is read at the commit of [`IndexSnapshot::GitCommit`], which
+    /// has to be present in the local copy, e.g. because it was returned by
+    /// [`CachedIndex::commit`] after an earlier fetch. Fetch the index first if it
+    /// isn't; later fetches don't change what the pinned index reads.
+    ///
+    /// A sparse index has no such commit, so it's pinned to the yank status saved
+    /// by [`CachedIndex::save_cache`] instead, see [`IndexSnapshot::SparseCache`].
+    /// Only the crates looked up before the cache was saved can be answered: looking
+    /// up any other crate fails with [`ErrorKind::NotCached`] rather than reaching out
+    /// to the registry, as its answer couldn't be reproduced. The cache file has to
+    /// have been saved for this index, and its crates don't expire.
+    ///
+    /// The index is kept in `cache_dir` if given, see [`CachedIndexBuilder::cache_dir`].
+    /// Otherwise this behaves like [`CachedIndex::open`], including locking.
+    pub fn open_pinned(
+        url: Option<&str>,
+        cache_dir: Option<&Path>,
+        snapshot: &IndexSnapshot,
+        lock_timeout: Duration,
+    ) -> Result<Self, Error> {
+        let (url, source) = index_url_and_source(url)?;
+        let root = cache_dir.map(index_root).transpose()?;
+        let mut cached_index = Self::open_inner(url, source, root, lock_timeout)?;
+
+        let pinned = match (snapshot, &cached_index.index) {
+            #[cfg(feature = "git-index")]
+            (IndexSnapshot::GitCommit(commit), Index::Git(_)) => Index::GitPinned(
+                PinnedCommit::resolve(cached_index.path.as_std_path(), commit)?,
+            ),
+            (IndexSnapshot::SparseCache(path), Index::SparseCached(_)) => {
+                let cache_file = cached_index.read_cache_file(path)?.ok_or_else(|| {
+                    format_err!(
+                        ErrorKind::BadParam,
+                        "{} is not a saved cache of the {}",
+                        path.display(),
+                        cached_index.source.display_index()
+                    )
+                })?;
+                cached_index.load_cache_file(cache_file, Duration::MAX)?;
+                Index::SparsePinned
+            }
+            _ => fail!(
+                ErrorKind::BadParam,
+                "the {} can't be pinned to {}",
+                cached_index.source.display_index(),
+                snapshot
+            ),
+        };
+
+        cached_index.index = pinned;
+        Ok(cached_index)
+    }
+
+    #[cfg_attr(not(feature = "git-index"), allow(unused_variables))]
+    fn open_inner(
+        url: tame_index::IndexUrl<'_>,
+        source: SourceId,
+        root: Option<tame_index::PathBuf>,
+        lock_timeout: Duration,
+    ) -> Result<Self, Error> {
+        #[cfg(feature = "parallel-index")]
+        let git_url = (!url.is_sparse()).then(|| url.as_str().to_owned());
+        let path = index_path(url.as_str(), root.clone())?;
+        let index = tame_index::index::ComboIndexCache::new(
+            tame_index::IndexLocation::new(url).with_root(root.clone()),
+        )
+        .map_err(Error::from_tame)?;
 
         let index = match index {
+            #[cfg(feature = "git-index")]
             tame_index::index::ComboIndexCache::Git(gi) => {
-                let rgi = new_remote_git_index(gi, lock_timeout)?;
-                Index::Git(rgi)
+                let rgi = new_remote_git_index(gi, lock_timeout, None.into())
+                    .map_err(Error::from_tame)?;
+                Index::Git(Mutex::new(rgi))
             }
+            #[cfg(not(feature = "git-index"))]
+            tame_index::index::ComboIndexCache::Git(_) => return Err(git_index_unsupported()),
             tame_index::index::ComboIndexCache::Sparse(si) => Index::SparseCached(si),
-            _ => panic!("Unsupported crates.io index type"),
+            _ => panic!("Unsupported index type"),
         };
 
         Ok(CachedIndex {
             index,
+            source,
             cache: Default::default(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            max_concurrent_requests: None,
+            offline_strict: false,
+            #[cfg(feature = "parallel-index")]
+            git_url,
+            #[cfg(feature = "parallel-index")]
+            root,
+            #[cfg(feature = "parallel-index")]
+            parallel_git_lookups: true,
+            fetch_hook: None,
+            path,
         })
     }
 
-    /// Populates the cache entries for all of the specified crates.
-    fn populate_cache(&mut self, packages: BTreeSet<&package::Name>) -> Result<(), Error> {
-        match &self.index {
-            Index::Git(_) | Index::SparseCached(_) => {
-                for pkg in packages {
-                    self.insert(pkg.to_owned(), self.index.krate(pkg));
-                }
+    /// Set the timeout for requesting an individual crate from the remote sparse index.
+    ///
+    /// If a crate fails to be requested for a retriable reason then it will be retried
+    /// until this time limit is reached, after which the crate is reported as an error.
+    /// `None` or a zero duration disables the per-crate timeout. Defaults to 10 seconds.
+    ///
+    /// Crates which time out are reported with an error of kind [`ErrorKind::Timeout`].
+    ///
+    /// This has no effect on the git index or the locally cached sparse index.
+    pub fn set_request_timeout(&mut self, timeout: Option<Duration>) {
+        self.request_timeout = timeout.filter(|t| !t.is_zero());
+    }
+
+    /// Limit how many crates are requested from the remote sparse index at once,
+    /// e.g. to stay within the rate limit of a mirror.
+    ///
+    /// Crates looked up together are requested in batches of at most `limit` crates,
+    /// each of which has to complete before the next one is started. `None` or zero
+    /// requests all of them at once, which is the default.
+    ///
+    /// This has no effect on the git index or the locally cached sparse index.
+    pub fn set_max_concurrent_requests(&mut self, limit: Option<usize>) {
+        self.max_concurrent_requests = limit.filter(|limit| *limit > 0);
+    }
+
+    /// Save the cached yank status of all crates to the given file, so that it can be
+    /// reused by a later run with [`CachedIndex::load_cache`].
+    ///
+    /// Crates which couldn't be retrieved from the index are not saved.
+    pub fn save_cache(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+
+        let crates = self
+            .cache
+            .iter()
+            .filter_map(|entry| {
+                let versions = entry.krate.as_ref().ok()?;
+                let crate_entry = CrateEntry {
+                    fetched_at: cache_file::to_unix_secs(entry.fetched_at),
+                    versions: versions
+                        .as_ref()
+                        .map(|versions| versions.clone().into_iter().collect()),
+                };
+                Some((entry.key().to_string(), crate_entry))
+            })
+            .collect();
+
+        let cache_file = CacheFile {
+            version: cache_file::FORMAT_VERSION,
+            source: self.source.to_string(),
+            crates,
+        };
+
+        let toml = toml::to_string(&cache_file).map_err(|e| {
+            format_err!(
+                ErrorKind::Io,
+                "couldn't serialize yanked crates cache: {}",
+                e
+            )
+        })?;
+
+        fs::write(path, toml)?;
+        Ok(())
+    }
+
+    /// Load the yank status of crates previously saved with [`CachedIndex::save_cache`].
+    ///
+    /// Crates which were retrieved from the index more than `ttl` ago are discarded,
+    /// so that stale yank status isn't used indefinitely. Crates which are already
+    /// cached in memory are kept as they are.
+    ///
+    /// A missing file, or one written for a different index or by an incompatible
+    /// version of this crate, is not an error: nothing is loaded from it.
+    pub fn load_cache(&self, path: impl AsRef<Path>, ttl: Duration) -> Result<(), Error> {
+        match self.read_cache_file(path.as_ref())? {
+            Some(cache_file) => self.load_cache_file(cache_file, ttl),
+            None => Ok(()),
+        }
+    }
+
+    /// Read a file written by [`CachedIndex::save_cache`], or `None` if it's
+    /// missing or wasn't written for this index by this version of the crate
+    fn read_cache_file(&self, path: &Path) -> Result<Option<CacheFile>, Error> {
+        let toml = match fs::read_to_string(path) {
+            Ok(toml) => toml,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let cache_file: CacheFile = toml::from_str(&toml).map_err(|e| {
+            format_err!(
+                ErrorKind::Parse,
+                "error parsing yanked crates cache {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+
+        if cache_file.version != cache_file::FORMAT_VERSION
+            || cache_file.source != self.source.to_string()
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(cache_file))
+    }
+
+    /// Cache the crates of the given cache file in memory, see [`CachedIndex::load_cache`]
+    fn load_cache_file(&self, cache_file: CacheFile, ttl: Duration) -> Result<(), Error> {
+        let now = SystemTime::now();
+
+        for (name, crate_entry) in cache_file.crates {
+            let fetched_at = cache_file::from_unix_secs(crate_entry.fetched_at);
+            let age = now.duration_since(fetched_at).unwrap_or_default();
+
+            if age > ttl {
+                continue;
             }
+
+            let name: package::Name = name.parse()?;
+
+            self.cache.entry(name).or_insert_with(|| CacheEntry {
+                krate: Ok(crate_entry
+                    .versions
+                    .map(|versions| versions.into_iter().collect())),
+                fetched_at,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// How long ago the local copy of the index was last updated, e.g. to warn
+    /// about auditing against a stale index opened with [`CachedIndex::open`].
+    ///
+    /// For a git index, this is the age of the commit checked out by the last
+    /// fetch. For a sparse index, it is the time since any crate was last
+    /// written to the local cache, so crates which were cached earlier may be
+    /// older still. For an index pinned to a snapshot with
+    /// [`CachedIndex::open_pinned`], it is the age of the snapshot.
+    ///
+    /// Returns `Ok(None)` if the age can't be determined, e.g. because nothing
+    /// has been cached locally yet or the timestamp lies in the future.
+    pub fn index_age(&self) -> Result<Option<Duration>, Error> {
+        let updated_at = match &self.index {
+            #[cfg(feature = "git-index")]
+            Index::Git(_) => head_commit_time(self.path.as_std_path())?,
+            #[cfg(feature = "git-index")]
+            Index::GitPinned(pinned) => pinned.time,
+            Index::SparseCached(_) | Index::SparseRemote(_) => {
+                newest_modification(self.path.as_std_path())?
+            }
+            Index::SparsePinned => self.cache.iter().map(|entry| entry.fetched_at).max(),
+        };
+
+        Ok(updated_at.and_then(|time| SystemTime::now().duration_since(time).ok()))
+    }
+
+    /// Names of the crates which have been looked up and are cached in memory,
+    /// sorted alphabetically.
+    ///
+    /// This includes crates which don't exist in the index, as well as crates
+    /// whose lookup failed and will be retried by the next lookup. The names are
+    /// copied, so that no part of the cache stays locked while they're used.
+    pub fn cached_names(&self) -> Vec<package::Name> {
+        let mut names: Vec<_> = self.cache.iter().map(|entry| entry.key().clone()).collect();
+        names.sort();
+        names
+    }
+
+    /// Number of crates cached in memory, see [`CachedIndex::cached_names`]
+    pub fn cache_len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Get the kind of index this reads from, i.e. whether it's a git or sparse index
+    pub fn kind(&self) -> IndexKind {
+        self.index.kind()
+    }
+
+    /// Commit of the git index which crates are read from, as a hex object ID.
+    ///
+    /// This is the commit checked out by the last fetch, or the one the index
+    /// was pinned to with [`CachedIndex::open_pinned`]. Recording it allows
+    /// pinning later audits to the same commit with [`IndexSnapshot::GitCommit`].
+    ///
+    /// Returns `None` for sparse indices, which don't have a revision for the
+    /// whole index, see [`IndexSnapshot`].
+    pub fn commit(&self) -> Option<String> {
+        match &self.index {
+            #[cfg(feature = "git-index")]
+            Index::Git(gi) => gi
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .local()
+                .head_commit()
+                .map(ToOwned::to_owned),
+            #[cfg(feature = "git-index")]
+            Index::GitPinned(pinned) => Some(pinned.id.to_string()),
+            Index::SparseCached(_) | Index::SparseRemote(_) | Index::SparsePinned => None,
+        }
+    }
+
+    /// Enable or disable strict offline mode.
+    ///
+    /// When a sparse index is opened with [`CachedIndex::open`], only crates which are
+    /// already cached locally can be looked up, so a crate missing from the local cache
+    /// may well exist in the registry. By default such crates are reported with
+    /// [`ErrorKind::NotFound`]; in strict offline mode they are reported with
+    /// [`ErrorKind::NotCached`] instead, so that users can be told to populate the index
+    /// by running with network access.
+    ///
+    /// This has no effect on git indices, whose local checkout contains every crate
+    /// as of the last fetch, nor on sparse indices opened with [`CachedIndex::fetch`],
+    /// which download missing crates on demand.
+    pub fn set_offline_strict(&mut self, offline_strict: bool) {
+        self.offline_strict = offline_strict;
+    }
+
+    /// Enable or disable looking up crates in a git index from several threads at once.
+    ///
+    /// Enabled by default. Each thread opens its own handle to the local copy of the
+    /// index, since a single handle can't be read from concurrently. Disabling this
+    /// reads crates one at a time, e.g. to compare the performance of both approaches.
+    ///
+    /// This has no effect on sparse indices, which are already queried in batches.
+    #[cfg(feature = "parallel-index")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "parallel-index")))]
+    pub fn set_parallel_git_lookups(&mut self, parallel: bool) {
+        self.parallel_git_lookups = parallel;
+    }
+
+    /// Set a hook to invoke whenever a crate is fetched over the network,
+    /// with the name of the crate and the outcome of the fetch, e.g. to
+    /// count how many crates were served from the cache.
+    ///
+    /// Crates which are already cached don't invoke the hook, and neither do
+    /// lookups in a git index or the locally cached sparse index, which don't
+    /// fetch individual crates over the network. By default there is no hook.
+    pub fn set_fetch_hook(&mut self, hook: Option<FetchHook>) {
+        self.fetch_hook = hook;
+    }
+
+    /// Populates the cache entries for all of the specified crates
+    /// which haven't been cached yet.
+    fn populate_cache(&self, packages: BTreeSet<&package::Name>) -> Result<(), Error> {
+        let packages = self.uncached(packages);
+
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        match &self.index {
+            #[cfg(feature = "parallel-index")]
+            Index::Git(_) if self.parallel_git_lookups => self.populate_git_parallel(packages),
+            #[cfg(feature = "git-index")]
+            Index::Git(_) | Index::GitPinned(_) => self.populate_locally(packages),
+            Index::SparseCached(_) | Index::SparsePinned => self.populate_locally(packages),
             Index::SparseRemote(rsi) => {
                 // Ensure we have a runtime
                 let rt = tame_index::external::tokio::runtime::Runtime::new().map_err(|err| {
-                    format_err!(
+                    Error::with_source(
                         ErrorKind::Registry,
-                        "unable to start a tokio runtime: {}",
-                        err
+                        &format!("unable to start a tokio runtime: {}", err),
+                        err,
                     )
                 })?;
                 let _rt = rt.enter();
 
-                /// This is the timeout per individual crate. If a crate fails to be
-                /// requested for a retriable reason then it will be retried until
-                /// this time limit is reached
-                const REQUEST_TIMEOUT: Option<Duration> = Some(Duration::from_secs(10));
-
-                let results = rsi
-                    .krates_blocking(
-                        packages
-                            .into_iter()
-                            .map(|p| p.as_str().to_owned())
-                            .collect(),
-                        true,
-                        REQUEST_TIMEOUT,
-                    )
-                    .map_err(|err| {
-                        format_err!(
-                            ErrorKind::Registry,
-                            "unable to acquire tokio runtime: {}",
-                            err
-                        )
-                    })?;
-
-                for (name, res) in results {
-                    self.insert(
-                        name.parse().expect("this was a package name before"),
-                        res.map_err(Error::from_tame),
-                    );
+                for batch in self.request_batches(packages) {
+                    let results = rsi
+                        .krates_blocking(batch, true, self.request_timeout)
+                        .map_err(|err| {
+                            Error::with_source(
+                                ErrorKind::Registry,
+                                &format!("unable to acquire tokio runtime: {}", err),
+                                err,
+                            )
+                        })?;
+
+                    for (name, res) in results {
+                        self.insert_fetched(
+                            name.parse().expect("this was a package name before"),
+                            res.map_err(Error::from_tame),
+                        );
+                    }
                 }
             }
         }
@@ -201,9 +806,134 @@ impl CachedIndex {
         Ok(())
     }
 
+    /// Populates the cache entries for the given crates from the local copy
+    /// of the index, one at a time.
+    fn populate_locally(&self, packages: BTreeSet<&package::Name>) {
+        for pkg in packages {
+            self.insert(pkg.to_owned(), self.index.krate(pkg));
+        }
+    }
+
+    /// Populates the cache entries for the given crates from the git index,
+    /// using a thread pool.
+    ///
+    /// Worker threads which can't open their own handle to the index fall back
+    /// to the shared one, which serializes their lookups.
+    #[cfg(feature = "parallel-index")]
+    fn populate_git_parallel(&self, packages: BTreeSet<&package::Name>) {
+        use rayon::prelude::*;
+
+        packages.into_par_iter().for_each_init(
+            || self.open_git_index(),
+            |git_index, pkg| {
+                let krate = match git_index {
+                    Some(git_index) => git_krate(git_index, pkg),
+                    None => self.index.krate(pkg),
+                };
+                self.insert(pkg.to_owned(), krate);
+            },
+        );
+    }
+
+    /// Open another handle to the local copy of the git index, if this is one
+    #[cfg(feature = "parallel-index")]
+    fn open_git_index(&self) -> Option<tame_index::index::RemoteGitIndex> {
+        let url = self.git_url.as_deref()?;
+        let location = tame_index::IndexLocation::new(url.into()).with_root(self.root.clone());
+
+        match tame_index::index::ComboIndexCache::new(location) {
+            // The index was already fetched, so there is no lock to wait for
+            Ok(tame_index::index::ComboIndexCache::Git(gi)) => {
+                new_remote_git_index(gi, Duration::from_secs(0), None.into()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Populates the cache entries for all of the specified crates
+    /// which haven't been cached yet, using the caller's async runtime.
+    ///
+    /// Only the remote sparse index performs network requests; the git index and
+    /// the locally cached sparse index are read from disk synchronously.
+    async fn populate_cache_async(&self, packages: BTreeSet<&package::Name>) -> Result<(), Error> {
+        let packages = self.uncached(packages);
+
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        match &self.index {
+            #[cfg(feature = "git-index")]
+            Index::Git(_) | Index::GitPinned(_) => self.populate_locally(packages),
+            Index::SparseCached(_) | Index::SparsePinned => self.populate_locally(packages),
+            Index::SparseRemote(rsi) => {
+                for batch in self.request_batches(packages) {
+                    let results = rsi.krates(batch, true, self.request_timeout).await;
+
+                    for (name, res) in results {
+                        self.insert_fetched(
+                            name.parse().expect("this was a package name before"),
+                            res.map_err(Error::from_tame),
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Filters out the crates which already have a cache entry.
+    ///
+    /// Crates whose previous lookup failed are kept, so that e.g. a network
+    /// failure is retried rather than poisoning the cache for the whole run.
+    fn uncached<'a>(&self, packages: BTreeSet<&'a package::Name>) -> BTreeSet<&'a package::Name> {
+        packages
+            .into_iter()
+            .filter(|pkg| {
+                self.cache
+                    .get(*pkg)
+                    .map_or(true, |entry| entry.krate.is_err())
+            })
+            .collect()
+    }
+
+    /// Splits the crates to request from the remote sparse index into batches
+    /// according to [`CachedIndex::set_max_concurrent_requests`]
+    fn request_batches(&self, packages: BTreeSet<&package::Name>) -> Vec<BTreeSet<String>> {
+        let names: Vec<String> = packages
+            .into_iter()
+            .map(|p| p.as_str().to_owned())
+            .collect();
+        let limit = self.max_concurrent_requests.unwrap_or(names.len()).max(1);
+
+        names
+            .chunks(limit)
+            .map(|batch| batch.iter().cloned().collect())
+            .collect()
+    }
+
+    /// Caches a crate fetched over the network, invoking the fetch hook
+    fn insert_fetched(
+        &self,
+        package: package::Name,
+        krate_res: Result<Option<tame_index::IndexKrate>, Error>,
+    ) {
+        if let Some(hook) = &self.fetch_hook {
+            let outcome = match &krate_res {
+                Ok(Some(_)) => FetchOutcome::Found,
+                Ok(None) => FetchOutcome::NotFound,
+                Err(_) => FetchOutcome::Failed,
+            };
+            hook(&package, outcome);
+        }
+
+        self.insert(package, krate_res);
+    }
+
     #[inline]
     fn insert(
-        &mut self,
+        &self,
         package: package::Name,
         krate_res: Result<Option<tame_index::IndexKrate>, Error>,
     ) {
@@ -216,58 +946,408 @@ impl CachedIndex {
             })
         });
 
-        self.cache.insert(package, krate_res);
+        self.cache.insert(
+            package,
+            CacheEntry {
+                krate: krate_res,
+                fetched_at: SystemTime::now(),
+            },
+        );
+    }
+
+    /// Get all known versions of the given crate, and whether each of them is yanked.
+    ///
+    /// Versions are keyed by their string representation rather than parsed,
+    /// because the crates.io index contains versions which aren't valid semver.
+    ///
+    /// Returns `Ok(None)` if there is no such crate in the index.
+    /// In strict offline mode, crates missing from the local cache of a sparse index
+    /// are reported as [`ErrorKind::NotCached`] instead (see [`CachedIndex::set_offline_strict`]).
+    pub fn versions(&self, name: &package::Name) -> Result<Option<HashMap<String, bool>>, Error> {
+        self.populate_cache(BTreeSet::from([name]))?;
+
+        let entry = self
+            .cache
+            .get(name)
+            .expect("cache entry was just populated");
+
+        match &entry.krate {
+            Ok(None) if self.is_cache_miss() => Err(self.not_cached(name)),
+            krate => krate.clone(),
+        }
+    }
+
+    /// Get all known versions of the given crate like [`CachedIndex::versions`],
+    /// falling back to the name with hyphens and underscores swapped if there is
+    /// no crate with the exact name.
+    ///
+    /// The index stores each crate under the exact name it was published with,
+    /// so `foo-bar` and `foo_bar` are different lookups, even though crates.io
+    /// doesn't allow publishing both. Names taken from `Cargo.toml` or
+    /// `Cargo.lock` are exact and should be looked up with [`CachedIndex::versions`];
+    /// this is meant for names from less reliable sources, such as Rust paths,
+    /// where hyphens are always written as underscores. Names mixing both are
+    /// tried with all hyphens replaced by underscores, and vice versa.
+    ///
+    /// Crates which were only found under another name are flagged with
+    /// [`LenientMatch::fuzzy`]. Returns `Ok(None)` if none of the names are in
+    /// the index. Errors looking up the exact name, including
+    /// [`ErrorKind::NotCached`] in strict offline mode, are returned without
+    /// trying the other names, as the crate may still exist under the exact name.
+    pub fn versions_lenient(&self, name: &package::Name) -> Result<Option<LenientMatch>, Error> {
+        if let Some(versions) = self.versions(name)? {
+            return Ok(Some(LenientMatch {
+                name: name.clone(),
+                fuzzy: false,
+                versions,
+            }));
+        }
+
+        let mut candidates = BTreeSet::new();
+        for (from, to) in [('-', "_"), ('_', "-")] {
+            let candidate = name.as_str().replace(from, to);
+            if candidate != name.as_str() {
+                candidates.insert(candidate.parse::<package::Name>()?);
+            }
+        }
+
+        for candidate in candidates {
+            if let Some(versions) = self.versions(&candidate)? {
+                return Ok(Some(LenientMatch {
+                    name: candidate,
+                    fuzzy: true,
+                    versions,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Is the given version of a crate yanked?
+    ///
+    /// The version is compared to those in the index as a string, without
+    /// parsing it as semver, so it can be taken straight from raw registry data.
+    ///
+    /// Fails with [`ErrorKind::NotFound`] if there is no such crate or version
+    /// in the index.
+    pub fn is_version_yanked(&self, name: &package::Name, version: &str) -> Result<bool, Error> {
+        self.populate_cache(BTreeSet::from([name]))?;
+        self.cached_version_yanked(name, version)
+    }
+
+    /// Is the latest version of the given crate yanked?
+    ///
+    /// Like on crates.io, the latest version is the highest release according
+    /// to semver precedence, or the highest pre-release if the crate has no
+    /// releases. Versions which aren't valid semver (see
+    /// [`CachedIndex::versions`]) are skipped, as there's no telling how they
+    /// compare to others. Versions differing only in their build metadata have
+    /// the same precedence; the tie is broken by comparing the build metadata
+    /// as [`Version`](crate::Version) does.
+    ///
+    /// Fails with [`ErrorKind::NotFound`] if there is no such crate in the
+    /// index or it has no published versions, and with [`ErrorKind::Version`]
+    /// if none of its versions are valid semver.
+    pub fn is_latest_yanked(&self, name: &package::Name) -> Result<bool, Error> {
+        let versions = match self.versions(name)? {
+            Some(versions) if versions.is_empty() => fail!(
+                ErrorKind::NotFound,
+                "{} has no published versions in {}",
+                name,
+                self.source.display_index()
+            ),
+            Some(versions) => versions,
+            None => fail!(
+                ErrorKind::NotFound,
+                "No such crate in {}: {}",
+                self.source.display_index(),
+                name
+            ),
+        };
+
+        versions
+            .iter()
+            .filter_map(|(version, yanked)| {
+                Some((version.parse::<crate::Version>().ok()?, *yanked))
+            })
+            .max_by(|(a, _), (b, _)| (a.pre.is_empty(), a).cmp(&(b.pre.is_empty(), b)))
+            .map(|(_, yanked)| yanked)
+            .ok_or_else(|| {
+                format_err!(
+                    ErrorKind::Version,
+                    "No version of {} in {} is valid semver",
+                    name,
+                    self.source.display_index()
+                )
+            })
     }
 
     /// Is the given package yanked?
-    fn is_yanked(&mut self, package: &Package) -> Result<bool, Error> {
+    fn is_yanked(&self, package: &Package) -> Result<bool, Error> {
         if !self.cache.contains_key(&package.name) {
             self.insert(package.name.to_owned(), self.index.krate(&package.name));
         }
 
-        match &self.cache[&package.name] {
-            Ok(Some(ik)) => match ik.get(&package.version.to_string()) {
+        self.cached_version_yanked(&package.name, &package.version.to_string())
+    }
+
+    /// Is the given version of a crate yanked, according to its cache entry?
+    fn cached_version_yanked(&self, name: &package::Name, version: &str) -> Result<bool, Error> {
+        let entry = self
+            .cache
+            .get(name)
+            .expect("cache entry was just populated");
+
+        match &entry.krate {
+            // All versions of a crate can be deleted from the index, which isn't
+            // the same as the particular version missing
+            Ok(Some(ik)) if ik.is_empty() => Err(format_err!(
+                ErrorKind::NotFound,
+                "{} has no published versions in {}",
+                name,
+                self.source.display_index()
+            )),
+            Ok(Some(ik)) => match ik.get(version) {
                 Some(is_yanked) => Ok(*is_yanked),
                 None => Err(format_err!(
                     ErrorKind::NotFound,
-                    "No such version in crates.io index: {} {}",
-                    &package.name,
-                    &package.version
+                    "No such version in {}: {} {}",
+                    self.source.display_index(),
+                    name,
+                    version
                 )),
             },
+            Ok(None) if self.is_cache_miss() => Err(self.not_cached(name)),
             Ok(None) => Err(format_err!(
                 ErrorKind::NotFound,
-                "No such crate in crates.io index: {}",
-                &package.name,
+                "No such crate in {}: {}",
+                self.source.display_index(),
+                name,
             )),
-            Err(err) => Err(format_err!(
-                ErrorKind::Registry,
-                "Failed to retrieve {} from crates.io index: {}",
-                &package.name,
-                err,
+            // Crates missing from a pinned snapshot are reported as such
+            Err(err) if err.kind() == ErrorKind::NotCached => Err(err.clone()),
+            // Keep the kind of other errors, so that e.g. timeouts can be told apart
+            Err(err) => Err(Error::with_source(
+                err.kind(),
+                &format!(
+                    "Failed to retrieve {} from {}: {}",
+                    name,
+                    self.source.display_index(),
+                    err,
+                ),
+                err.clone(),
             )),
         }
     }
 
+    /// Fetch the given crates from the index into the cache, without checking
+    /// whether any of their versions are yanked.
+    ///
+    /// This allows warming the cache ahead of time, e.g. in the background, so
+    /// that later lookups of these crates don't need to access the index.
+    /// Like [`CachedIndex::find_yanked`], it should be called with many crates
+    /// at once rather than one by one.
+    ///
+    /// Fails if the index itself couldn't be accessed. Crates which couldn't be
+    /// retrieved are looked up again, and reported, by subsequent lookups.
+    pub fn prefetch<'a, I>(&self, names: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = &'a package::Name>,
+    {
+        self.populate_cache(names.into_iter().collect())
+    }
+
     /// Iterate over the provided packages, returning a vector of the
     /// packages which have been yanked.
     ///
+    /// Lookups of individual packages which fail are reported as errors in place of
+    /// those packages. If the index itself couldn't be accessed, an additional error of
+    /// kind [`ErrorKind::IndexUnavailable`] is reported first; the underlying cause is
+    /// available via [`std::error::Error::source`].
+    ///
     /// This function should be called with many packages at once rather than one by one;
     /// that way it can download the status of a large number of packages at once from the sparse index
     /// very quickly, orders of magnitude faster than requesting packages one by one.
-    pub fn find_yanked<'a, I>(&mut self, packages: I) -> Vec<Result<&'a Package, Error>>
+    ///
+    /// It is safe to call this concurrently from several threads; crates which are
+    /// already cached are answered without waiting on other lookups.
+    pub fn find_yanked<'a, I>(&self, packages: I) -> Vec<Result<&'a Package, Error>>
     where
         I: IntoIterator<Item = &'a Package>,
     {
-        let mut yanked = Vec::new();
+        let dedup_packages: BTreeSet<&Package> = packages
+            .into_iter()
+            .filter(|pkg| self.is_indexed(pkg))
+            .collect();
+        let package_names: BTreeSet<&package::Name> =
+            dedup_packages.iter().map(|p| &p.name).collect();
+        let populated = self.populate_cache(package_names);
+
+        self.yanked_from_cache(dedup_packages, populated)
+    }
+
+    /// Find the packages of the given lockfile which have been yanked from
+    /// this registry, like [`CachedIndex::find_yanked`].
+    ///
+    /// Only packages published to this registry are looked up, in a single
+    /// batch: git and path dependencies, workspace members and packages from
+    /// other registries are skipped.
+    pub fn check_lockfile<'a>(&self, lockfile: &'a Lockfile) -> Vec<Result<&'a Package, Error>> {
+        self.find_yanked(
+            lockfile
+                .packages
+                .iter()
+                .filter(|package| package.source.is_some()),
+        )
+    }
+
+    /// Look up the yanked status of the provided packages, sorting each of them
+    /// into whether it's yanked, not yanked, or unknown.
+    ///
+    /// Unlike [`CachedIndex::find_yanked`], this also reports the packages which
+    /// aren't yanked, and attributes failed lookups to the packages they concern.
+    /// Packages which aren't published to this registry are skipped, and
+    /// duplicates are only reported once, sorted by name and version.
+    pub fn find_yanked_detailed<'a, I>(&self, packages: I) -> YankReport<'a>
+    where
+        I: IntoIterator<Item = &'a Package>,
+    {
+        let dedup_packages: BTreeSet<&Package> = packages
+            .into_iter()
+            .filter(|pkg| self.is_indexed(pkg))
+            .collect();
+        let package_names: BTreeSet<&package::Name> =
+            dedup_packages.iter().map(|p| &p.name).collect();
+        let populated = self.populate_cache(package_names);
+
+        let mut report = YankReport {
+            yanked: Vec::new(),
+            not_yanked: Vec::new(),
+            unknown: Vec::new(),
+            index_error: populated.err().map(|e| self.index_unavailable(e)),
+        };
+
+        for package in dedup_packages {
+            match self.is_yanked(package) {
+                Ok(true) => report.yanked.push(package),
+                Ok(false) => report.not_yanked.push(package),
+                Err(error) => report.unknown.push((package, error)),
+            }
+        }
+
+        report
+    }
 
-        let dedup_packages: BTreeSet<&Package> = packages.into_iter().collect();
+    /// Iterate over the provided packages, returning a vector of the
+    /// packages which have been yanked.
+    ///
+    /// This is the async equivalent of [`CachedIndex::find_yanked`]: instead of
+    /// starting its own Tokio runtime, it awaits the sparse index requests on the
+    /// runtime it is polled from, so it can be used from within an async context.
+    pub async fn find_yanked_async<'a, I>(&self, packages: I) -> Vec<Result<&'a Package, Error>>
+    where
+        I: IntoIterator<Item = &'a Package>,
+    {
+        let dedup_packages: BTreeSet<&Package> = packages
+            .into_iter()
+            .filter(|pkg| self.is_indexed(pkg))
+            .collect();
         let package_names: BTreeSet<&package::Name> =
             dedup_packages.iter().map(|p| &p.name).collect();
-        if let Err(e) = self.populate_cache(package_names) {
-            yanked.push(Err(Error::new(ErrorKind::Registry,
-                &format!("Failed to download crates.io index: {}\nData may be missing or stale when checking for yanked packages.", e)
-            )));
+        let populated = self.populate_cache_async(package_names).await;
+
+        self.yanked_from_cache(dedup_packages, populated)
+    }
+
+    /// Iterate over the provided packages, returning a vector of the
+    /// packages which have been yanked, in the order they first appear in `packages`.
+    ///
+    /// [`CachedIndex::find_yanked`] returns packages sorted by name and version instead;
+    /// otherwise the two behave identically, including deduplication and error reporting.
+    pub fn find_yanked_ordered<'a, I>(&self, packages: I) -> Vec<Result<&'a Package, Error>>
+    where
+        I: IntoIterator<Item = &'a Package>,
+    {
+        let mut seen = BTreeSet::new();
+        let dedup_packages: Vec<&Package> = packages
+            .into_iter()
+            .filter(|pkg| self.is_indexed(pkg) && seen.insert(*pkg))
+            .collect();
+        let package_names: BTreeSet<&package::Name> =
+            dedup_packages.iter().map(|p| &p.name).collect();
+        let populated = self.populate_cache(package_names);
+
+        self.yanked_from_cache(dedup_packages, populated)
+    }
+
+    /// Is the given package yanked?
+    ///
+    /// Prefer [`CachedIndex::find_yanked_async`] when checking many packages:
+    /// it fetches all of them from the sparse index at once.
+    pub async fn is_yanked_async(&self, package: &Package) -> Result<bool, Error> {
+        if !self.is_indexed(package) {
+            fail!(
+                ErrorKind::BadParam,
+                "{} is not published to the {}",
+                &package.name,
+                self.source.display_index()
+            );
+        }
+
+        self.populate_cache_async(BTreeSet::from([&package.name]))
+            .await?;
+        self.is_yanked(package)
+    }
+
+    /// Does a missing crate only mean it isn't cached locally, and should it be reported as such?
+    fn is_cache_miss(&self) -> bool {
+        self.offline_strict && matches!(self.index, Index::SparseCached(_))
+    }
+
+    /// Error for a crate which is missing from the local cache of a sparse index
+    fn not_cached(&self, name: &package::Name) -> Error {
+        format_err!(
+            ErrorKind::NotCached,
+            "{} is not cached locally; run with network access to populate the {}",
+            name,
+            self.source.display_index()
+        )
+    }
+
+    /// Is the given package published to the registry this index describes?
+    ///
+    /// Packages without a source are assumed to be.
+    fn is_indexed(&self, package: &Package) -> bool {
+        match &package.source {
+            Some(source) if self.source.is_default_registry() => source.is_default_registry(),
+            Some(source) => source.is_remote_registry() && source.url() == self.source.url(),
+            None => true,
+        }
+    }
+
+    /// Error for failing to access the index itself, which affects all
+    /// packages rather than a particular one
+    fn index_unavailable(&self, e: Error) -> Error {
+        Error::with_source(ErrorKind::IndexUnavailable,
+            &format!("Failed to download {}: {}\nData may be missing or stale when checking for yanked packages.", self.source.display_index(), e),
+            e,
+        )
+    }
+
+    /// Look up the yanked status of the given packages, which should have
+    /// already been fetched into the cache
+    fn yanked_from_cache<'a>(
+        &self,
+        dedup_packages: impl IntoIterator<Item = &'a Package>,
+        populated: Result<(), Error>,
+    ) -> Vec<Result<&'a Package, Error>> {
+        let mut yanked = Vec::new();
+
+        // A failure here affects all packages rather than a particular one,
+        // which is signalled with a distinct `ErrorKind`
+        if let Err(e) = populated {
+            yanked.push(Err(self.index_unavailable(e)));
         }
 
         for package in dedup_packages {
@@ -282,10 +1362,142 @@ impl CachedIndex {
     }
 }
 
+/// URL and source ID of the registry index at the given URL, or of crates.io if `url` is `None`
+fn index_url_and_source(url: Option<&str>) -> Result<(tame_index::IndexUrl<'_>, SourceId), Error> {
+    Ok(match url {
+        Some(url) => (url.into(), registry_source_id(url)?),
+        None => (
+            tame_index::IndexUrl::crates_io(None, None, None).map_err(Error::from_tame)?,
+            SourceId::default(),
+        ),
+    })
+}
+
+/// Convert a directory to keep the index in to the UTF-8 path `tame_index` expects
+fn index_root(cache_dir: &Path) -> Result<tame_index::PathBuf, Error> {
+    tame_index::PathBuf::from_path_buf(cache_dir.to_owned()).map_err(|path| {
+        format_err!(
+            ErrorKind::BadParam,
+            "index cache directory is not valid UTF-8: {}",
+            path.display()
+        )
+    })
+}
+
+/// Local directory of the registry index at the given URL, kept in `root` or
+/// the default location of Cargo if `None`
+fn index_path(url: &str, root: Option<tame_index::PathBuf>) -> Result<tame_index::PathBuf, Error> {
+    let (path, _url) = tame_index::IndexLocation::new(url.into())
+        .with_root(root)
+        .into_parts()
+        .map_err(Error::from_tame)?;
+    Ok(path)
+}
+
+/// Time of the commit checked out in the git index at the given path, or
+/// `None` if it doesn't have one yet
+#[cfg(feature = "git-index")]
+fn head_commit_time(path: &Path) -> Result<Option<SystemTime>, Error> {
+    let repo = gix::open(path).map_err(|err| {
+        format_err!(
+            ErrorKind::Repo,
+            "couldn't open git index at {}: {}",
+            path.display(),
+            err
+        )
+    })?;
+
+    let Ok(commit) = repo.head_commit() else {
+        return Ok(None);
+    };
+
+    let time = commit.time().map_err(|err| {
+        format_err!(
+            ErrorKind::Repo,
+            "couldn't read HEAD commit of git index at {}: {}",
+            path.display(),
+            err
+        )
+    })?;
+
+    Ok(u64::try_from(time.seconds)
+        .ok()
+        .map(cache_file::from_unix_secs))
+}
+
+/// Latest modification time of any file in the given directory or its
+/// subdirectories, or `None` if there are no files
+fn newest_modification(dir: &Path) -> Result<Option<SystemTime>, Error> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut newest = None;
+
+    for entry in entries {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+
+        let modified = if file_type.is_dir() {
+            newest_modification(&entry.path())?
+        } else if file_type.is_file() {
+            Some(entry.metadata()?.modified()?)
+        } else {
+            None
+        };
+
+        newest = newest.max(modified);
+    }
+
+    Ok(newest)
+}
+
+/// Get the `Cargo.lock` source of packages published to the registry at the given index URL
+fn registry_source_id(url: &str) -> Result<SourceId, Error> {
+    let source = if url.starts_with("sparse+") {
+        SourceId::from_url(url)
+    } else {
+        SourceId::from_url(&format!("registry+{}", url))
+    };
+
+    source.map_err(|err| {
+        format_err!(
+            ErrorKind::BadParam,
+            "invalid registry index URL {}: {}",
+            url,
+            err
+        )
+    })
+}
+
+/// Look up a crate in the given git index
+#[cfg(feature = "parallel-index")]
+fn git_krate(
+    index: &tame_index::index::RemoteGitIndex,
+    name: &package::Name,
+) -> Result<Option<tame_index::IndexKrate>, Error> {
+    let name = name.as_str().try_into().map_err(Error::from_tame)?;
+    index.krate(name, true).map_err(Error::from_tame)
+}
+
+/// Error for opening a git index without git support compiled in
+#[cfg(not(feature = "git-index"))]
+fn git_index_unsupported() -> Error {
+    format_err!(
+        ErrorKind::Registry,
+        "git registry indices are not supported: rustsec was built without the `git-index` feature"
+    )
+}
+
 /// Replacement to [tame_index::index::RemoteGitIndex::new] that also supports passing the lock timeout
+/// and reporting the progress of the initial clone
+#[cfg(feature = "git-index")]
 fn new_remote_git_index(
     index: tame_index::index::git::GitIndex,
     lock_timeout: Duration,
+    progress: gix::progress::DoOrDiscard<CallbackProgress>,
 ) -> Result<tame_index::index::RemoteGitIndex, tame_index::Error> {
     let lock_policy = if lock_timeout == Duration::from_secs(0) {
         gix::lock::acquire::Fail::Immediately
@@ -294,7 +1506,7 @@ fn new_remote_git_index(
     };
     tame_index::index::RemoteGitIndex::with_options(
         index,
-        gix::progress::Discard,
+        progress,
         &gix::interrupt::IS_INTERRUPTED,
         lock_policy,
     )
@@ -0,0 +1,111 @@
+//! Options for fetching a registry index
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use super::{CachedIndex, ClientBuilder, ProgressCallback, ProgressEvent, RetryPolicy};
+use crate::error::Error;
+
+/// Builder for fetching a [`CachedIndex`] with options that
+/// [`CachedIndex::fetch`] doesn't take, which can be freely combined.
+///
+/// ```no_run
+/// use rustsec::registry::{CachedIndex, RetryPolicy};
+/// use std::time::Duration;
+///
+/// let index = CachedIndex::builder()
+///     .url("sparse+https://index.example.com/")
+///     .cache_dir("/var/cache/rustsec")
+///     .retry(RetryPolicy::no_retry())
+///     .fetch(Duration::from_secs(30))?;
+/// # Ok::<(), rustsec::Error>(())
+/// ```
+#[derive(Default)]
+pub struct CachedIndexBuilder {
+    /// URL of the index, or crates.io if `None`
+    pub(super) url: Option<String>,
+    /// Directory taking the place of `$CARGO_HOME`
+    pub(super) cache_dir: Option<PathBuf>,
+    /// Client for a sparse index
+    pub(super) client: Option<ClientBuilder>,
+    /// How to retry a failed git fetch
+    pub(super) retry: RetryPolicy,
+    /// Whether to assume a sparse index supports HTTP/2, or only for crates.io if `None`
+    pub(super) http2_prior_knowledge: Option<bool>,
+    /// Callback receiving the progress of fetching a git index
+    pub(super) progress: Option<ProgressCallback>,
+}
+
+impl CachedIndexBuilder {
+    /// Create a builder for the crates.io index with the default options,
+    /// see [`CachedIndex::builder`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetch the registry index located at the given URL rather than crates.io
+    ///
+    /// The URL is the one used in `Cargo.lock`, without the `registry+` prefix,
+    /// see [`CachedIndex::fetch_with_url`].
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Keep the index in `cache_dir` rather than in the Cargo home directory
+    ///
+    /// The directory takes the place of `$CARGO_HOME`, i.e. the index is read from
+    /// and written to the same location within it as Cargo would use, so pointing
+    /// it at another Cargo home shares that one's copy of the index.
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    /// Use the given client builder for requests to a sparse index,
+    /// e.g. to configure a proxy
+    pub fn client(mut self, client: ClientBuilder) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Retry a failed git fetch according to `retry` rather than
+    /// [`RetryPolicy::default`]
+    ///
+    /// Failing to acquire the filesystem lock is not retried, since the lock
+    /// timeout already controls how long to wait for it.
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Choose whether to assume that a sparse index supports HTTP/2
+    ///
+    /// By default, HTTP/2 is assumed for crates.io, skipping the negotiation of
+    /// the HTTP version. HTTP(S) proxies which don't support HTTP/2 break this,
+    /// in which case this should be disabled to fall back to negotiating HTTP/2
+    /// or using HTTP/1.1. Alternate registries, which are more likely to be behind
+    /// such a proxy, don't assume HTTP/2 unless it's enabled here.
+    pub fn http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.http2_prior_knowledge = Some(enabled);
+        self
+    }
+
+    /// Report the progress of fetching a git index to the `progress` callback
+    ///
+    /// The initial download of a large git index such as the one of crates.io can
+    /// take minutes, so this allows rendering e.g. a progress bar in the meantime.
+    /// Sparse indices are only contacted when looking up crates, so no progress is
+    /// reported for them.
+    pub fn progress(mut self, progress: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Open the local copy of the index with these options, fetching it if it's
+    /// a git index
+    ///
+    /// Otherwise this behaves like [`CachedIndex::fetch`], including locking.
+    pub fn fetch(self, lock_timeout: Duration) -> Result<CachedIndex, Error> {
+        CachedIndex::fetch_inner(self, lock_timeout)
+    }
+}
@@ -0,0 +1,47 @@
+//! On-disk format used to persist the yank status cached by [`CachedIndex`](super::CachedIndex)
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Version of the on-disk format. Files with any other version are ignored.
+pub(super) const FORMAT_VERSION: u32 = 1;
+
+/// Contents of a cache file
+#[derive(Debug, Deserialize, Serialize)]
+pub(super) struct CacheFile {
+    /// Version of the on-disk format
+    pub version: u32,
+
+    /// Source of the packages in the index this cache was populated from
+    pub source: String,
+
+    /// Cached crates, keyed by crate name
+    #[serde(default)]
+    pub crates: BTreeMap<String, CrateEntry>,
+}
+
+/// Cached information about a single crate
+#[derive(Debug, Deserialize, Serialize)]
+pub(super) struct CrateEntry {
+    /// When this crate was retrieved from the index, in seconds since the UNIX epoch
+    pub fetched_at: u64,
+
+    /// Whether each version of the crate is yanked, or `None` if there is no such crate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub versions: Option<BTreeMap<String, bool>>,
+}
+
+/// Convert a timestamp to seconds since the UNIX epoch
+pub(super) fn to_unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Convert seconds since the UNIX epoch to a timestamp
+pub(super) fn from_unix_secs(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
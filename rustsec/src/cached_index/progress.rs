@@ -0,0 +1,169 @@
+//! Reporting the progress of fetching a git index
+
+use std::sync::Arc;
+
+#[cfg(feature = "git-index")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "git-index")]
+use tame_index::external::gix::progress::{
+    prodash::progress::UNKNOWN, Count, Id, MessageLevel, NestedProgress, Progress, Step,
+    StepShared, Unit,
+};
+
+/// Callback receiving the progress of fetching a git index,
+/// see [`CachedIndexBuilder::progress`](super::CachedIndexBuilder::progress)
+pub type ProgressCallback = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+/// Progress of fetching a git index, e.g. for rendering a progress bar.
+///
+/// Progress is reported as `gix` reports it, so tasks and their names may
+/// change between versions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// A task such as receiving objects was started, with the number of
+    /// steps it takes if known
+    Started {
+        /// Name of the task
+        task: String,
+        /// Total number of steps, if known
+        max: Option<usize>,
+    },
+
+    /// A task has advanced to the given step
+    Step {
+        /// Name of the task
+        task: String,
+        /// Current step
+        step: usize,
+        /// Total number of steps, if known
+        max: Option<usize>,
+    },
+
+    /// A task emitted a message
+    Message {
+        /// Name of the task
+        task: String,
+        /// The message
+        message: String,
+    },
+}
+
+/// Adapter from the progress reporting of `gix` to a [`ProgressCallback`].
+///
+/// This keeps `gix` types out of the public API, as its semver is bumped
+/// frequently.
+#[cfg(feature = "git-index")]
+#[derive(Clone)]
+pub(super) struct CallbackProgress {
+    callback: ProgressCallback,
+    task: String,
+    id: Id,
+    max: Option<Step>,
+    unit: Option<Unit>,
+    step: StepShared,
+}
+
+#[cfg(feature = "git-index")]
+impl CallbackProgress {
+    pub(super) fn new(callback: ProgressCallback) -> Self {
+        Self::with_task(callback, String::new(), UNKNOWN)
+    }
+
+    fn with_task(callback: ProgressCallback, task: String, id: Id) -> Self {
+        Self {
+            callback,
+            task,
+            id,
+            max: None,
+            unit: None,
+            step: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn report_step(&self) {
+        (self.callback)(&ProgressEvent::Step {
+            task: self.task.clone(),
+            step: self.step(),
+            max: self.max,
+        });
+    }
+}
+
+#[cfg(feature = "git-index")]
+impl Count for CallbackProgress {
+    fn set(&self, step: Step) {
+        self.step.store(step, Ordering::SeqCst);
+        self.report_step();
+    }
+
+    fn step(&self) -> Step {
+        self.step.load(Ordering::SeqCst)
+    }
+
+    fn inc_by(&self, step: Step) {
+        self.step.fetch_add(step, Ordering::SeqCst);
+        self.report_step();
+    }
+
+    fn counter(&self) -> StepShared {
+        Arc::clone(&self.step)
+    }
+}
+
+#[cfg(feature = "git-index")]
+impl Progress for CallbackProgress {
+    fn init(&mut self, max: Option<Step>, unit: Option<Unit>) {
+        self.max = max;
+        self.unit = unit;
+        self.step.store(0, Ordering::SeqCst);
+        (self.callback)(&ProgressEvent::Started {
+            task: self.task.clone(),
+            max,
+        });
+    }
+
+    fn unit(&self) -> Option<Unit> {
+        self.unit.clone()
+    }
+
+    fn max(&self) -> Option<Step> {
+        self.max
+    }
+
+    fn set_max(&mut self, max: Option<Step>) -> Option<Step> {
+        std::mem::replace(&mut self.max, max)
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.task = name;
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(self.task.clone())
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn message(&self, _level: MessageLevel, message: String) {
+        (self.callback)(&ProgressEvent::Message {
+            task: self.task.clone(),
+            message,
+        });
+    }
+}
+
+#[cfg(feature = "git-index")]
+impl NestedProgress for CallbackProgress {
+    type SubProgress = Self;
+
+    fn add_child(&mut self, name: impl Into<String>) -> Self {
+        self.add_child_with_id(name, UNKNOWN)
+    }
+
+    fn add_child_with_id(&mut self, name: impl Into<String>, id: Id) -> Self {
+        Self::with_task(Arc::clone(&self.callback), name.into(), id)
+    }
+}
@@ -0,0 +1,104 @@
+//! Retrying operations which may fail transiently, e.g. due to a flaky network
+
+use std::{thread, time::Duration};
+
+/// How many times to attempt an operation which may fail transiently,
+/// and how long to wait between attempts.
+///
+/// The wait time doubles after every failed attempt (exponential backoff).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+
+    /// Time to wait after the first failed attempt
+    pub initial_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Make a single attempt, without retrying
+    pub const fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Run `op` until it succeeds, fails with an error that `is_retriable`
+    /// rejects, or the maximum number of attempts is reached.
+    // Only git fetches are retried
+    #[cfg_attr(not(feature = "git-index"), allow(dead_code))]
+    pub(crate) fn retry<T, E>(
+        &self,
+        mut op: impl FnMut() -> Result<T, E>,
+        is_retriable: impl Fn(&E) -> bool,
+    ) -> Result<T, E> {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+
+        loop {
+            match op() {
+                Err(err) if attempt < self.max_attempts && is_retriable(&err) => {
+                    thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, waiting one and then two seconds in between
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use std::time::Duration;
+
+    const POLICY: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::ZERO,
+    };
+
+    /// Operation which fails the given number of times before succeeding
+    fn flaky(failures: u32) -> impl FnMut() -> Result<u32, u32> {
+        let mut attempts = 0;
+        move || {
+            attempts += 1;
+            if attempts <= failures {
+                Err(attempts)
+            } else {
+                Ok(attempts)
+            }
+        }
+    }
+
+    #[test]
+    fn succeeds_after_failures() {
+        assert_eq!(POLICY.retry(flaky(2), |_| true), Ok(3));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        assert_eq!(POLICY.retry(flaky(5), |_| true), Err(3));
+    }
+
+    #[test]
+    fn does_not_retry_fatal_errors() {
+        assert_eq!(POLICY.retry(flaky(2), |_| false), Err(1));
+    }
+
+    #[test]
+    fn no_retry() {
+        assert_eq!(RetryPolicy::no_retry().retry(flaky(1), |_| true), Err(1));
+    }
+}
@@ -0,0 +1,159 @@
+//! Snapshots of an index which lookups can be pinned to, for reproducible results
+
+use std::{fmt, path::PathBuf};
+
+#[cfg(feature = "git-index")]
+use {
+    crate::{
+        error::{Error, ErrorKind},
+        package,
+    },
+    std::{path::Path, time::SystemTime},
+    tame_index::external::gix,
+};
+
+/// Snapshot of a registry index which a [`CachedIndex`](super::CachedIndex) can be
+/// pinned to with [`CachedIndex::open_pinned`](super::CachedIndex::open_pinned),
+/// so that auditing the same lockfile again later reports the same yanked crates.
+///
+/// A git index has a commit for every revision of the whole index, which is
+/// returned by [`CachedIndex::commit`](super::CachedIndex::commit) after a fetch.
+///
+/// Sparse indices have no such revision: every crate is downloaded on its own,
+/// with a revision (e.g. an `ETag`) which the registry only uses to tell whether
+/// the local copy is still current, and can't be used to request an older one.
+/// Snapshots of a sparse index are therefore the yank status saved by
+/// [`CachedIndex::save_cache`](super::CachedIndex::save_cache) instead, which
+/// only covers the crates looked up before it was saved.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum IndexSnapshot {
+    /// Commit of a git index, given as a hex object ID
+    GitCommit(String),
+
+    /// File the yank status of crates in a sparse index was saved to
+    SparseCache(PathBuf),
+}
+
+impl fmt::Display for IndexSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GitCommit(commit) => write!(f, "git commit {}", commit),
+            Self::SparseCache(path) => write!(f, "sparse index cache {}", path.display()),
+        }
+    }
+}
+
+/// Commit of a git index which crates are read from, regardless of the
+/// commit checked out by later fetches
+#[cfg(feature = "git-index")]
+pub(super) struct PinnedCommit {
+    repo: gix::ThreadSafeRepository,
+    /// ID of the commit
+    pub(super) id: gix::ObjectId,
+    /// ID of the tree of the commit, i.e. the root of the index
+    tree: gix::ObjectId,
+    /// When the commit was made
+    pub(super) time: Option<SystemTime>,
+}
+
+#[cfg(feature = "git-index")]
+impl PinnedCommit {
+    /// Resolve the given commit in the local copy of the git index at `path`
+    pub(super) fn resolve(path: &Path, commit: &str) -> Result<Self, Error> {
+        let repo = gix::open(path).map_err(|err| {
+            format_err!(
+                ErrorKind::Repo,
+                "couldn't open git index at {}: {}",
+                path.display(),
+                err
+            )
+        })?;
+
+        let not_found = |err: &dyn fmt::Display| {
+            format_err!(
+                ErrorKind::NotFound,
+                "commit {} not found in git index at {}; fetch the index first: {}",
+                commit,
+                path.display(),
+                err
+            )
+        };
+
+        let (id, tree, time) = {
+            let id = repo
+                .rev_parse_single(commit)
+                .map_err(|err| not_found(&err))?
+                .detach();
+            let commit = repo
+                .find_object(id)
+                .map_err(|err| not_found(&err))?
+                .try_into_commit()
+                .map_err(|err| not_found(&err))?;
+
+            let tree = commit
+                .tree_id()
+                .map_err(|err| repo_error(id, &err))?
+                .detach();
+            let time = commit.time().map_err(|err| repo_error(id, &err))?;
+
+            (id, tree, u64::try_from(time.seconds).ok())
+        };
+
+        Ok(Self {
+            repo: repo.into_sync(),
+            id,
+            tree,
+            time: time.map(super::cache_file::from_unix_secs),
+        })
+    }
+
+    /// Read the given crate from the tree of the commit, or `None` if there is
+    /// no such crate in it
+    pub(super) fn krate(
+        &self,
+        name: &package::Name,
+    ) -> Result<Option<tame_index::IndexKrate>, Error> {
+        let repo = self.repo.to_thread_local();
+        let mut tree = repo
+            .find_object(self.tree)
+            .map_err(|err| repo_error(self.id, &err))?
+            .into_tree();
+
+        let Some(entry) = tree
+            .peel_to_entry_by_path(crate_path(name.as_str()))
+            .map_err(|err| repo_error(self.id, &err))?
+        else {
+            return Ok(None);
+        };
+
+        let blob = entry.object().map_err(|err| repo_error(self.id, &err))?;
+        tame_index::IndexKrate::from_slice(&blob.data)
+            .map(Some)
+            .map_err(Error::from_tame)
+    }
+}
+
+/// Error for failing to read a commit of the git index
+#[cfg(feature = "git-index")]
+fn repo_error(commit: gix::ObjectId, err: &dyn fmt::Display) -> Error {
+    format_err!(
+        ErrorKind::Repo,
+        "couldn't read commit {} of git index: {}",
+        commit,
+        err
+    )
+}
+
+/// Path of the file describing the given crate within an index, e.g. `se/rd/serde`
+#[cfg(feature = "git-index")]
+fn crate_path(name: &str) -> String {
+    let name = name.to_lowercase();
+
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
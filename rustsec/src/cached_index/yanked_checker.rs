@@ -0,0 +1,117 @@
+//! Abstraction over sources of the yanked status of packages
+
+use super::CachedIndex;
+use crate::{
+    error::Error,
+    package::{self, Package},
+};
+
+/// Source of the yanked status of packages, implemented by [`CachedIndex`].
+///
+/// Code which only checks whether packages are yanked can take any
+/// `YankedChecker` (or a `&dyn YankedChecker`) rather than a [`CachedIndex`],
+/// so that its tests can substitute an in-memory fake for an index backed by
+/// the network or filesystem.
+pub trait YankedChecker {
+    /// Is the given version of a crate yanked?
+    ///
+    /// See [`CachedIndex::is_version_yanked`], including which errors are expected
+    /// for crates and versions missing from the index.
+    fn is_version_yanked(&self, name: &package::Name, version: &str) -> Result<bool, Error>;
+
+    /// Find which of the given packages have been yanked, reporting lookups
+    /// which fail as errors in place of those packages.
+    ///
+    /// By default, the packages are looked up one at a time with
+    /// [`YankedChecker::is_version_yanked`]. [`CachedIndex`] looks them up in a
+    /// single batch instead, see [`CachedIndex::find_yanked`].
+    fn find_yanked<'a>(&self, packages: &[&'a Package]) -> Vec<Result<&'a Package, Error>> {
+        packages
+            .iter()
+            .filter_map(|&package| {
+                match self.is_version_yanked(&package.name, &package.version.to_string()) {
+                    Ok(true) => Some(Ok(package)),
+                    Ok(false) => None,
+                    Err(err) => Some(Err(err)),
+                }
+            })
+            .collect()
+    }
+}
+
+impl YankedChecker for CachedIndex {
+    fn is_version_yanked(&self, name: &package::Name, version: &str) -> Result<bool, Error> {
+        CachedIndex::is_version_yanked(self, name, version)
+    }
+
+    fn find_yanked<'a>(&self, packages: &[&'a Package]) -> Vec<Result<&'a Package, Error>> {
+        CachedIndex::find_yanked(self, packages.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::YankedChecker;
+    use crate::{
+        error::{Error, ErrorKind},
+        package::{self, Package},
+    };
+
+    /// In-memory index of the yanked status of each version of some crates
+    struct MockIndex(Vec<(&'static str, &'static str, bool)>);
+
+    impl YankedChecker for MockIndex {
+        fn is_version_yanked(&self, name: &package::Name, version: &str) -> Result<bool, Error> {
+            self.0
+                .iter()
+                .find(|(n, v, _)| *n == name.as_str() && *v == version)
+                .map(|&(_, _, yanked)| yanked)
+                .ok_or_else(|| format_err!(ErrorKind::NotFound, "no such crate: {}", name))
+        }
+    }
+
+    fn package(name: &str, version: &str) -> Package {
+        Package {
+            name: name.parse().unwrap(),
+            version: version.parse().unwrap(),
+            source: None,
+            checksum: None,
+            dependencies: vec![],
+            replace: None,
+        }
+    }
+
+    /// Consumer code checking packages against any source of yanked status
+    fn yanked_names(checker: &dyn YankedChecker, packages: &[&Package]) -> Vec<String> {
+        checker
+            .find_yanked(packages)
+            .into_iter()
+            .map(|result| match result {
+                Ok(package) => package.name.to_string(),
+                Err(err) => format!("error: {}", err.kind()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_yanked_with_mock() {
+        let index = MockIndex(vec![
+            ("yanked", "1.0.0", true),
+            ("yanked", "1.0.1", false),
+            ("clean", "0.1.0", false),
+        ]);
+
+        let (yanked, fixed, clean, missing) = (
+            package("yanked", "1.0.0"),
+            package("yanked", "1.0.1"),
+            package("clean", "0.1.0"),
+            package("missing", "0.1.0"),
+        );
+
+        assert_eq!(
+            yanked_names(&index, &[&yanked, &fixed, &clean, &missing]),
+            ["yanked", "error: not found"]
+        );
+        assert!(yanked_names(&index, &[&fixed, &clean]).is_empty());
+    }
+}
@@ -7,27 +7,41 @@ use std::{fmt, str::FromStr};
 /// Collections of packages (`crates` vs `rust`).
 ///
 /// Advisories are either filed against crates published to <https://crates.io>
-/// or packages provided by the Rust language itself (e.g. `std`, `rustdoc`)
+/// or packages provided by the Rust language itself (e.g. `std`, `rustdoc`).
+/// Advisories from any other collection directory of a database are grouped
+/// under [`Collection::Other`].
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum Collection {
     /// Crates published through crates.io
     Crates,
 
     /// Rust core vulnerabilities
     Rust,
+
+    /// Packages from a collection directory unknown to this crate.
+    ///
+    /// The name of the directory an advisory was loaded from is available from
+    /// [`Advisory::collection_dir`](crate::Advisory::collection_dir).
+    Other,
 }
 
 impl Collection {
-    /// Get all collections as a slice
+    /// Get all collections with their own directory in the database as a slice
     pub fn all() -> &'static [Self] {
         &[Collection::Crates, Collection::Rust]
     }
 
-    /// Get a `str` representing the kind of package
+    /// Get a `str` representing the kind of package.
+    ///
+    /// This is also the name of the collection's directory within the database,
+    /// except for [`Collection::Other`], which groups advisories from any other
+    /// directory.
     pub fn as_str(&self) -> &str {
         match self {
             Collection::Crates => "crates",
             Collection::Rust => "rust",
+            Collection::Other => "other",
         }
     }
 }
@@ -45,6 +59,7 @@ impl FromStr for Collection {
         Ok(match s {
             "crates" => Collection::Crates,
             "rust" => Collection::Rust,
+            "other" => Collection::Other,
             other => fail!(ErrorKind::Parse, "invalid package type: {}", other),
         })
     }
@@ -1,24 +1,40 @@
 //! Database containing `RustSec` security advisories
 
+mod diff;
 mod entries;
 mod index;
+mod lazy;
 mod query;
+mod statistics;
 
-pub use self::query::Query;
+pub use self::{
+    diff::DatabaseDiff,
+    lazy::LazyIter,
+    query::{Query, QueryBuilder},
+    statistics::DatabaseStats,
+};
 
-use self::{entries::Entries, index::Index};
+use self::{
+    entries::{Entries, Slot},
+    index::Index,
+};
 use crate::{
     advisory::{self, Advisory},
     collection::Collection,
-    error::Error,
+    error::{Error, ErrorKind},
     fs,
+    package::{self, Package},
+    report,
     vulnerability::Vulnerability,
-    Lockfile,
+    Lockfile, Version,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[cfg(feature = "git")]
-use crate::repository::git;
+use crate::{
+    repository::{git, signature::TrustedKey},
+    Set,
+};
 
 /// Iterator over entries in the database
 pub type Iter<'a> = std::slice::Iter<'a, Advisory>;
@@ -26,6 +42,9 @@ pub type Iter<'a> = std::slice::Iter<'a, Advisory>;
 /// Database of RustSec security advisories, indexed both by ID and collection
 #[derive(Debug)]
 pub struct Database {
+    /// Local path the database was loaded from
+    path: PathBuf,
+
     /// All advisories in the database
     advisories: Entries,
 
@@ -35,18 +54,122 @@ pub struct Database {
     /// Index of third party crates
     crate_index: Index,
 
+    /// Index of advisory aliases (e.g. CVE IDs)
+    alias_index: Index<String>,
+
+    /// Advisory files which failed to load
+    load_errors: Vec<(PathBuf, Error)>,
+
+    /// Advisory files which failed to load because another file declares
+    /// the same advisory ID, along with that ID
+    duplicates: Vec<(PathBuf, advisory::Id)>,
+
     /// Information about the last git commit to the database
     #[cfg(feature = "git")]
     latest_commit: Option<git::Commit>,
+
+    /// Keys every commit the database is updated to must be signed by, if it
+    /// was loaded with [`Database::load_from_repo_verified`]
+    #[cfg(feature = "git")]
+    trusted_keys: Option<Vec<TrustedKey>>,
 }
 
 impl Database {
     /// Open [`Database`] located at the given local path
+    ///
+    /// Advisory files which fail to load are skipped, and their errors are
+    /// available from [`Database::load_errors`]. This includes advisories
+    /// whose ID doesn't match their file name, or whose package doesn't match
+    /// the directory they're in, which fail with [`ErrorKind::Repo`](crate::ErrorKind::Repo).
     pub fn open(path: &Path) -> Result<Self, Error> {
-        let mut advisory_paths = vec![];
+        Self::load(path, &default_collection_dirs(), false)
+    }
+
+    /// Open [`Database`] located at the given local path, failing if any
+    /// advisory file in it can't be loaded.
+    pub fn open_strict(path: &Path) -> Result<Self, Error> {
+        Self::load(path, &default_collection_dirs(), true)
+    }
+
+    /// Open [`Database`] from a plain directory of advisories, e.g. ones
+    /// vendored into another repository, which needn't be a git checkout.
+    ///
+    /// The directory must be laid out like the advisory database, with
+    /// advisories in `crates/<package>/<id>.md` (or `rust/<package>/<id>.md`).
+    /// Unlike [`Database::open`], which loads nothing from a directory
+    /// without this layout, this fails with an error of kind
+    /// [`ErrorKind::Repo`] if the layout is unexpected: if there's neither a
+    /// `crates` nor a `rust` directory, if they contain files rather than
+    /// package directories, or if an advisory isn't located where its ID and
+    /// package say it should be. Advisories which fail to load also fail
+    /// opening the database, like with [`Database::open_strict`].
+    pub fn open_dir(path: &Path) -> Result<Self, Error> {
+        if !path.is_dir() {
+            fail!(
+                ErrorKind::NotFound,
+                "advisory directory not found: {}",
+                path.display()
+            );
+        }
+
+        let collection_dirs: Vec<_> = default_collection_dirs()
+            .into_iter()
+            .filter(|dir| path.join(dir).is_dir())
+            .collect();
+
+        if collection_dirs.is_empty() {
+            fail!(
+                ErrorKind::Repo,
+                "expected a `crates` or `rust` directory of advisories in {}",
+                path.display()
+            );
+        }
+
+        for collection_dir in &collection_dirs {
+            for dir_entry in fs::read_dir(path.join(collection_dir))? {
+                let dir_entry = dir_entry?;
+                let is_dotfile = dir_entry.file_name().to_string_lossy().starts_with('.');
+                if !is_dotfile && !dir_entry.file_type()?.is_dir() {
+                    fail!(
+                        ErrorKind::Repo,
+                        "expected only package directories in {}, found {}",
+                        path.join(collection_dir).display(),
+                        dir_entry.path().display()
+                    );
+                }
+            }
+        }
+
+        Self::load(path, &collection_dirs, true)
+    }
 
-        for collection in Collection::all() {
-            let collection_path = path.join(collection.as_str());
+    /// Open [`Database`] located at the given local path, loading advisories
+    /// from the given collection directories instead of only `crates` and
+    /// `rust`.
+    ///
+    /// Advisories in directories other than those of the known collections
+    /// belong to [`Collection::Other`], and the directory they were loaded from
+    /// is available from [`Advisory::collection_dir`]. Advisory files which fail
+    /// to load are skipped like with [`Database::open`].
+    pub fn open_with_collections(path: &Path, collection_dirs: &[&str]) -> Result<Self, Error> {
+        Self::load(path, collection_dirs, false)
+    }
+
+    /// Iterate over the advisories of the database located at the given local
+    /// path without loading all of them into memory at once.
+    ///
+    /// Advisory files are parsed one at a time as the iterator advances, and
+    /// those which fail to load are yielded as errors. Unlike when opening a
+    /// [`Database`], duplicate advisory IDs aren't detected.
+    pub fn iter_lazy(path: &Path) -> LazyIter {
+        LazyIter::new(path)
+    }
+
+    fn load(path: &Path, collection_dirs: &[&str], strict: bool) -> Result<Self, Error> {
+        let mut db = Self::empty(path);
+
+        for collection_dir in collection_dirs {
+            let collection_path = path.join(collection_dir);
 
             if let Ok(collection_entry) = fs::read_dir(&collection_path) {
                 for dir_entry in collection_entry {
@@ -61,37 +184,126 @@ impl Database {
                         if file_name.map_or(false, |f| f.starts_with('.')) {
                             continue;
                         }
-                        advisory_paths.push(advisory_path);
+                        db.load_file(advisory_path, strict)?;
                     }
                 }
             }
         }
 
-        let mut advisories = Entries::new();
-        let mut rust_index = Index::new();
-        let mut crate_index = Index::new();
+        Ok(db)
+    }
+
+    /// Load [`Database`] from a gzipped tar archive of an advisory database,
+    /// e.g. a pinned tarball of the `advisory-db` repository, without
+    /// extracting it to the filesystem.
+    ///
+    /// Advisories are read from the `crates` and `rust` directories, which may
+    /// be nested in a single top-level directory as is common for tarballs.
+    /// Like with [`Database::open`], advisory files which fail to load are
+    /// skipped, and their errors are available from [`Database::load_errors`]
+    /// along with their path within the archive. The [`Database::path`] of
+    /// the loaded database is empty.
+    #[cfg(feature = "archive")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "archive")))]
+    pub fn from_archive(reader: impl std::io::Read) -> Result<Self, Error> {
+        use std::io::Read;
 
-        for path in &advisory_paths {
-            if let Some(slot) = advisories.load_file(path)? {
-                let advisory = advisories.get(slot).unwrap();
-                match advisory.metadata.collection.unwrap() {
-                    Collection::Crates => {
-                        crate_index.insert(&advisory.metadata.package, slot);
-                    }
-                    Collection::Rust => {
-                        rust_index.insert(&advisory.metadata.package, slot);
-                    }
-                }
+        let mut db = Self::empty(Path::new(""));
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
             }
+
+            let path = entry.path()?.into_owned();
+            if !is_archived_advisory_path(&path) {
+                continue;
+            }
+
+            let mut advisory_data = String::new();
+            let loaded = match entry.read_to_string(&mut advisory_data) {
+                Ok(_) => entries::parse_advisory(&path, &advisory_data),
+                Err(err) => Err(err.into()),
+            };
+            db.insert_loaded(path, loaded, false)?;
         }
 
-        Ok(Self {
-            advisories,
-            crate_index,
-            rust_index,
+        Ok(db)
+    }
+
+    /// Create a database without any advisories
+    fn empty(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            advisories: Entries::new(),
+            crate_index: Index::new(),
+            rust_index: Index::new(),
+            alias_index: Index::new(),
+            load_errors: vec![],
+            duplicates: vec![],
             #[cfg(feature = "git")]
             latest_commit: None,
-        })
+            #[cfg(feature = "git")]
+            trusted_keys: None,
+        }
+    }
+
+    /// Load an advisory file into the database, recording the error if it
+    /// fails to load unless in strict mode
+    fn load_file(&mut self, path: PathBuf, strict: bool) -> Result<(), Error> {
+        let loaded = entries::load_advisory(&path);
+        self.insert_loaded(path, loaded, strict)
+    }
+
+    /// Insert an advisory loaded from the given path into the database,
+    /// recording the error if it failed to load unless in strict mode
+    fn insert_loaded(
+        &mut self,
+        path: PathBuf,
+        loaded: Result<Option<Advisory>, Error>,
+        strict: bool,
+    ) -> Result<(), Error> {
+        if let Ok(Some(advisory)) = &loaded {
+            if self.advisories.find_by_id(advisory.id()).is_some() {
+                self.duplicates.push((path.clone(), advisory.id().clone()));
+            }
+        }
+
+        let inserted = loaded.and_then(|advisory| {
+            advisory
+                .map(|advisory| self.advisories.insert(advisory))
+                .transpose()
+        });
+
+        match inserted {
+            Ok(Some(slot)) => self.index(slot),
+            Ok(None) => (),
+            Err(err) if strict => return Err(err),
+            Err(err) => self.load_errors.push((path, err)),
+        }
+
+        Ok(())
+    }
+
+    /// Add the advisory in the given slot to the indexes
+    fn index(&mut self, slot: Slot) {
+        let advisory = self.advisories.get(slot).unwrap();
+        match advisory.metadata.collection.unwrap() {
+            Collection::Crates => {
+                self.crate_index.insert(&advisory.metadata.package, slot);
+            }
+            Collection::Rust => {
+                self.rust_index.insert(&advisory.metadata.package, slot);
+            }
+            // Advisories from other collections are only found by scanning
+            Collection::Other => (),
+        }
+
+        for alias in &advisory.metadata.aliases {
+            self.alias_index.insert(&alias.to_string(), slot);
+        }
     }
 
     /// Load [`Database`] from the given [`git::Repository`]
@@ -102,33 +314,267 @@ impl Database {
         Ok(db)
     }
 
+    /// Load [`Database`] from the given [`git::Repository`] after verifying
+    /// its `HEAD` commit was signed by one of the given trusted keys.
+    ///
+    /// Fails with [`ErrorKind::Signature`] without loading any advisories if
+    /// the commit is unsigned, or its signature isn't valid for a trusted key.
+    ///
+    /// The trusted keys are kept, so that [`Database::update_from_repo`] only
+    /// accepts commits signed by them as well.
+    #[cfg(feature = "git")]
+    pub fn load_from_repo_verified(
+        repo: &git::Repository,
+        trusted_keys: &[TrustedKey],
+    ) -> Result<Self, Error> {
+        let latest_commit = repo.latest_commit()?;
+        latest_commit.verify_signature(trusted_keys)?;
+
+        let mut db = Self::open(repo.path())?;
+        db.latest_commit = Some(latest_commit);
+        db.trusted_keys = Some(trusted_keys.to_vec());
+        Ok(db)
+    }
+
+    /// Update a [`Database`] previously loaded from the given
+    /// [`git::Repository`] to the repository's current `HEAD`, e.g. after
+    /// fetching it again.
+    ///
+    /// Only the advisory files which changed between [`Database::latest_commit`]
+    /// and `HEAD` are parsed again, the rest are kept as they are. If the
+    /// database wasn't loaded from this repository, it is reloaded entirely.
+    ///
+    /// If the database was loaded with [`Database::load_from_repo_verified`],
+    /// this fails with [`ErrorKind::Signature`] and leaves the database as it
+    /// is unless `HEAD` is signed by one of the same trusted keys.
+    #[cfg(feature = "git")]
+    pub fn update_from_repo(&mut self, repo: &git::Repository) -> Result<(), Error> {
+        let latest_commit = repo.latest_commit()?;
+
+        if let Some(trusted_keys) = &self.trusted_keys {
+            latest_commit.verify_signature(trusted_keys)?;
+        }
+
+        let previous_commit_id = match &self.latest_commit {
+            Some(commit) if self.path == repo.path() => commit.commit_id,
+            _ => {
+                let trusted_keys = self.trusted_keys.clone();
+                *self = Self::load_from_repo(repo)?;
+                self.trusted_keys = trusted_keys;
+                return Ok(());
+            }
+        };
+
+        if previous_commit_id == latest_commit.commit_id {
+            return Ok(());
+        }
+
+        let changed_paths: Set<PathBuf> = repo
+            .changed_paths(previous_commit_id, latest_commit.commit_id)?
+            .into_iter()
+            .filter(|path| is_advisory_path(path))
+            .map(|path| self.path.join(path))
+            .collect();
+
+        let previous = std::mem::replace(self, Self::empty(repo.path()));
+
+        for advisory in previous.advisories {
+            // Advisories of other collections aren't loaded from repositories,
+            // so there are no changes to them to pick up
+            let changed = advisory_path(&advisory).map_or(false, |path| {
+                changed_paths.contains(&previous.path.join(path))
+            });
+
+            if !changed {
+                let slot = self.advisories.insert(advisory)?;
+                self.index(slot);
+            }
+        }
+
+        self.load_errors.extend(
+            previous
+                .load_errors
+                .into_iter()
+                .filter(|(path, _)| !changed_paths.contains(path)),
+        );
+        self.duplicates.extend(
+            previous
+                .duplicates
+                .into_iter()
+                .filter(|(path, _)| !changed_paths.contains(path)),
+        );
+
+        // Deleted advisories are simply not loaded again
+        for path in changed_paths {
+            if path.is_file() {
+                self.load_file(path, false)?;
+            }
+        }
+
+        self.latest_commit = Some(latest_commit);
+        self.trusted_keys = previous.trusted_keys;
+        Ok(())
+    }
+
     /// Fetch the default advisory database from GitHub
     #[cfg(feature = "git")]
     pub fn fetch() -> Result<Self, Error> {
         git::Repository::fetch_default_repo().and_then(|repo| Self::load_from_repo(&repo))
     }
 
+    /// Fetch an advisory database from the given git URL, e.g. an internal fork
+    /// of the default one containing additional advisories.
+    ///
+    /// The URL must use either the `https://` or the `file://` scheme. If
+    /// `git_ref` is given, that branch, tag or commit is loaded instead of the
+    /// remote `HEAD`. The repository is kept at [`git::Repository::path_for_url`].
+    ///
+    /// Fails with [`ErrorKind::Repo`] if the repository doesn't have the
+    /// layout of an advisory database, or if a clone of another repository
+    /// is already kept there.
+    #[cfg(feature = "git")]
+    pub fn fetch_from(url: &str, git_ref: Option<&str>) -> Result<Self, Error> {
+        Self::fetch_repo_from(url, git_ref).and_then(|repo| Self::load_from_repo(&repo))
+    }
+
+    /// Fetch an advisory database from the given git URL like [`Database::fetch_from`],
+    /// verifying that the loaded commit was signed by one of the given trusted keys
+    /// like [`Database::load_from_repo_verified`].
+    #[cfg(feature = "git")]
+    pub fn fetch_from_verified(
+        url: &str,
+        git_ref: Option<&str>,
+        trusted_keys: &[TrustedKey],
+    ) -> Result<Self, Error> {
+        Self::fetch_repo_from(url, git_ref)
+            .and_then(|repo| Self::load_from_repo_verified(&repo, trusted_keys))
+    }
+
+    /// Fetch the advisory database repository at the given git URL, see [`Database::fetch_from`]
+    #[cfg(feature = "git")]
+    fn fetch_repo_from(url: &str, git_ref: Option<&str>) -> Result<git::Repository, Error> {
+        let repo = git::Repository::fetch_custom(url, git_ref)?;
+
+        if !repo.path().join(Collection::Crates.as_str()).is_dir() {
+            fail!(
+                ErrorKind::Repo,
+                "{} is not an advisory database: missing `{}` directory",
+                url,
+                Collection::Crates
+            );
+        }
+
+        Ok(repo)
+    }
+
     /// Look up an advisory by an advisory ID (e.g. "RUSTSEC-YYYY-XXXX")
     pub fn get(&self, id: &advisory::Id) -> Option<&Advisory> {
         self.advisories.find_by_id(id)
     }
 
+    /// Find all advisories which list the given ID (e.g. "CVE-YYYY-XXXX") among their aliases
+    ///
+    /// Several advisories may share an alias. Withdrawn advisories are included.
+    pub fn find_by_alias(&self, alias: &str) -> Vec<&Advisory> {
+        self.alias_index
+            .get(alias)
+            .map(|slots| {
+                slots
+                    .map(|slot| self.advisories.get(*slot).unwrap())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Find advisories related to the advisory with the given ID, i.e. which
+    /// it lists as `related` or which list it (or one of its aliases) as
+    /// `related` themselves.
+    pub fn related(&self, id: &advisory::Id) -> Vec<&Advisory> {
+        let advisory = match self.get(id) {
+            Some(advisory) => advisory,
+            None => return vec![],
+        };
+
+        let is_referenced_by = |ids: &[advisory::Id], other: &Advisory| {
+            ids.iter()
+                .any(|id| id == other.id() || other.metadata.aliases.contains(id))
+        };
+
+        self.iter()
+            .filter(|other| other.id() != advisory.id())
+            .filter(|other| {
+                is_referenced_by(&advisory.metadata.related, other)
+                    || is_referenced_by(&other.metadata.related, advisory)
+            })
+            .collect()
+    }
+
+    /// Find all advisories for the crate with the given name, regardless of
+    /// which of its versions they affect, sorted by date (oldest first).
+    ///
+    /// Withdrawn advisories are included, and can be told apart with
+    /// [`Advisory::withdrawn`].
+    pub fn advisories_for_crate(&self, name: &package::Name) -> Vec<&Advisory> {
+        let mut advisories: Vec<&Advisory> = self
+            .crate_index
+            .get(name)
+            .map(|slots| {
+                slots
+                    .map(|slot| self.advisories.get(*slot).unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        advisories.sort_by(|a, b| a.date().cmp(b.date()).then_with(|| a.id().cmp(b.id())));
+        advisories
+    }
+
+    /// Search the titles and descriptions of advisories for the given text,
+    /// ignoring case.
+    ///
+    /// Advisories match if they contain every word of the text, in any order.
+    /// They're ranked by where it was found: first come advisories whose title
+    /// contains the whole text, then those whose title contains all of its
+    /// words, those whose description contains the whole text, and finally
+    /// those containing its words across both. Advisories of the same rank
+    /// keep the order of the database.
+    pub fn search(&self, text: &str) -> Vec<&Advisory> {
+        let phrase = normalize_text(text);
+        let words: Vec<&str> = phrase.split(' ').filter(|word| !word.is_empty()).collect();
+
+        if words.is_empty() {
+            return vec![];
+        }
+
+        let mut matches: Vec<(u8, &Advisory)> = self
+            .iter()
+            .filter_map(|advisory| Some((search_rank(advisory, &phrase, &words)?, advisory)))
+            .collect();
+        matches.sort_by_key(|(rank, _)| *rank);
+
+        matches.into_iter().map(|(_, advisory)| advisory).collect()
+    }
+
     /// Query the database according to the given query object
     pub fn query(&self, query: &Query) -> Vec<&Advisory> {
         // Use indexes if we know a package name and collection
         if let Some(name) = &query.package_name {
-            if let Some(collection) = query.collection {
-                return match collection {
-                    Collection::Crates => self.crate_index.get(name),
-                    Collection::Rust => self.rust_index.get(name),
-                }
-                .map(|slots| {
-                    slots
-                        .map(|slot| self.advisories.get(*slot).unwrap())
-                        .filter(|advisory| query.matches(advisory))
-                        .collect()
-                })
-                .unwrap_or_else(Vec::new);
+            let index = match query.collection {
+                Some(Collection::Crates) => Some(&self.crate_index),
+                Some(Collection::Rust) => Some(&self.rust_index),
+                _ => None,
+            };
+
+            if let Some(index) = index {
+                return index
+                    .get(name)
+                    .map(|slots| {
+                        slots
+                            .map(|slot| self.advisories.get(*slot).unwrap())
+                            .filter(|advisory| query.matches(advisory))
+                            .collect()
+                    })
+                    .unwrap_or_else(Vec::new);
             }
         }
 
@@ -139,9 +585,18 @@ impl Database {
 
     /// Find vulnerabilities in the provided `Lockfile` which match a given query.
     pub fn query_vulnerabilities(&self, lockfile: &Lockfile, query: &Query) -> Vec<Vulnerability> {
+        self.packages_vulnerabilities(&lockfile.packages, query)
+    }
+
+    /// Find vulnerabilities in the given packages
+    pub(crate) fn packages_vulnerabilities(
+        &self,
+        packages: &[Package],
+        query: &Query,
+    ) -> Vec<Vulnerability> {
         let mut vulns = vec![];
 
-        for package in &lockfile.packages {
+        for package in packages {
             let advisories = self.query(&query.clone().package(package));
 
             vulns.extend(
@@ -154,16 +609,162 @@ impl Database {
         vulns
     }
 
+    /// Find the advisories against the Rust toolchain which affect the given
+    /// version of it, see [`Query::toolchain_scope`]
+    pub fn toolchain_vulnerabilities(&self, rust_version: &Version) -> Vec<&Advisory> {
+        self.query(&Query::toolchain_scope(rust_version.clone()))
+    }
+
+    /// Highest CVSS severity among the advisories affecting the given version
+    /// of a package. Withdrawn advisories are ignored.
+    ///
+    /// Advisories without CVSS information are of unknown severity and don't
+    /// count towards the result, so this returns `None` if no advisory with a
+    /// CVSS vector affects the package.
+    pub fn max_severity(&self, package: &Package) -> Option<advisory::Severity> {
+        let query = Query::new()
+            .collection(Collection::Crates)
+            .withdrawn(false)
+            .package(package);
+
+        self.query(&query)
+            .into_iter()
+            .filter_map(|advisory| advisory.severity())
+            .max()
+    }
+
+    /// Count the advisories in the database by category
+    pub fn statistics(&self) -> DatabaseStats {
+        DatabaseStats::new(self.iter())
+    }
+
     /// Scan for vulnerabilities in the provided `Lockfile`.
     pub fn vulnerabilities(&self, lockfile: &Lockfile) -> Vec<Vulnerability> {
         self.query_vulnerabilities(lockfile, &Query::crate_scope())
     }
 
+    /// Scan for vulnerabilities in the provided `Lockfile` according to the
+    /// given report settings.
+    ///
+    /// Like [`Database::vulnerabilities`], but only matches the configured
+    /// target platform and severity, and skips advisories which are ignored
+    /// as of today or by a severity override as well as ignored packages.
+    ///
+    /// When CVSS metrics or severity overrides are configured, the adjusted
+    /// severity of each vulnerability is recorded in
+    /// [`Vulnerability::effective_severity`], and the applied override in
+    /// [`Vulnerability::severity_override`].
+    pub fn vulnerabilities_with_settings(
+        &self,
+        lockfile: &Lockfile,
+        settings: &report::Settings,
+    ) -> Vec<Vulnerability> {
+        self.vulnerabilities_with_overrides(&lockfile.packages, settings)
+            .into_iter()
+            .filter(|vuln| vuln.severity_override != Some(report::SeverityOverride::Ignore))
+            .collect()
+    }
+
+    /// Find vulnerabilities like [`Database::vulnerabilities_with_settings`],
+    /// in the given packages, but keep those ignored by
+    /// [`report::Settings::severity_overrides`]
+    pub(crate) fn vulnerabilities_with_overrides(
+        &self,
+        packages: &[Package],
+        settings: &report::Settings,
+    ) -> Vec<Vulnerability> {
+        let today = advisory::Date::today();
+
+        self.packages_vulnerabilities(packages, &settings.query())
+            .into_iter()
+            .filter(|vuln| {
+                !settings.is_ignored(&vuln.advisory.id, &today)
+                    && !settings.is_package_ignored(&vuln.package)
+                    && !settings.is_below_severity(&vuln.advisory)
+            })
+            .map(|mut vuln| {
+                vuln.effective_severity = settings.adjusted_severity(&vuln.advisory);
+                vuln.severity_override =
+                    settings.severity_overrides.get(&vuln.advisory.id).copied();
+                vuln
+            })
+            .collect()
+    }
+
+    /// Compare this database with a newer version of it, e.g. after fetching
+    /// updates, finding which advisories were added, removed or modified.
+    ///
+    /// Advisories count as modified if any of the fields which determine what
+    /// they affect changed, such as their versions, CVSS score or withdrawal.
+    pub fn diff(&self, other: &Database) -> DatabaseDiff {
+        DatabaseDiff::new(self.iter(), other.iter())
+    }
+
+    /// Merge the advisories of another database into this one, e.g. to audit
+    /// against internal advisories alongside the official ones.
+    ///
+    /// When both databases contain an advisory with the same ID, the one from
+    /// `other` replaces the one in this database. Load errors and duplicate
+    /// IDs of both are kept, while the path and latest commit of this database are unchanged.
+    pub fn merge(&mut self, other: Database) {
+        let path = self.path.clone();
+        let previous = std::mem::replace(self, Self::empty(&path));
+
+        let mut advisories: Vec<_> = previous
+            .advisories
+            .into_iter()
+            .filter(|advisory| other.get(advisory.id()).is_none())
+            .collect();
+        advisories.extend(other.advisories);
+
+        for advisory in advisories {
+            let slot = self
+                .advisories
+                .insert(advisory)
+                .expect("advisory IDs are unique after merging");
+            self.index(slot);
+        }
+
+        self.load_errors = previous.load_errors;
+        self.load_errors.extend(other.load_errors);
+        self.duplicates = previous.duplicates;
+        self.duplicates.extend(other.duplicates);
+
+        #[cfg(feature = "git")]
+        {
+            self.latest_commit = previous.latest_commit;
+        }
+    }
+
     /// Iterate over all of the advisories in the database
     pub fn iter(&self) -> Iter<'_> {
         self.advisories.iter()
     }
 
+    /// Advisory files which were skipped because they failed to load, along
+    /// with the reason why
+    pub fn load_errors(&self) -> &[(PathBuf, Error)] {
+        &self.load_errors
+    }
+
+    /// Advisory IDs declared by more than one advisory file, e.g. after a bad
+    /// merge, sorted and without repetitions.
+    ///
+    /// Only the first of these files to be loaded is kept, while the others
+    /// are skipped and listed in [`Database::load_errors`]. Opening a
+    /// database in strict mode fails on the first duplicate instead.
+    pub fn duplicate_ids(&self) -> Vec<&advisory::Id> {
+        let mut ids: Vec<_> = self.duplicates.iter().map(|(_, id)| id).collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Local path the database was loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Get information about the latest commit to the repo
     #[cfg(feature = "git")]
     pub fn latest_commit(&self) -> Option<&git::Commit> {
@@ -180,3 +781,80 @@ impl IntoIterator for Database {
         self.advisories.into_iter()
     }
 }
+
+/// Lowercase the given text and collapse its whitespace, for searching it
+fn normalize_text(text: &str) -> String {
+    text.to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Rank of an advisory in the results of [`Database::search`] for the given
+/// normalized phrase and its words, lower being better, or `None` if it
+/// doesn't match
+fn search_rank(advisory: &Advisory, phrase: &str, words: &[&str]) -> Option<u8> {
+    let title = normalize_text(&advisory.metadata.title);
+    let description = normalize_text(&advisory.metadata.description);
+
+    if title.contains(phrase) {
+        Some(0)
+    } else if words.iter().all(|word| title.contains(word)) {
+        Some(1)
+    } else if description.contains(phrase) {
+        Some(2)
+    } else if words
+        .iter()
+        .all(|word| title.contains(word) || description.contains(word))
+    {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// Directories of the collections [`Database::open`] loads advisories from
+fn default_collection_dirs() -> Vec<&'static str> {
+    Collection::all().iter().map(Collection::as_str).collect()
+}
+
+/// Path of an advisory relative to the root of the database, or `None` if
+/// its collection directory isn't known because it wasn't loaded from one
+#[cfg(feature = "git")]
+pub(crate) fn advisory_path(advisory: &Advisory) -> Option<PathBuf> {
+    Some(
+        Path::new(advisory.collection_dir()?)
+            .join(advisory.metadata.package.as_str())
+            .join(format!("{}.md", advisory.metadata.id)),
+    )
+}
+
+/// Is the given path within an archive the location of an advisory file,
+/// i.e. `[<root>/]<collection>/<package>/<file>`?
+#[cfg(feature = "archive")]
+fn is_archived_advisory_path(path: &Path) -> bool {
+    let components: Vec<_> = path.iter().filter_map(|c| c.to_str()).collect();
+
+    match components.as_slice() {
+        [collection, _, file_name] | [_, collection, _, file_name] => {
+            Collection::all().iter().any(|c| c.as_str() == *collection)
+                && !file_name.starts_with('.')
+        }
+        _ => false,
+    }
+}
+
+/// Is the given path relative to the root of the database the location of an
+/// advisory file, i.e. `<collection>/<package>/<file>`?
+#[cfg(feature = "git")]
+fn is_advisory_path(path: &Path) -> bool {
+    let components: Vec<_> = path.iter().filter_map(|c| c.to_str()).collect();
+
+    match components.as_slice() {
+        [collection, _, file_name] => {
+            Collection::all().iter().any(|c| c.as_str() == *collection)
+                && !file_name.starts_with('.')
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,70 @@
+//! Differences between two versions of an advisory database
+
+use crate::{
+    advisory::{Advisory, Id},
+    Map, Set,
+};
+
+/// Advisories which changed between two versions of a
+/// [`Database`](crate::Database), see [`Database::diff`](crate::Database::diff).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DatabaseDiff {
+    /// IDs of advisories which were added
+    pub added: Set<Id>,
+
+    /// IDs of advisories which were removed
+    pub removed: Set<Id>,
+
+    /// IDs of advisories whose key metadata was modified, including ones
+    /// which were withdrawn
+    pub modified: Set<Id>,
+}
+
+impl DatabaseDiff {
+    /// Compute the differences between the advisories of an older and a newer
+    /// version of a database
+    pub(super) fn new<'a>(
+        old: impl Iterator<Item = &'a Advisory>,
+        new: impl Iterator<Item = &'a Advisory>,
+    ) -> Self {
+        let mut old: Map<&Id, &Advisory> = old.map(|advisory| (advisory.id(), advisory)).collect();
+        let mut diff = Self::default();
+
+        for advisory in new {
+            match old.remove(advisory.id()) {
+                Some(old_advisory) if is_modified(old_advisory, advisory) => {
+                    diff.modified.insert(advisory.id().clone());
+                }
+                Some(_) => (),
+                None => {
+                    diff.added.insert(advisory.id().clone());
+                }
+            }
+        }
+
+        diff.removed = old.into_keys().cloned().collect();
+        diff
+    }
+
+    /// Did any advisories change?
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Do the two versions of an advisory differ in any of the fields relevant to
+/// which packages are affected, and how?
+///
+/// Edits to prose like the description, keywords or references are ignored.
+fn is_modified(old: &Advisory, new: &Advisory) -> bool {
+    let (old_metadata, new_metadata) = (&old.metadata, &new.metadata);
+
+    old_metadata.package != new_metadata.package
+        || old_metadata.title != new_metadata.title
+        || old_metadata.aliases != new_metadata.aliases
+        || old_metadata.cvss != new_metadata.cvss
+        || old_metadata.informational != new_metadata.informational
+        || old_metadata.withdrawn != new_metadata.withdrawn
+        || old.affected != new.affected
+        || old.versions != new.versions
+}
@@ -33,99 +33,11 @@ impl Entries {
         Self::default()
     }
 
-    /// Load an advisory from a file and insert it into the database entry table
-    // TODO(tarcieri): factor more of this into `advisory.rs`?
-    pub fn load_file(&mut self, path: &Path) -> Result<Option<Slot>, Error> {
-        let mut advisory = Advisory::load_file(path)?;
-
-        // TODO(tarcieri): deprecate and remove legacy TOML-based advisory format
-        let expected_filename = match path.extension().and_then(|ext| ext.to_str()) {
-            Some("md") => OsString::from(format!("{}.md", advisory.metadata.id)),
-            _ => fail!(
-                ErrorKind::Repo,
-                "unexpected file extension: {}",
-                path.display()
-            ),
-        };
-
-        // Ensure advisory has the correct filename
-        if path.file_name().unwrap() != expected_filename {
-            fail!(
-                ErrorKind::Repo,
-                "expected {} to be named {:?}",
-                path.display(),
-                expected_filename
-            );
-        }
-
-        // Ensure advisory is in a directory named after its package
-        let package_dir = path.parent().ok_or_else(|| {
-            format_err!(
-                ErrorKind::Repo,
-                "advisory has no parent dir: {}",
-                path.display()
-            )
-        })?;
-
-        if package_dir.file_name().unwrap() != OsStr::new(advisory.metadata.package.as_str()) {
-            fail!(
-                ErrorKind::Repo,
-                "expected {} to be in {} directory (instead of \"{:?}\")",
-                advisory.metadata.id,
-                advisory.metadata.package,
-                package_dir
-            );
-        }
-
-        // Get the collection this advisory is part of
-        let collection_dir = package_dir
-            .parent()
-            .ok_or_else(|| {
-                format_err!(
-                    ErrorKind::Repo,
-                    "advisory has no collection: {}",
-                    path.display()
-                )
-            })?
-            .file_name()
-            .unwrap();
-
-        let collection = if collection_dir == OsStr::new(Collection::Crates.as_str()) {
-            Collection::Crates
-        } else if collection_dir == OsStr::new(Collection::Rust.as_str()) {
-            Collection::Rust
-        } else {
-            fail!(
-                ErrorKind::Repo,
-                "invalid package collection: {:?}",
-                collection_dir
-            );
-        };
-
-        match advisory.metadata.collection {
-            Some(c) => {
-                if c != collection {
-                    fail!(
-                        ErrorKind::Parse,
-                        "collection mismatch for {}",
-                        &advisory.metadata.id
-                    );
-                }
-            }
-            None => advisory.metadata.collection = Some(collection),
-        }
-
-        // Ensure placeholder advisories load and parse correctly, but
-        // don't actually insert them into the advisory database
-        if advisory.metadata.id.is_placeholder() {
-            return Ok(None);
-        }
-
-        let id = advisory.metadata.id.clone();
+    /// Insert an already loaded advisory into the database entry table
+    pub fn insert(&mut self, advisory: Advisory) -> Result<Slot, Error> {
         let slot = Slot(self.advisories.len());
-        self.advisories.push(advisory);
 
-        match self.index.entry(id) {
+        match self.index.entry(advisory.metadata.id.clone()) {
             map::Entry::Vacant(entry) => {
                 entry.insert(slot);
             }
@@ -134,7 +46,8 @@ impl Entries {
             }
         }
 
-        Ok(Some(slot))
+        self.advisories.push(advisory);
+        Ok(slot)
     }
 
     /// Find an advisory by its `advisory::Id`
@@ -162,3 +75,115 @@ impl IntoIterator for Entries {
         self.advisories.into_iter()
     }
 }
+
+/// Load an advisory from a file, ensuring it's located where the database
+/// expects it to be.
+///
+/// Placeholder advisories are loaded and parsed to ensure they're correct,
+/// but `None` is returned for them as they aren't part of the database.
+pub(crate) fn load_advisory(path: &Path) -> Result<Option<Advisory>, Error> {
+    locate_advisory(path, Advisory::load_file(path)?)
+}
+
+/// Parse an advisory which was read from the given path other than from the
+/// filesystem, e.g. within an archive, like [`load_advisory`]
+#[cfg(feature = "archive")]
+pub(crate) fn parse_advisory(path: &Path, advisory_data: &str) -> Result<Option<Advisory>, Error> {
+    let advisory = advisory_data
+        .parse()
+        .map_err(|e| format_err!(ErrorKind::Parse, "error parsing {}: {}", path.display(), e))?;
+
+    locate_advisory(path, advisory)
+}
+
+/// Ensure an advisory loaded from the given path is located where the
+/// database expects it to be, and set its collection accordingly
+// TODO(tarcieri): factor more of this into `advisory.rs`?
+fn locate_advisory(path: &Path, mut advisory: Advisory) -> Result<Option<Advisory>, Error> {
+    // TODO(tarcieri): deprecate and remove legacy TOML-based advisory format
+    let expected_filename = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("md") => OsString::from(format!("{}.md", advisory.metadata.id)),
+        _ => fail!(
+            ErrorKind::Repo,
+            "unexpected file extension: {}",
+            path.display()
+        ),
+    };
+
+    // Ensure advisory has the correct filename
+    if path.file_name().unwrap() != expected_filename {
+        fail!(
+            ErrorKind::Repo,
+            "expected {} to be named {:?}",
+            path.display(),
+            expected_filename
+        );
+    }
+
+    // Ensure advisory is in a directory named after its package
+    let package_dir = path.parent().ok_or_else(|| {
+        format_err!(
+            ErrorKind::Repo,
+            "advisory has no parent dir: {}",
+            path.display()
+        )
+    })?;
+
+    if package_dir.file_name().unwrap() != OsStr::new(advisory.metadata.package.as_str()) {
+        fail!(
+            ErrorKind::Repo,
+            "expected {} to be in {} directory (instead of {})",
+            advisory.metadata.id,
+            advisory.metadata.package,
+            package_dir.display()
+        );
+    }
+
+    // Get the collection this advisory is part of
+    let collection_dir = package_dir
+        .parent()
+        .ok_or_else(|| {
+            format_err!(
+                ErrorKind::Repo,
+                "advisory has no collection: {}",
+                path.display()
+            )
+        })?
+        .file_name()
+        .unwrap();
+
+    // Advisories in directories other than those of the known collections
+    // are loaded into a generic bucket rather than being rejected
+    let collection = if collection_dir == OsStr::new(Collection::Crates.as_str()) {
+        Collection::Crates
+    } else if collection_dir == OsStr::new(Collection::Rust.as_str()) {
+        Collection::Rust
+    } else {
+        Collection::Other
+    };
+
+    match advisory.metadata.collection {
+        Some(c) => {
+            if c != collection {
+                fail!(
+                    ErrorKind::Parse,
+                    "collection mismatch for {}",
+                    &advisory.metadata.id
+                );
+            }
+        }
+        None => advisory.metadata.collection = Some(collection),
+    }
+
+    if collection == Collection::Other {
+        advisory.collection_dir = Some(collection_dir.to_string_lossy().into_owned());
+    }
+
+    // Ensure placeholder advisories load and parse correctly, but
+    // don't actually insert them into the advisory database
+    if advisory.metadata.id.is_placeholder() {
+        return Ok(None);
+    }
+
+    Ok(Some(advisory))
+}
@@ -4,19 +4,20 @@ pub use crate::set::Iter;
 
 use super::entries::Slot;
 use crate::{map, package, Map, Set};
+use std::borrow::Borrow;
 
-/// Database index which maps package names to a set of advisory IDs
-#[derive(Debug, Default)]
-pub(crate) struct Index(Map<package::Name, Set<Slot>>);
+/// Database index which maps keys (package names by default) to a set of advisory IDs
+#[derive(Debug)]
+pub(crate) struct Index<K = package::Name>(Map<K, Set<Slot>>);
 
-impl Index {
+impl<K: Clone + Ord> Index<K> {
     /// Create a new index
     pub fn new() -> Self {
-        Self::default()
+        Self(Map::new())
     }
 
     /// Insert an entry into the index
-    pub fn insert(&mut self, key: &package::Name, slot: Slot) -> bool {
+    pub fn insert(&mut self, key: &K, slot: Slot) -> bool {
         let values = match self.0.entry(key.clone()) {
             map::Entry::Vacant(entry) => entry.insert(Set::new()),
             map::Entry::Occupied(entry) => entry.into_mut(),
@@ -25,8 +26,18 @@ impl Index {
         values.insert(slot)
     }
 
-    /// Get an iterator over advisory IDs for a given package name
-    pub fn get(&self, key: &package::Name) -> Option<Iter<'_, Slot>> {
+    /// Get an iterator over advisory IDs for a given key
+    pub fn get<Q>(&self, key: &Q) -> Option<Iter<'_, Slot>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         self.0.get(key).map(|set| set.iter())
     }
 }
+
+impl<K: Clone + Ord> Default for Index<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,108 @@
+//! Lazily loading the advisories in a database directory
+
+use super::entries;
+use crate::{advisory::Advisory, collection::Collection, error::Error, fs};
+use std::path::{Path, PathBuf};
+
+/// Iterator over the advisories in a database directory which loads them
+/// one file at a time, see [`Database::iter_lazy`](crate::Database::iter_lazy).
+///
+/// Files which fail to load are yielded as errors, and iteration continues
+/// with the next file.
+#[derive(Debug)]
+pub struct LazyIter {
+    /// Directories of the collections which haven't been visited yet
+    collections: std::vec::IntoIter<PathBuf>,
+
+    /// Package directories in the current collection
+    packages: Option<fs::ReadDir>,
+
+    /// Advisory files in the current package directory
+    advisories: Option<fs::ReadDir>,
+}
+
+impl LazyIter {
+    /// Iterate over the advisories in the database located at the given path
+    pub(super) fn new(path: &Path) -> Self {
+        let collections: Vec<_> = Collection::all()
+            .iter()
+            .map(|collection| path.join(collection.as_str()))
+            .collect();
+
+        Self {
+            collections: collections.into_iter(),
+            packages: None,
+            advisories: None,
+        }
+    }
+
+    /// Load the next advisory from the current package directory
+    fn next_advisory(&mut self) -> Option<Result<Advisory, Error>> {
+        let advisories = self.advisories.as_mut()?;
+
+        for advisory_entry in advisories {
+            let advisory_path = match advisory_entry {
+                Ok(entry) => entry.path(),
+                Err(err) => return Some(Err(err.into())),
+            };
+
+            // skip dotfiles like .DS_Store
+            let file_name = advisory_path.file_name().and_then(|f| f.to_str());
+            if file_name.map_or(false, |f| f.starts_with('.')) {
+                continue;
+            }
+
+            match entries::load_advisory(&advisory_path) {
+                Ok(Some(advisory)) => return Some(Ok(advisory)),
+                Ok(None) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        self.advisories = None;
+        None
+    }
+
+    /// Move on to the next package directory, returning `false` once the
+    /// current collection is exhausted
+    fn next_package(&mut self) -> Result<bool, Error> {
+        let packages = match self.packages.as_mut() {
+            Some(packages) => packages,
+            None => return Ok(false),
+        };
+
+        for dir_entry in packages {
+            let dir_entry = dir_entry?;
+            if dir_entry.file_type()?.is_dir() {
+                self.advisories = Some(fs::read_dir(dir_entry.path())?);
+                return Ok(true);
+            }
+        }
+
+        self.packages = None;
+        Ok(false)
+    }
+}
+
+impl Iterator for LazyIter {
+    type Item = Result<Advisory, Error>;
+
+    fn next(&mut self) -> Option<Result<Advisory, Error>> {
+        loop {
+            if let Some(result) = self.next_advisory() {
+                return Some(result);
+            }
+
+            match self.next_package() {
+                Ok(true) => continue,
+                Ok(false) => (),
+                Err(err) => return Some(Err(err)),
+            }
+
+            // Collections which don't exist are skipped, like when loading
+            // the whole database
+            let collection_path = self.collections.next()?;
+            self.packages = fs::read_dir(&collection_path).ok();
+        }
+    }
+}
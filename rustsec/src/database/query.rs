@@ -1,12 +1,15 @@
 //! Queries against the RustSec database
 //!
 use crate::{
-    advisory::{Advisory, Severity},
+    advisory::{affected::FunctionPath, Advisory, Cwe, Date, Severity},
     collection::Collection,
     package::{self, Package},
     SourceId,
 };
-use platforms::target::{Arch, OS};
+use platforms::{
+    target::{Arch, OS},
+    Platform,
+};
 use semver::Version;
 
 /// Queries against the RustSec database
@@ -36,6 +39,22 @@ pub struct Query {
     /// Year associated with the advisory ID
     year: Option<u32>,
 
+    /// Advisories must be dated after this day
+    after: Option<Date>,
+
+    /// Advisories must be dated before this day
+    before: Option<Date>,
+
+    /// Match `after` and `before` against the date advisories were withdrawn
+    /// on instead of their `date`
+    by_withdrawn_date: bool,
+
+    /// Weakness the advisory must be classified as
+    cwe: Option<Cwe>,
+
+    /// Path of a function (or module containing functions) which must be affected
+    affected_function: Option<String>,
+
     /// Query for withdrawn advisories
     /// (i.e. advisories which were soft-deleted from the database,
     /// as opposed to yanked crates)
@@ -43,6 +62,12 @@ pub struct Query {
 
     /// Query for informational advisories
     informational: Option<bool>,
+
+    /// Query for advisories with patched versions available
+    fixable: Option<bool>,
+
+    /// Do patched and unaffected ranges include pre-releases ordered in them?
+    include_prereleases: bool,
 }
 
 impl Query {
@@ -65,8 +90,15 @@ impl Query {
             target_arch: None,
             target_os: None,
             year: None,
+            after: None,
+            before: None,
+            by_withdrawn_date: false,
+            cwe: None,
+            affected_function: None,
             withdrawn: None,
             informational: None,
+            fixable: None,
+            include_prereleases: true,
         }
     }
 
@@ -82,6 +114,24 @@ impl Query {
             .informational(false)
     }
 
+    /// Create a new query for advisories against the Rust toolchain affecting
+    /// the given version of it, e.g. as parsed by
+    /// [`parse_rust_version`](crate::advisory::parse_rust_version):
+    ///
+    /// - Only `Collection::Rust`
+    /// - Ignore withdrawn advisories
+    /// - Ignore informational advisories
+    ///
+    /// Advisories of this collection concern components of the toolchain such
+    /// as `std` or `rustdoc`, whose `[versions]` are those of the toolchain.
+    pub fn toolchain_scope(rust_version: Version) -> Self {
+        Self::new()
+            .collection(Collection::Rust)
+            .package_version(rust_version)
+            .withdrawn(false)
+            .informational(false)
+    }
+
     /// Set collection to query against
     pub fn collection(mut self, collection: Collection) -> Self {
         self.collection = Some(collection);
@@ -124,7 +174,11 @@ impl Query {
         self
     }
 
-    /// Set target architecture
+    /// Set target architecture.
+    ///
+    /// Advisories restricted to other architectures in their `[affected]`
+    /// section (e.g. overflows which only occur on 32-bit targets) won't match,
+    /// while those without an `arch` restriction affect all architectures.
     pub fn target_arch(mut self, arch: Arch) -> Self {
         self.target_arch = Some(arch);
         self
@@ -136,12 +190,78 @@ impl Query {
         self
     }
 
+    /// Set the target platform, i.e. both its architecture and operating system.
+    ///
+    /// Advisories which only affect other platforms won't match, while those
+    /// without any platform constraints affect all platforms and always match.
+    pub fn target(self, platform: &Platform) -> Self {
+        self.target_arch(platform.target_arch)
+            .target_os(platform.target_os)
+    }
+
     /// Query for vulnerabilities occurring in a specific year.
     pub fn year(mut self, year: u32) -> Self {
         self.year = Some(year);
         self
     }
 
+    /// Query for advisories dated after the given day, e.g. to find those
+    /// published in the last week.
+    ///
+    /// This matches against the `date` field of advisories, i.e. when they
+    /// were reported, unless [`Query::by_withdrawn_date`] is set. Dates are
+    /// compared by calendar day, ignoring any time of day, and advisories
+    /// dated on the given day itself don't match.
+    pub fn after(mut self, date: Date) -> Self {
+        self.after = Some(date);
+        self
+    }
+
+    /// Query for advisories dated before the given day.
+    ///
+    /// Like [`Query::after`], this matches against the `date` field of
+    /// advisories unless [`Query::by_withdrawn_date`] is set, and advisories
+    /// dated on the given day itself don't match.
+    pub fn before(mut self, date: Date) -> Self {
+        self.before = Some(date);
+        self
+    }
+
+    /// Match [`Query::after`] and [`Query::before`] against the `withdrawn`
+    /// field of advisories instead of their `date`, e.g. to find advisories
+    /// withdrawn in the last week.
+    ///
+    /// Advisories which haven't been withdrawn never match date ranges then.
+    /// Note that withdrawn advisories are only included in query results if
+    /// [`Query::withdrawn`] or [`Query::include_withdrawn`] allow them.
+    pub fn by_withdrawn_date(mut self, setting: bool) -> Self {
+        self.by_withdrawn_date = setting;
+        self
+    }
+
+    /// Query for advisories classified as the given weakness, e.g.
+    /// `CWE-416` for all use after free vulnerabilities.
+    ///
+    /// Advisories without any CWE classification never match.
+    pub fn cwe(mut self, cwe: impl Into<Cwe>) -> Self {
+        self.cwe = Some(cwe.into());
+        self
+    }
+
+    /// Query for advisories affecting the function with the given canonical
+    /// path, e.g. `mycrate::module::Type::func`.
+    ///
+    /// The path can also be a prefix of the affected paths, e.g. a module, in
+    /// which case advisories affecting any function within it match. Likewise,
+    /// advisories listing a type match queries for its methods.
+    ///
+    /// Advisories which don't list any affected functions are considered to
+    /// affect all of them, and thus always match.
+    pub fn affected_function(mut self, path: &str) -> Self {
+        self.affected_function = Some(path.to_owned());
+        self
+    }
+
     /// Query for withdrawn advisories.
     ///
     /// By default they will be omitted from query results.
@@ -150,6 +270,16 @@ impl Query {
         self
     }
 
+    /// Include withdrawn advisories in query results alongside active ones,
+    /// rather than omitting them.
+    ///
+    /// Unlike [`Query::withdrawn`], passing `true` doesn't restrict the results
+    /// to withdrawn advisories.
+    pub fn include_withdrawn(mut self, setting: bool) -> Self {
+        self.withdrawn = if setting { None } else { Some(false) };
+        self
+    }
+
     /// Query for informational advisories. By default they will be omitted
     /// from query results.
     pub fn informational(mut self, setting: bool) -> Self {
@@ -157,6 +287,40 @@ impl Query {
         self
     }
 
+    /// Query for advisories which have been fixed, i.e. with at least one
+    /// patched version an affected package can be upgraded to.
+    ///
+    /// Passing `false` matches advisories without any patched versions.
+    pub fn fixable(mut self, setting: bool) -> Self {
+        self.fixable = Some(setting);
+        self
+    }
+
+    /// Query for advisories which haven't been fixed, i.e. without any
+    /// patched versions, so they can only be mitigated.
+    ///
+    /// This is the inverse of [`Query::fixable`].
+    pub fn unfixable(mut self, setting: bool) -> Self {
+        self.fixable = Some(!setting);
+        self
+    }
+
+    /// Set whether pre-release versions of a package are considered patched
+    /// when they're within a patched range by semver precedence (the default).
+    ///
+    /// Disabling this treats pre-releases the way Cargo does, see
+    /// [`Versions::is_affected_with_prereleases`](crate::advisory::Versions::is_affected_with_prereleases).
+    pub fn include_prereleases(mut self, setting: bool) -> Self {
+        self.include_prereleases = setting;
+        self
+    }
+
+    /// Get a [`QueryBuilder`] which starts out from this query, e.g. to
+    /// conditionally add filters to [`Query::crate_scope`].
+    pub fn into_builder(self) -> QueryBuilder {
+        QueryBuilder { query: self }
+    }
+
     /// Does this query match a given advisory?
     pub fn matches(&self, advisory: &Advisory) -> bool {
         if let Some(collection) = self.collection {
@@ -172,23 +336,16 @@ impl Query {
         }
 
         if let Some(package_version) = &self.package_version {
-            if !advisory.versions.is_vulnerable(package_version) {
+            if !advisory
+                .versions
+                .is_affected_with_prereleases(package_version, self.include_prereleases)
+            {
                 return false;
             }
         }
 
         if let Some(package_source) = &self.package_source {
-            let advisory_source = advisory
-                .metadata
-                .source
-                .as_ref()
-                .cloned()
-                .unwrap_or_default();
-
-            // TODO(tarcieri): better source comparison?
-            if advisory_source.kind() != package_source.kind()
-                || advisory_source.url() != package_source.url()
-            {
+            if !same_source(&advisory.source(), package_source) {
                 return false;
             }
         }
@@ -223,6 +380,50 @@ impl Query {
             }
         }
 
+        if self.after.is_some() || self.before.is_some() {
+            let date = if self.by_withdrawn_date {
+                advisory.metadata.withdrawn.as_ref()
+            } else {
+                Some(&advisory.metadata.date)
+            };
+
+            let day = match date {
+                Some(date) => calendar_day(date),
+                None => return false,
+            };
+
+            if let Some(after) = &self.after {
+                if day <= calendar_day(after) {
+                    return false;
+                }
+            }
+
+            if let Some(before) = &self.before {
+                if day >= calendar_day(before) {
+                    return false;
+                }
+            }
+        }
+
+        if let Some(cwe) = self.cwe {
+            if !advisory.metadata.cwe.contains(&cwe) {
+                return false;
+            }
+        }
+
+        if let Some(path) = &self.affected_function {
+            if let Some(affected) = &advisory.affected {
+                if !affected.functions.is_empty()
+                    && !affected
+                        .functions
+                        .keys()
+                        .any(|function| paths_overlap(function, path))
+                {
+                    return false;
+                }
+            }
+        }
+
         if let Some(withdrawn) = self.withdrawn {
             if withdrawn != advisory.metadata.withdrawn.is_some() {
                 return false;
@@ -235,12 +436,291 @@ impl Query {
             }
         }
 
+        if let Some(fixable) = self.fixable {
+            if fixable == advisory.versions.patched().is_empty() {
+                return false;
+            }
+        }
+
         true
     }
 }
 
+/// Do the given sources refer to the same place packages are published to?
+///
+/// crates.io is the same registry whether it's accessed through its git or
+/// its sparse index.
+fn same_source(a: &SourceId, b: &SourceId) -> bool {
+    (a.is_default_registry() && b.is_default_registry())
+        || (a.kind() == b.kind() && a.url() == b.url())
+}
+
+/// Calendar day of the given date, ignoring any time of day
+fn calendar_day(date: &Date) -> (u32, u32, u32) {
+    (date.year(), date.month(), date.day())
+}
+
+/// Is one of the given paths a prefix of the other, segment by segment?
+fn paths_overlap(function: &FunctionPath, path: &str) -> bool {
+    function
+        .iter()
+        .zip(path.split("::"))
+        .all(|(segment, other)| segment.as_str() == other)
+}
+
 impl Default for Query {
     fn default() -> Query {
         Query::crate_scope()
     }
 }
+
+/// Builder for [`Query`] whose setters take an `Option`, so that filters can
+/// be applied conditionally: passing `None` leaves the filter as it is, e.g.
+/// as set by [`QueryBuilder::crate_scope`]. Filters are removed with the
+/// `clear_*` methods instead.
+///
+/// ```
+/// use rustsec::{advisory::Severity, database::QueryBuilder};
+///
+/// let min_severity: Option<Severity> = None;
+/// let query = QueryBuilder::crate_scope()
+///     .severity(min_severity)
+///     .year(Some(2020))
+///     .build();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct QueryBuilder {
+    query: Query,
+}
+
+impl QueryBuilder {
+    /// Create a builder for a "wildcard" query without any constraints,
+    /// see [`Query::new`]
+    pub fn new() -> Self {
+        Query::new().into_builder()
+    }
+
+    /// Create a builder for a query which uses the default scope rules for
+    /// crates, see [`Query::crate_scope`]
+    pub fn crate_scope() -> Self {
+        Query::crate_scope().into_builder()
+    }
+
+    /// Set collection to query against, see [`Query::collection`]
+    pub fn collection(mut self, collection: Option<Collection>) -> Self {
+        if collection.is_some() {
+            self.query.collection = collection;
+        }
+        self
+    }
+
+    /// Clear the collection filter, querying advisories of all collections
+    pub fn clear_collection(mut self) -> Self {
+        self.query.collection = None;
+        self
+    }
+
+    /// Set package name to search for, see [`Query::package_name`]
+    pub fn package_name(mut self, name: Option<package::Name>) -> Self {
+        if name.is_some() {
+            self.query.package_name = name;
+        }
+        self
+    }
+
+    /// Clear the package name filter
+    pub fn clear_package_name(mut self) -> Self {
+        self.query.package_name = None;
+        self
+    }
+
+    /// Set package version to search for, see [`Query::package_version`]
+    pub fn package_version(mut self, version: Option<Version>) -> Self {
+        if version.is_some() {
+            self.query.package_version = version;
+        }
+        self
+    }
+
+    /// Clear the package version filter
+    pub fn clear_package_version(mut self) -> Self {
+        self.query.package_version = None;
+        self
+    }
+
+    /// Set package source to match advisories against, see [`Query::package_source`]
+    pub fn package_source(mut self, source: Option<SourceId>) -> Self {
+        if source.is_some() {
+            self.query.package_source = source;
+        }
+        self
+    }
+
+    /// Clear the package source filter
+    pub fn clear_package_source(mut self) -> Self {
+        self.query.package_source = None;
+        self
+    }
+
+    /// Set minimum severity threshold, see [`Query::severity`]
+    pub fn severity(mut self, severity: Option<Severity>) -> Self {
+        if severity.is_some() {
+            self.query.severity = severity;
+        }
+        self
+    }
+
+    /// Clear the minimum severity threshold
+    pub fn clear_severity(mut self) -> Self {
+        self.query.severity = None;
+        self
+    }
+
+    /// Set target architecture, see [`Query::target_arch`]
+    pub fn target_arch(mut self, arch: Option<Arch>) -> Self {
+        if arch.is_some() {
+            self.query.target_arch = arch;
+        }
+        self
+    }
+
+    /// Clear the target architecture filter
+    pub fn clear_target_arch(mut self) -> Self {
+        self.query.target_arch = None;
+        self
+    }
+
+    /// Set target operating system, see [`Query::target_os`]
+    pub fn target_os(mut self, os: Option<OS>) -> Self {
+        if os.is_some() {
+            self.query.target_os = os;
+        }
+        self
+    }
+
+    /// Clear the target operating system filter
+    pub fn clear_target_os(mut self) -> Self {
+        self.query.target_os = None;
+        self
+    }
+
+    /// Set the year of advisories, see [`Query::year`]
+    pub fn year(mut self, year: Option<u32>) -> Self {
+        if year.is_some() {
+            self.query.year = year;
+        }
+        self
+    }
+
+    /// Clear the year filter
+    pub fn clear_year(mut self) -> Self {
+        self.query.year = None;
+        self
+    }
+
+    /// Set the day advisories must be dated after, see [`Query::after`]
+    pub fn after(mut self, date: Option<Date>) -> Self {
+        if date.is_some() {
+            self.query.after = date;
+        }
+        self
+    }
+
+    /// Clear the day advisories must be dated after
+    pub fn clear_after(mut self) -> Self {
+        self.query.after = None;
+        self
+    }
+
+    /// Set the day advisories must be dated before, see [`Query::before`]
+    pub fn before(mut self, date: Option<Date>) -> Self {
+        if date.is_some() {
+            self.query.before = date;
+        }
+        self
+    }
+
+    /// Clear the day advisories must be dated before
+    pub fn clear_before(mut self) -> Self {
+        self.query.before = None;
+        self
+    }
+
+    /// Set the weakness advisories must be classified as, see [`Query::cwe`]
+    pub fn cwe(mut self, cwe: Option<Cwe>) -> Self {
+        if cwe.is_some() {
+            self.query.cwe = cwe;
+        }
+        self
+    }
+
+    /// Clear the weakness filter
+    pub fn clear_cwe(mut self) -> Self {
+        self.query.cwe = None;
+        self
+    }
+
+    /// Set the path of a function which must be affected, see
+    /// [`Query::affected_function`]
+    pub fn affected_function(mut self, path: Option<&str>) -> Self {
+        if let Some(path) = path {
+            self.query.affected_function = Some(path.to_owned());
+        }
+        self
+    }
+
+    /// Clear the affected function filter
+    pub fn clear_affected_function(mut self) -> Self {
+        self.query.affected_function = None;
+        self
+    }
+
+    /// Set whether to query for withdrawn advisories, see [`Query::withdrawn`].
+    pub fn withdrawn(mut self, setting: Option<bool>) -> Self {
+        if setting.is_some() {
+            self.query.withdrawn = setting;
+        }
+        self
+    }
+
+    /// Include withdrawn advisories alongside active ones
+    pub fn clear_withdrawn(mut self) -> Self {
+        self.query.withdrawn = None;
+        self
+    }
+
+    /// Set whether to query for informational advisories, see
+    /// [`Query::informational`].
+    pub fn informational(mut self, setting: Option<bool>) -> Self {
+        if setting.is_some() {
+            self.query.informational = setting;
+        }
+        self
+    }
+
+    /// Include informational advisories alongside vulnerabilities
+    pub fn clear_informational(mut self) -> Self {
+        self.query.informational = None;
+        self
+    }
+
+    /// Set whether to query for advisories which have been fixed, see
+    /// [`Query::fixable`].
+    pub fn fixable(mut self, setting: Option<bool>) -> Self {
+        if setting.is_some() {
+            self.query.fixable = setting;
+        }
+        self
+    }
+
+    /// Include advisories regardless of whether they've been fixed
+    pub fn clear_fixable(mut self) -> Self {
+        self.query.fixable = None;
+        self
+    }
+
+    /// Build the query
+    pub fn build(self) -> Query {
+        self.query
+    }
+}
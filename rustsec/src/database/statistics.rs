@@ -0,0 +1,65 @@
+//! Aggregate statistics about the advisories in a database
+
+use crate::{
+    advisory::{Advisory, Informational},
+    Map,
+};
+
+/// Advisory counts of a [`Database`](crate::Database) by category.
+///
+/// All advisories are counted, including withdrawn ones.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DatabaseStats {
+    /// Total number of advisories
+    pub total: usize,
+
+    /// Number of informational advisories of each kind
+    pub informational: Map<Informational, usize>,
+
+    /// Number of advisories by the year of their ID
+    pub by_year: Map<u32, usize>,
+
+    /// Number of advisories with a CVSS vector
+    pub with_cvss: usize,
+
+    /// Number of advisories without a CVSS vector
+    pub without_cvss: usize,
+
+    /// Number of withdrawn advisories
+    pub withdrawn: usize,
+}
+
+impl DatabaseStats {
+    /// Compute statistics over the given advisories
+    pub(super) fn new<'a>(advisories: impl Iterator<Item = &'a Advisory>) -> Self {
+        let mut stats = Self::default();
+
+        for advisory in advisories {
+            let metadata = &advisory.metadata;
+            stats.total += 1;
+
+            if let Some(informational) = &metadata.informational {
+                *stats
+                    .informational
+                    .entry(informational.clone())
+                    .or_default() += 1;
+            }
+
+            if let Some(year) = metadata.id.year() {
+                *stats.by_year.entry(year).or_default() += 1;
+            }
+
+            if metadata.cvss.is_some() {
+                stats.with_cvss += 1;
+            } else {
+                stats.without_cvss += 1;
+            }
+
+            if metadata.withdrawn.is_some() {
+                stats.withdrawn += 1;
+            }
+        }
+
+        stats
+    }
+}
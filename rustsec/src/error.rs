@@ -4,10 +4,11 @@ use std::{
     fmt::{self, Display},
     io,
     str::Utf8Error,
+    sync::Arc,
 };
 use thiserror::Error;
 
-#[cfg(feature = "git")]
+#[cfg(feature = "git-index")]
 use tame_index::external::gix;
 
 /// Create a new error (of a given enum variant) with a formatted message
@@ -37,13 +38,16 @@ macro_rules! fail {
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Error type
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Error {
     /// Kind of error
     kind: ErrorKind,
 
     /// Message providing additional information
     msg: String,
+
+    /// Underlying error which caused this one (if any)
+    source: Option<Arc<dyn std::error::Error + Send + Sync + 'static>>,
 }
 
 impl Error {
@@ -52,6 +56,23 @@ impl Error {
         Self {
             kind,
             msg: description.to_string(),
+            source: None,
+        }
+    }
+
+    /// Create a new error with the given description, caused by `source`.
+    ///
+    /// The source is only exposed through [`std::error::Error::source`]
+    /// so that the types of dependencies don't leak into the public API.
+    pub(crate) fn with_source<S, E>(kind: ErrorKind, description: &S, source: E) -> Self
+    where
+        S: ToString,
+        E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    {
+        Self {
+            kind,
+            msg: description.to_string(),
+            source: Some(Arc::from(source.into())),
         }
     }
 
@@ -67,7 +88,13 @@ impl Display for Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
 
 /// Custom error type for this library
 #[derive(Copy, Clone, Debug, Error, Eq, PartialEq)]
@@ -91,10 +118,18 @@ pub enum ErrorKind {
     #[error("not found")]
     NotFound,
 
+    /// Not available in a local cache, and fetching it requires network access
+    #[error("not cached")]
+    NotCached,
+
     /// Unable to acquire filesystem lock
     #[error("unable to acquire filesystem lock")]
     LockTimeout,
 
+    /// A request to a remote registry didn't complete within its timeout
+    #[error("request timed out")]
+    Timeout,
+
     /// Couldn't parse response data
     #[error("parse error")]
     Parse,
@@ -103,10 +138,21 @@ pub enum ErrorKind {
     #[error("registry")]
     Registry,
 
+    /// The registry index as a whole could not be accessed, as opposed to
+    /// a lookup of an individual package failing (see [`ErrorKind::Registry`]).
+    ///
+    /// Results derived from the index may be missing or stale.
+    #[error("registry index unavailable")]
+    IndexUnavailable,
+
     /// Git operation failed
     #[error("git operation failed")]
     Repo,
 
+    /// Commit signature is missing or wasn't made by a trusted key
+    #[error("signature verification failed")]
+    Signature,
+
     /// Errors related to versions
     #[error("bad version")]
     Version,
@@ -140,7 +186,7 @@ impl From<fmt::Error> for Error {
 
 impl From<io::Error> for Error {
     fn from(other: io::Error) -> Self {
-        format_err!(ErrorKind::Io, &other)
+        Self::with_source(ErrorKind::Io, &other.to_string(), other)
     }
 }
 
@@ -152,15 +198,25 @@ impl Error {
     /// and we need to keep it private because `tame_index` semver
     /// will be bumped frequently and we don't want to bump `rustsec` semver
     /// every time it changes.
-    #[cfg(feature = "git")]
+    #[cfg(feature = "sparse-index")]
     pub(crate) fn from_tame(err: tame_index::Error) -> Self {
-        // Separate lock timeouts into their own LockTimeout variant.
+        // Separate lock timeouts into their own LockTimeout variant, including
+        // those wrapped in other errors (e.g. of a fetch), so that callers can
+        // reliably wait and retry on lock contention. Request timeouts get their
+        // own Timeout variant for the same reason.
         match err {
-            tame_index::Error::Git(git_err) => match git_err {
-                tame_index::error::GitError::Lock(lock_err) => Self::from_gix_lock(lock_err),
-                other => format_err!(ErrorKind::Registry, "{}", other),
-            },
-            other => format_err!(ErrorKind::Registry, "{}", other),
+            #[cfg(feature = "git-index")]
+            tame_index::Error::Git(tame_index::error::GitError::Lock(lock_err)) => {
+                Self::from_gix_lock(lock_err)
+            }
+            #[cfg(feature = "git-index")]
+            other if is_lock_timeout(&other) => {
+                Self::with_source(ErrorKind::LockTimeout, &other.to_string(), other)
+            }
+            other if is_request_timeout(&other) => {
+                Self::with_source(ErrorKind::Timeout, &other.to_string(), other)
+            }
+            other => Self::with_source(ErrorKind::Registry, &other.to_string(), other),
         }
     }
 
@@ -171,7 +227,7 @@ impl Error {
     /// and we need to keep it private because `gix` semver
     /// will be bumped frequently and we don't want to bump `rustsec` semver
     /// every time it changes.
-    #[cfg(feature = "git")]
+    #[cfg(feature = "git-index")]
     pub(crate) fn from_gix_lock(other: gix::lock::acquire::Error) -> Self {
         match other {
             gix::lock::acquire::Error::Io(e) => {
@@ -195,6 +251,51 @@ impl Error {
     /// This is used so rarely that there is no need to `impl From`,
     /// and this way we can avoid leaking it into the public API.
     pub(crate) fn from_toml(other: toml::de::Error) -> Self {
-        format_err!(crate::ErrorKind::Parse, &other)
+        Self::with_source(ErrorKind::Parse, &other.to_string(), other)
+    }
+}
+
+/// Was the given error, or any of its causes, caused by timing out while
+/// acquiring a filesystem lock?
+#[cfg(feature = "git-index")]
+pub(crate) fn is_lock_timeout(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut cause = Some(err);
+
+    while let Some(err) = cause {
+        if let Some(gix::lock::acquire::Error::PermanentlyLocked { .. }) = err.downcast_ref() {
+            return true;
+        }
+        cause = err.source();
     }
+
+    false
+}
+
+/// Was the given error, or any of its causes, caused by a request to a
+/// registry not completing within its timeout?
+#[cfg(feature = "sparse-index")]
+pub(crate) fn is_request_timeout(err: &(dyn std::error::Error + 'static)) -> bool {
+    use tame_index::{error::HttpError, external::reqwest};
+
+    let mut cause = Some(err);
+
+    while let Some(err) = cause {
+        // Errors of `tame_index` are transparent, i.e. their `source` is that of
+        // the error they wrap rather than that error itself, so unwrap it here
+        let http_err = match err.downcast_ref() {
+            Some(tame_index::Error::Http(http_err)) => Some(http_err),
+            _ => err.downcast_ref::<HttpError>(),
+        };
+        let reqwest_err = match http_err {
+            Some(HttpError::Timeout) => return true,
+            Some(HttpError::Reqwest(reqwest_err)) => Some(reqwest_err),
+            _ => err.downcast_ref::<reqwest::Error>(),
+        };
+        if reqwest_err.map_or(false, reqwest::Error::is_timeout) {
+            return true;
+        }
+        cause = err.source();
+    }
+
+    false
 }
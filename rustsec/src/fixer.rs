@@ -1,44 +1,395 @@
 //! Automatically attempt to fix vulnerable dependencies
 
 use crate::{
+    advisory::versions::is_compatible,
     error::{Error, ErrorKind},
+    package,
     vulnerability::Vulnerability,
+    Map,
 };
-use semver::VersionReq;
-use std::path::Path;
+use semver::{Comparator, Op, Version, VersionReq};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "sparse-index")]
+use crate::registry::CachedIndex;
+
+mod workspace;
+
+use self::workspace::{Declaration, Workspace};
 
 /// Auto-fixer for vulnerable dependencies
 #[cfg_attr(docsrs, doc(cfg(feature = "fix")))]
 pub struct Fixer {
-    manifest: cargo_edit::LocalManifest,
+    workspace: Workspace,
 }
 
 impl Fixer {
-    /// Create a new [`Fixer`] for the given `Cargo.toml` file
+    /// Create a new [`Fixer`] for the given `Cargo.toml` file.
+    ///
+    /// If it belongs to a workspace, dependencies are fixed in whichever
+    /// manifest of the workspace declares them.
     pub fn new(cargo_toml: impl AsRef<Path>) -> Result<Self, Error> {
-        let manifest =
-            cargo_edit::LocalManifest::try_new(cargo_toml.as_ref().canonicalize()?.as_ref())?;
-        Ok(Self { manifest })
+        let workspace = Workspace::find(cargo_toml.as_ref())?;
+        Ok(Self { workspace })
+    }
+
+    /// Plan how to fix the given vulnerabilities without modifying `Cargo.toml`.
+    ///
+    /// Contains one [`Remediation`] per vulnerable package, upgrading it to the
+    /// lowest version which is patched against all of its vulnerabilities
+    /// (see [`Remediation::for_vulnerabilities`]). Vulnerabilities without
+    /// any patched versions are ignored, and packages which only have such
+    /// vulnerabilities are omitted.
+    ///
+    /// Packages without any version patched against all of their
+    /// vulnerabilities are listed in [`Plan::unfixable`] instead, with an
+    /// error of kind [`ErrorKind::Fix`], so that the other packages can
+    /// still be fixed.
+    pub fn plan(&self, vulnerabilities: &[Vulnerability]) -> Plan {
+        let mut plan = Plan::default();
+
+        for vulnerabilities in group_fixable(vulnerabilities).values() {
+            match Remediation::for_vulnerabilities(vulnerabilities) {
+                Ok(remediation) => plan.remediations.extend(remediation),
+                Err(err) => plan.add_unfixable(vulnerabilities, err),
+            }
+        }
+
+        plan
+    }
+
+    /// Plan how to fix the given vulnerabilities like [`Fixer::plan`], but only
+    /// upgrade to versions which are published in the given index and aren't
+    /// yanked.
+    ///
+    /// Yanked patched versions which were skipped over are listed in
+    /// [`Remediation::yanked`]. Packages whose patched versions are all yanked
+    /// are omitted, as are packages missing from the index. Packages which
+    /// couldn't be looked up in the index are listed in [`Plan::unfixable`].
+    #[cfg(feature = "sparse-index")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sparse-index")))]
+    pub fn plan_with_index(&self, vulnerabilities: &[Vulnerability], index: &CachedIndex) -> Plan {
+        let mut plan = Plan::default();
+
+        for vulnerabilities in group_fixable(vulnerabilities).values() {
+            match Remediation::for_vulnerabilities(vulnerabilities) {
+                Ok(Some(_)) => (),
+                Ok(None) => continue,
+                Err(err) => {
+                    plan.add_unfixable(vulnerabilities, err);
+                    continue;
+                }
+            }
+
+            match index.versions(&vulnerabilities[0].package.name) {
+                Ok(Some(versions)) => {
+                    plan.remediations
+                        .extend(Remediation::for_published_versions_of_all(
+                            vulnerabilities,
+                            &versions,
+                        ))
+                }
+                Ok(None) => (),
+                Err(err) => plan.add_unfixable(vulnerabilities, err),
+            }
+        }
+
+        plan
+    }
+
+    /// Apply the given remediation to `Cargo.toml`.
+    ///
+    /// Dependencies inherited from `[workspace.dependencies]` are upgraded in
+    /// the workspace's root manifest. Path and git dependencies can't be fixed
+    /// and are left alone; the manifests declaring them are returned, so that
+    /// they can be reported.
+    pub fn apply(
+        &mut self,
+        remediation: &Remediation,
+        dry_run: bool,
+    ) -> Result<Vec<PathBuf>, Error> {
+        let version = remediation.to.to_string();
+        let declarations = self.workspace.declarations(&remediation.package)?;
+        let mut skipped = vec![];
+
+        if declarations.is_empty() {
+            fail!(
+                ErrorKind::Fix,
+                "no dependency on {} declared in the workspace",
+                remediation.package
+            );
+        }
+
+        for declaration in declarations {
+            match declaration {
+                Declaration::Manifest(path) => {
+                    let dependency = cargo_edit::Dependency::new(remediation.package.as_str())
+                        .set_version(&version);
+
+                    cargo_edit::LocalManifest::try_new(&path)?.upgrade(
+                        &dependency,
+                        dry_run,
+                        false,
+                    )?;
+                }
+                Declaration::Workspace(root) => workspace::upgrade_workspace_dependency(
+                    &root,
+                    &remediation.package,
+                    &version,
+                    dry_run,
+                )?,
+                Declaration::NonRegistry(path) => skipped.push(path),
+            }
+        }
+
+        Ok(skipped)
     }
 
-    /// Attempt to fix the given vulnerability
+    /// Attempt to fix the given vulnerability.
+    ///
+    /// Path and git dependencies are left alone, like with [`Fixer::apply`].
     pub fn fix(
         &mut self,
         vulnerability: &Vulnerability,
         dry_run: bool,
     ) -> Result<VersionReq, Error> {
-        // TODO(tarcieri): find semver-compatible fix?
-        let version_req = match vulnerability.versions.patched().get(0) {
-            Some(req) => req,
+        let remediation = match Remediation::for_vulnerability(vulnerability) {
+            Some(remediation) => remediation,
             None => fail!(ErrorKind::Version, "no fixed version available"),
         };
 
-        let dependency = cargo_edit::Dependency::new(vulnerability.package.name.as_str())
-            .set_version(&version_req.to_string());
+        self.apply(&remediation, dry_run)?;
 
-        self.manifest.upgrade(&dependency, dry_run, false)?;
+        Ok(VersionReq {
+            comparators: vec![Comparator {
+                op: Op::Caret,
+                major: remediation.to.major,
+                minor: Some(remediation.to.minor),
+                patch: Some(remediation.to.patch),
+                pre: remediation.to.pre,
+            }],
+        })
+    }
+}
+
+/// Planned upgrades of vulnerable packages, see [`Fixer::plan`]
+#[cfg_attr(docsrs, doc(cfg(feature = "fix")))]
+#[derive(Debug, Default)]
+pub struct Plan {
+    /// Upgrades of vulnerable packages to patched versions
+    pub remediations: Vec<Remediation>,
 
-        // TODO(tarcieri): return new version rather than req?
-        Ok(version_req.clone())
+    /// Vulnerable packages which can't be upgraded, along with the reason why
+    pub unfixable: Vec<(package::Name, Error)>,
+}
+
+impl Plan {
+    /// Record that the package of the given vulnerabilities can't be upgraded
+    fn add_unfixable(&mut self, vulnerabilities: &[&Vulnerability], err: Error) {
+        self.unfixable
+            .push((vulnerabilities[0].package.name.clone(), err));
     }
 }
+
+/// Upgrade of a vulnerable package to a patched version
+#[cfg_attr(docsrs, doc(cfg(feature = "fix")))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Remediation {
+    /// Name of the vulnerable package
+    pub package: package::Name,
+
+    /// Vulnerable version which is currently used
+    pub from: Version,
+
+    /// Patched version to upgrade to
+    pub to: Version,
+
+    /// Is the upgrade semver-incompatible, e.g. crossing a major version?
+    pub breaking: bool,
+
+    /// Patched versions lower than `to` which were skipped because they've
+    /// been yanked, see [`Remediation::for_published_versions`]
+    pub yanked: Vec<Version>,
+}
+
+impl Remediation {
+    /// Find the lowest patched version the package of the given vulnerability
+    /// can be upgraded to, if there is any.
+    pub fn for_vulnerability(vulnerability: &Vulnerability) -> Option<Self> {
+        Self::for_vulnerabilities(&[vulnerability]).ok().flatten()
+    }
+
+    /// Find the lowest version the package of the given vulnerabilities can be
+    /// upgraded to which is patched against all of them, i.e. which lies in
+    /// the intersection of their patched ranges.
+    ///
+    /// The vulnerabilities must all be of the same version of a package.
+    /// Returns `None` if none of them have patched versions, and an error of
+    /// kind [`ErrorKind::Fix`] if no version is patched against all of them.
+    pub fn for_vulnerabilities(vulnerabilities: &[&Vulnerability]) -> Result<Option<Self>, Error> {
+        let package = match vulnerabilities.first() {
+            Some(vulnerability) => &vulnerability.package,
+            None => return Ok(None),
+        };
+        let from = &package.version;
+
+        // Every range's lowest version is a candidate: the lowest version in
+        // the intersection is the lowest version of one of its ranges
+        let candidates = vulnerabilities
+            .iter()
+            .flat_map(|vulnerability| vulnerability.versions.patched())
+            .flat_map(|req| &req.comparators)
+            .filter_map(lowest_matching_version)
+            .filter(|version| version > from)
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let to = match candidates
+            .into_iter()
+            .filter(|version| !is_affected_by_any(vulnerabilities, version))
+            .min()
+        {
+            Some(to) => to,
+            None => fail!(
+                ErrorKind::Fix,
+                "no version of {} is patched against all of {}",
+                package.name,
+                advisory_ids(vulnerabilities)
+            ),
+        };
+
+        Ok(Some(Self {
+            package: package.name.clone(),
+            from: from.clone(),
+            breaking: !is_compatible(from, &to),
+            to,
+            yanked: vec![],
+        }))
+    }
+
+    /// Find the lowest patched version the package of the given vulnerability
+    /// can be upgraded to among its published versions, which map the version
+    /// strings to whether they're yanked (see
+    /// [`CachedIndex::versions`](crate::registry::CachedIndex::versions)).
+    ///
+    /// Yanked versions are never upgraded to, instead they're recorded in
+    /// [`Remediation::yanked`] when a higher patched version is picked. If all
+    /// patched versions are yanked, there is no remediation. Pre-releases are
+    /// only considered when upgrading from a pre-release.
+    pub fn for_published_versions(
+        vulnerability: &Vulnerability,
+        versions: &HashMap<String, bool>,
+    ) -> Option<Self> {
+        Self::for_published_versions_of_all(&[vulnerability], versions)
+    }
+
+    /// Like [`Remediation::for_published_versions`], but picking a version
+    /// which is patched against all of the given vulnerabilities of the same
+    /// version of a package
+    fn for_published_versions_of_all(
+        vulnerabilities: &[&Vulnerability],
+        versions: &HashMap<String, bool>,
+    ) -> Option<Self> {
+        let package = &vulnerabilities.first()?.package;
+        let from = &package.version;
+
+        let mut candidates: Vec<(Version, bool)> = versions
+            .iter()
+            .filter_map(|(version, yanked)| Some((version.parse::<Version>().ok()?, *yanked)))
+            .filter(|(version, _)| {
+                version > from
+                    && (version.pre.is_empty() || !from.pre.is_empty())
+                    && !is_affected_by_any(vulnerabilities, version)
+            })
+            .collect();
+        candidates.sort();
+
+        let position = candidates.iter().position(|(_, yanked)| !yanked)?;
+        let to = candidates[position].0.clone();
+        let yanked = candidates
+            .drain(..position)
+            .map(|(version, _)| version)
+            .collect();
+
+        Some(Self {
+            package: package.name.clone(),
+            from: from.clone(),
+            breaking: !is_compatible(from, &to),
+            to,
+            yanked,
+        })
+    }
+}
+
+/// Group the vulnerabilities which have patched versions by the version of
+/// the package they're in
+fn group_fixable(
+    vulnerabilities: &[Vulnerability],
+) -> Map<(&package::Name, &Version), Vec<&Vulnerability>> {
+    let mut groups: Map<_, Vec<_>> = Map::new();
+
+    for vulnerability in vulnerabilities {
+        if !vulnerability.versions.patched().is_empty() {
+            let key = (&vulnerability.package.name, &vulnerability.package.version);
+            groups.entry(key).or_default().push(vulnerability);
+        }
+    }
+
+    groups
+}
+
+/// Is the given version affected by any of the given vulnerabilities?
+fn is_affected_by_any(vulnerabilities: &[&Vulnerability], version: &Version) -> bool {
+    vulnerabilities
+        .iter()
+        .any(|vulnerability| vulnerability.versions.is_affected(version))
+}
+
+/// Comma-separated IDs of the advisories of the given vulnerabilities
+fn advisory_ids(vulnerabilities: &[&Vulnerability]) -> String {
+    vulnerabilities
+        .iter()
+        .map(|vulnerability| vulnerability.advisory.id.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Lowest version matched by the given comparator, if it has a lower bound
+fn lowest_matching_version(comparator: &Comparator) -> Option<Version> {
+    let mut version = Version {
+        major: comparator.major,
+        minor: comparator.minor.unwrap_or(0),
+        patch: comparator.patch.unwrap_or(0),
+        pre: comparator.pre.clone(),
+        build: Default::default(),
+    };
+
+    match comparator.op {
+        Op::Exact | Op::GreaterEq | Op::Tilde | Op::Caret | Op::Wildcard => (),
+        Op::Greater => {
+            version.pre = Default::default();
+            match (comparator.minor, comparator.patch) {
+                (Some(_), Some(_)) if !comparator.pre.is_empty() => (),
+                (Some(_), Some(_)) => version.patch += 1,
+                (Some(_), None) => {
+                    version.minor += 1;
+                    version.patch = 0;
+                }
+                _ => {
+                    version.major += 1;
+                    version.minor = 0;
+                    version.patch = 0;
+                }
+            }
+        }
+        _ => return None,
+    }
+
+    Some(version)
+}
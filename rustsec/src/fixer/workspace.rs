@@ -0,0 +1,359 @@
+//! Cargo workspaces, for locating where dependencies are declared
+
+use crate::{
+    error::{Error, ErrorKind},
+    fs, package,
+};
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, Item, TableLike, Value};
+
+/// Tables dependencies can be declared in
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Where the version requirement of a dependency is declared
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum Declaration {
+    /// In a dependency table of the given manifest
+    Manifest(PathBuf),
+
+    /// In the `[workspace.dependencies]` table of the given root manifest,
+    /// from where it's inherited by workspace members
+    Workspace(PathBuf),
+
+    /// As a path or git dependency in the given manifest, which can't be
+    /// fixed by changing its version
+    NonRegistry(PathBuf),
+}
+
+/// Manifests of a Cargo workspace
+#[derive(Debug)]
+pub(super) struct Workspace {
+    /// Root manifest of the workspace, if there is a workspace
+    root: Option<PathBuf>,
+
+    /// Manifests of all packages in the workspace
+    members: Vec<PathBuf>,
+}
+
+impl Workspace {
+    /// Find the workspace the given manifest belongs to, which is the one named
+    /// by its `package.workspace` key, or else the closest one in the parent
+    /// directories.
+    ///
+    /// A manifest which isn't a member of that workspace (e.g. because it's
+    /// excluded from it), or which has no workspace at all, is treated like a
+    /// workspace with a single member.
+    pub(super) fn find(manifest: &Path) -> Result<Self, Error> {
+        let manifest = manifest.canonicalize()?;
+
+        if let Some(root) = workspace_root(&manifest)? {
+            let document = read_manifest(&root)?;
+            let root_dir = root.parent().expect("manifests are files in a directory");
+
+            if let Some(workspace) = document.get("workspace").and_then(Item::as_table_like) {
+                let mut members = Vec::new();
+                if document.contains_key("package") {
+                    members.push(root.clone());
+                }
+                members.extend(member_manifests(root_dir, workspace)?);
+
+                if members.contains(&manifest) {
+                    return Ok(Self {
+                        root: Some(root),
+                        members,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            root: None,
+            members: vec![manifest],
+        })
+    }
+
+    /// Locate all declarations of a dependency on the given package.
+    ///
+    /// Declarations as a path or git dependency are included as
+    /// [`Declaration::NonRegistry`], since they can't be fixed by changing
+    /// their version.
+    pub(super) fn declarations(&self, name: &package::Name) -> Result<Vec<Declaration>, Error> {
+        let root = match &self.root {
+            Some(root) => Some((root, read_manifest(root)?)),
+            None => None,
+        };
+
+        let workspace_dependencies = root.as_ref().and_then(|(_, document)| {
+            document
+                .get("workspace")
+                .and_then(|workspace| workspace.get("dependencies"))
+                .and_then(Item::as_table_like)
+        });
+
+        let mut declarations = Vec::new();
+
+        for manifest in &self.members {
+            let document = read_manifest(manifest)?;
+
+            for (key, dependency) in dependencies(&document) {
+                let inherited = dependency
+                    .get("workspace")
+                    .and_then(Item::as_bool)
+                    .unwrap_or(false);
+
+                let (declaration, dependency) = match (inherited, &root, workspace_dependencies) {
+                    (true, Some((root, _)), Some(workspace_dependencies)) => {
+                        match workspace_dependencies.get(key) {
+                            Some(dependency) => {
+                                (Declaration::Workspace(root.to_path_buf()), dependency)
+                            }
+                            None => continue,
+                        }
+                    }
+                    (true, _, _) => continue,
+                    (false, _, _) => (Declaration::Manifest(manifest.clone()), dependency),
+                };
+
+                if package_name(key, dependency) != name.as_str() {
+                    continue;
+                }
+
+                let declaration =
+                    if dependency.get("path").is_some() || dependency.get("git").is_some() {
+                        match declaration {
+                            Declaration::Manifest(path) | Declaration::Workspace(path) => {
+                                Declaration::NonRegistry(path)
+                            }
+                            declaration @ Declaration::NonRegistry(_) => declaration,
+                        }
+                    } else {
+                        declaration
+                    };
+
+                if !declarations.contains(&declaration) {
+                    declarations.push(declaration);
+                }
+            }
+        }
+
+        Ok(declarations)
+    }
+}
+
+/// Set the version requirement of a dependency on the given package in the
+/// `[workspace.dependencies]` table of the given root manifest
+pub(super) fn upgrade_workspace_dependency(
+    root: &Path,
+    name: &package::Name,
+    version: &str,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let mut document = read_manifest(root)?;
+
+    let dependency = document
+        .get_mut("workspace")
+        .and_then(|workspace| workspace.get_mut("dependencies"))
+        .and_then(Item::as_table_like_mut)
+        .and_then(|dependencies| {
+            let key = dependencies
+                .iter()
+                .find(|(key, dependency)| package_name(key, dependency) == name.as_str())
+                .map(|(key, _)| key.to_owned())?;
+            dependencies.get_mut(&key)
+        })
+        .ok_or_else(|| {
+            format_err!(
+                ErrorKind::Fix,
+                "no dependency on {} in [workspace.dependencies] of {}",
+                name,
+                root.display()
+            )
+        })?;
+
+    match dependency.as_table_like_mut() {
+        Some(table) => match table.get_mut("version").and_then(Item::as_value_mut) {
+            Some(value) => set_version(value, version),
+            None => {
+                table.insert("version", toml_edit::value(version));
+            }
+        },
+        None => match dependency.as_value_mut() {
+            Some(value) => set_version(value, version),
+            None => fail!(
+                ErrorKind::Fix,
+                "invalid dependency on {} in {}",
+                name,
+                root.display()
+            ),
+        },
+    }
+
+    if !dry_run {
+        fs::write(root, document.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Parse the given `Cargo.toml` file
+fn read_manifest(path: &Path) -> Result<Document, Error> {
+    fs::read_to_string(path)?.parse().map_err(|err| {
+        format_err!(
+            ErrorKind::Parse,
+            "couldn't parse {}: {}",
+            path.display(),
+            err
+        )
+    })
+}
+
+/// Root manifest of the workspace the given manifest would belong to: the one
+/// its `package.workspace` key points to, or else the closest manifest with a
+/// `[workspace]` table, starting with the given manifest itself
+fn workspace_root(manifest: &Path) -> Result<Option<PathBuf>, Error> {
+    let dir = manifest
+        .parent()
+        .expect("manifests are files in a directory");
+
+    let document = read_manifest(manifest)?;
+    if let Some(root_dir) = document
+        .get("package")
+        .and_then(|package| package.get("workspace"))
+        .and_then(Item::as_str)
+    {
+        return Ok(Some(dir.join(root_dir).join("Cargo.toml").canonicalize()?));
+    }
+
+    for dir in dir.ancestors() {
+        let candidate = dir.join("Cargo.toml");
+        if candidate.is_file() && read_manifest(&candidate)?.contains_key("workspace") {
+            return Ok(Some(candidate));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Manifests of the members of the workspace rooted at the given directory.
+///
+/// Member paths may contain `*` and `?` wildcards, which are expanded to all
+/// matching directories containing a `Cargo.toml` file. Directories within an
+/// excluded path aren't members.
+fn member_manifests(root_dir: &Path, workspace: &dyn TableLike) -> Result<Vec<PathBuf>, Error> {
+    let paths = |key: &str| -> Vec<&str> {
+        workspace
+            .get(key)
+            .and_then(Item::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect()
+    };
+
+    let excluded: Vec<PathBuf> = paths("exclude")
+        .into_iter()
+        .map(|path| root_dir.join(path))
+        .collect();
+
+    let mut dirs = Vec::new();
+    for member in paths("members") {
+        dirs.extend(expand_wildcards(root_dir, member)?);
+    }
+
+    let mut manifests = Vec::new();
+    for dir in dirs {
+        let manifest = dir.join("Cargo.toml");
+        if manifest.is_file() && !excluded.iter().any(|excluded| dir.starts_with(excluded)) {
+            manifests.push(manifest.canonicalize()?);
+        }
+    }
+
+    manifests.sort();
+    manifests.dedup();
+    Ok(manifests)
+}
+
+/// Paths matching the given path relative to `dir`, any component of which
+/// may contain `*` and `?` wildcards
+fn expand_wildcards(dir: &Path, pattern: &str) -> Result<Vec<PathBuf>, Error> {
+    let mut paths = vec![dir.to_owned()];
+
+    for component in Path::new(pattern).iter() {
+        let component = component.to_str().expect("pattern is a str");
+
+        if !component.contains(['*', '?']) {
+            for path in &mut paths {
+                path.push(component);
+            }
+            continue;
+        }
+
+        let mut matches = Vec::new();
+        for path in paths.iter().filter(|path| path.is_dir()) {
+            for entry in fs::read_dir(path)? {
+                let entry = entry?;
+                if entry
+                    .file_name()
+                    .to_str()
+                    .map_or(false, |name| matches_wildcards(component, name))
+                {
+                    matches.push(entry.path());
+                }
+            }
+        }
+        paths = matches;
+    }
+
+    Ok(paths)
+}
+
+/// Does the given file name match a pattern with `*` and `?` wildcards?
+fn matches_wildcards(pattern: &str, name: &str) -> bool {
+    let mut chars = pattern.chars();
+
+    match chars.next() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len())
+            .filter(|&i| name.is_char_boundary(i))
+            .any(|i| matches_wildcards(chars.as_str(), &name[i..])),
+        Some('?') => {
+            let mut name = name.chars();
+            name.next().is_some() && matches_wildcards(chars.as_str(), name.as_str())
+        }
+        Some(c) => name
+            .strip_prefix(c)
+            .map_or(false, |name| matches_wildcards(chars.as_str(), name)),
+    }
+}
+
+/// All dependencies declared in the given manifest, including
+/// platform-specific ones
+fn dependencies(document: &Document) -> impl Iterator<Item = (&str, &Item)> + '_ {
+    let targets = document
+        .get("target")
+        .and_then(Item::as_table_like)
+        .into_iter()
+        .flat_map(|targets| targets.iter().map(|(_, target)| target));
+
+    std::iter::once(document.as_item())
+        .chain(targets)
+        .flat_map(|table| DEPENDENCY_TABLES.iter().filter_map(|key| table.get(key)))
+        .filter_map(Item::as_table_like)
+        .flat_map(|table| table.iter())
+}
+
+/// Name of the package a dependency refers to, which differs from its key if
+/// the dependency was renamed
+fn package_name<'a>(key: &'a str, dependency: &'a Item) -> &'a str {
+    dependency
+        .get("package")
+        .and_then(Item::as_str)
+        .unwrap_or(key)
+}
+
+/// Replace a version requirement, keeping its formatting
+fn set_version(value: &mut Value, version: &str) {
+    let decor = value.decor().clone();
+    *value = Value::from(version);
+    *value.decor_mut() = decor;
+}
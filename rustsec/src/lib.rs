@@ -19,18 +19,23 @@ mod warning;
 #[cfg(feature = "fix")]
 mod fixer;
 
-#[cfg(feature = "git")]
-#[cfg_attr(docsrs, doc(cfg(feature = "git")))]
+#[cfg(feature = "sparse-index")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sparse-index")))]
 mod cached_index;
 
-#[cfg(feature = "git")]
-#[cfg_attr(docsrs, doc(cfg(feature = "git")))]
+#[cfg(feature = "sparse-index")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sparse-index")))]
 pub mod registry {
     //! Support for interacting with the local crates.io registry index
-    pub use super::cached_index::{CachedIndex, ClientBuilder};
+    pub use super::cached_index::{
+        CachedIndex, CachedIndexBuilder, ClientBuilder, FetchHook, FetchOutcome, IndexKind,
+        IndexSnapshot, LenientMatch, ProgressCallback, ProgressEvent, RetryPolicy, YankReport,
+        YankedChecker,
+    };
 }
 
 pub use cargo_lock::{self, package, Lockfile, SourceId};
+pub use cvss;
 pub use fs_err as fs;
 pub use platforms;
 pub use semver::{self, Version, VersionReq};
@@ -46,7 +51,7 @@ pub use crate::{
 };
 
 #[cfg(feature = "fix")]
-pub use crate::fixer::Fixer;
+pub use crate::fixer::{Fixer, Plan, Remediation};
 
 #[cfg(feature = "git")]
 pub use crate::repository::git::Repository;
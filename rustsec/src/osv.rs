@@ -1,5 +1,5 @@
-//! Provides support for exporting to the interchange format defined by
-//! <https://github.com/google/osv>
+//! Provides support for exporting to and importing from the interchange
+//! format defined by <https://github.com/google/osv>
 //!
 //! We also use OSV-style ranges for version matching in RustSec crate
 //! because it allows handling pre-releases correctly,
@@ -10,9 +10,23 @@
 #[cfg(feature = "osv-export")]
 mod advisory;
 
+#[cfg(feature = "osv-export")]
+mod export;
+
 #[cfg(feature = "osv-export")]
 pub use advisory::OsvAdvisory;
 
+#[cfg(feature = "osv-export")]
+#[cfg_attr(docsrs, doc(cfg(feature = "osv-export")))]
+pub use export::{export_all, export_all_and_prune};
+
+#[cfg(feature = "osv-import")]
+mod import;
+
+#[cfg(feature = "osv-import")]
+#[cfg_attr(docsrs, doc(cfg(feature = "osv-import")))]
+pub use import::from_osv;
+
 // The rest are enabled unconditionally because the OSV range format
 // is used for determining whether a given version is affected or not
 
@@ -23,3 +37,4 @@ mod unaffected_range;
 pub use range::OsvRange;
 pub use ranges_for_advisory::ranges_for_advisory;
 pub(crate) use ranges_for_advisory::ranges_for_unvalidated_advisory;
+pub(crate) use unaffected_range::UnaffectedRange;
@@ -4,7 +4,9 @@ use tame_index::external::gix;
 
 use super::ranges_for_advisory;
 use crate::{
-    advisory::{affected::FunctionPath, Affected, Category, Id, Informational},
+    advisory::{
+        affected::FunctionPath, Affected, Category, Id, Informational, Reference, ReferenceKind,
+    },
     repository::git::{self, GitModificationTimes, GitPath},
     Advisory,
 };
@@ -26,6 +28,7 @@ pub struct OsvAdvisory {
     related: Vec<Id>,
     summary: String,
     details: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     severity: Vec<OsvSeverity>,
     affected: Vec<OsvAffected>,
     references: Vec<OsvReference>,
@@ -57,11 +60,15 @@ impl From<&cargo_lock::Name> for OsvPackage {
 #[serde(tag = "type", content = "score")]
 pub enum OsvSeverity {
     CVSS_V3(cvss::v3::Base),
+    CVSS_V4(cvss::v4::Vector),
 }
 
-impl From<cvss::v3::Base> for OsvSeverity {
-    fn from(cvss: cvss::v3::Base) -> Self {
-        OsvSeverity::CVSS_V3(cvss)
+impl From<cvss::Cvss> for OsvSeverity {
+    fn from(cvss: cvss::Cvss) -> Self {
+        match cvss {
+            cvss::Cvss::V3(base) => OsvSeverity::CVSS_V3(base),
+            cvss::Cvss::V4(vector) => OsvSeverity::CVSS_V4(vector),
+        }
     }
 }
 
@@ -85,42 +92,43 @@ pub struct OsvJsonRange {
 
 #[derive(Debug, Clone, Serialize)]
 pub enum OsvTimelineEvent {
-    #[serde(rename = "introduced")]
-    Introduced(semver::Version),
+    /// First affected version, where `None` means all versions since `0`
+    #[serde(rename = "introduced", serialize_with = "serialize_introduced")]
+    Introduced(Option<semver::Version>),
     #[serde(rename = "fixed")]
     Fixed(semver::Version),
+    #[serde(rename = "last_affected")]
+    LastAffected(semver::Version),
+}
+
+/// OSV uses `0` to denote a range which was introduced with the first version
+fn serialize_introduced<S: serde::Serializer>(
+    version: &Option<semver::Version>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match version {
+        Some(version) => version.serialize(serializer),
+        None => serializer.serialize_str("0"),
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct OsvReference {
     // 'type' is a reserved keyword in Rust
     #[serde(rename = "type")]
-    kind: OsvReferenceKind,
+    kind: ReferenceKind,
     url: Url,
 }
 
-impl From<Url> for OsvReference {
-    fn from(url: Url) -> Self {
+impl From<Reference> for OsvReference {
+    fn from(reference: Reference) -> Self {
         OsvReference {
-            kind: guess_url_kind(&url),
-            url,
+            kind: reference.kind,
+            url: reference.url,
         }
     }
 }
 
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Debug, Clone, Serialize)]
-pub enum OsvReferenceKind {
-    ADVISORY,
-    #[allow(dead_code)]
-    ARTICLE,
-    REPORT,
-    #[allow(dead_code)]
-    FIX,
-    PACKAGE,
-    WEB,
-}
-
 #[derive(Debug, Clone, Serialize)]
 pub struct OsvEcosystemSpecific {
     affects: OsvEcosystemSpecificAffected,
@@ -148,16 +156,28 @@ impl From<Affected> for OsvEcosystemSpecificAffected {
 #[derive(Debug, Clone, Serialize)]
 pub struct OsvDatabaseSpecific {
     categories: Vec<Category>,
-    cvss: Option<cvss::v3::Base>,
+    cvss: Option<cvss::Cvss>,
     informational: Option<Informational>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct MainOsvDatabaseSpecific {
     license: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    informational: Option<Informational>,
 }
 
 impl OsvAdvisory {
+    /// ID of the advisory
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// When the advisory was last modified (RFC 3339)
+    pub fn modified(&self) -> &str {
+        &self.modified
+    }
+
     /// Converts a single RustSec advisory to OSV format.
     /// `path` is the path to the advisory file. It must be relative to the git repository root.
     pub fn from_rustsec(
@@ -167,23 +187,22 @@ impl OsvAdvisory {
     ) -> Self {
         let metadata = advisory.metadata;
 
-        // Assemble the URLs to put into 'references' field
-        let mut reference_urls: Vec<Url> = Vec::new();
+        // Assemble the 'references' field
+        let mut references: Vec<OsvReference> = Vec::new();
         // link to the package on crates.io
         let package_url = "https://crates.io/crates/".to_owned() + metadata.package.as_str();
-        reference_urls.push(Url::parse(&package_url).unwrap());
+        references
+            .push(Reference::new(ReferenceKind::Package, Url::parse(&package_url).unwrap()).into());
         // link to human-readable RustSec advisory
         let advisory_url = format!(
             "https://rustsec.org/advisories/{}.html",
             metadata.id.as_str()
         );
-        reference_urls.push(Url::parse(&advisory_url).unwrap());
-        // primary URL for the issue specified in the advisory
-        if let Some(url) = metadata.url {
-            reference_urls.push(url);
-        }
-        // other references
-        reference_urls.extend(metadata.references);
+        references.push(
+            Reference::new(ReferenceKind::Advisory, Url::parse(&advisory_url).unwrap()).into(),
+        );
+        // primary URL for the issue specified in the advisory, and other references
+        references.extend(metadata.all_references().map(OsvReference::from));
 
         OsvAdvisory {
             id: metadata.id,
@@ -191,14 +210,14 @@ impl OsvAdvisory {
             published: rustsec_date_to_rfc3339(&metadata.date),
             affected: vec![OsvAffected {
                 package: (&metadata.package).into(),
-                ranges: vec![timeline_for_advisory(&advisory.versions)],
+                ranges: timeline_for_advisory(&advisory.versions),
                 ecosystem_specific: OsvEcosystemSpecific {
                     affects: advisory.affected.unwrap_or_default().into(),
                 },
                 database_specific: OsvDatabaseSpecific {
                     categories: metadata.categories,
                     cvss: metadata.cvss.clone(),
-                    informational: metadata.informational,
+                    informational: metadata.informational.clone(),
                 },
             }],
             withdrawn: metadata.withdrawn.map(|d| rustsec_date_to_rfc3339(&d)),
@@ -207,55 +226,58 @@ impl OsvAdvisory {
             summary: metadata.title,
             severity: metadata.cvss.into_iter().map(|s| s.into()).collect(),
             details: metadata.description,
-            references: osv_references(reference_urls),
+            references,
             database_specific: MainOsvDatabaseSpecific {
                 license: metadata.license.spdx().to_string(),
+                informational: metadata.informational,
             },
         }
     }
 }
 
-fn osv_references(references: Vec<Url>) -> Vec<OsvReference> {
-    references.into_iter().map(|u| u.into()).collect()
-}
-
-fn guess_url_kind(url: &Url) -> OsvReferenceKind {
-    let str = url.as_str();
-    if (str.contains("://github.com/") || str.contains("://gitlab.")) && str.contains("/issues/") {
-        OsvReferenceKind::REPORT
-    // the check for "/advisories/" matches both RustSec and GHSA URLs
-    } else if str.contains("/advisories/") || str.contains("://cve.mitre.org/") {
-        OsvReferenceKind::ADVISORY
-    } else if str.contains("://crates.io/crates/") {
-        OsvReferenceKind::PACKAGE
-    } else {
-        OsvReferenceKind::WEB
-    }
-}
-
 /// Generates the timeline of the bug being introduced and fixed for the
 /// [`affected[].ranges[].events`](https://github.com/ossf/osv-schema/blob/main/schema.md#affectedrangesevents-fields) field.
-fn timeline_for_advisory(versions: &crate::advisory::Versions) -> OsvJsonRange {
+///
+/// Every disjoint range of affected versions becomes a range entry of its
+/// own. Ranges which end before an exclusive unaffected bound such as
+/// `> 1.2.3` are closed with a `last_affected` event instead of `fixed`,
+/// since the first unaffected version isn't known. Ranges which start after
+/// `unaffected` versions are introduced at the first release they can affect
+/// (see [`first_affected_release`]).
+fn timeline_for_advisory(versions: &crate::advisory::Versions) -> Vec<OsvJsonRange> {
     let ranges = ranges_for_advisory(versions);
     assert!(!ranges.is_empty()); // zero ranges means nothing is affected, so why even have an advisory?
-    let mut timeline = Vec::new();
-    for range in ranges {
-        match range.introduced {
-            Some(ver) => timeline.push(OsvTimelineEvent::Introduced(ver)),
-            None => timeline.push(OsvTimelineEvent::Introduced(
-                semver::Version::parse("0.0.0-0").unwrap(),
-            )),
-        }
-        #[allow(clippy::single_match)]
-        match range.fixed {
-            Some(ver) => timeline.push(OsvTimelineEvent::Fixed(ver)),
-            None => (), // "everything after 'introduced' is affected" is implicit in OSV
-        }
-    }
-    OsvJsonRange {
-        kind: "SEMVER",
-        events: timeline,
+    ranges
+        .into_iter()
+        .map(|range| {
+            let introduced = range.introduced.map(first_affected_release);
+            let mut events = vec![OsvTimelineEvent::Introduced(introduced)];
+            match (range.last_affected, range.fixed) {
+                (Some(ver), _) => events.push(OsvTimelineEvent::LastAffected(ver)),
+                (None, Some(ver)) => events.push(OsvTimelineEvent::Fixed(ver)),
+                // "everything after 'introduced' is affected" is implicit in OSV
+                (None, None) => (),
+            }
+            OsvJsonRange {
+                kind: "SEMVER",
+                events,
+            }
+        })
+        .collect()
+}
+
+/// First release in a range of affected versions starting at `version`.
+///
+/// Ranges following unaffected versions start at the lowest version above
+/// them, which is the lowest pre-release `-0` of the next version, e.g.
+/// `0.2.0-0` after `^0.1` or `0.1.10-0` after `<= 0.1.9`. No such version is
+/// ever published, so the range is introduced at the release instead, lest
+/// consumers of the OSV data take earlier pre-releases as affected as well.
+fn first_affected_release(mut version: semver::Version) -> semver::Version {
+    if version.pre.as_str() == "0" {
+        version.pre = semver::Prerelease::EMPTY;
     }
+    version
 }
 
 fn git_time_to_rfc3339(time: gix::date::Time) -> String {
@@ -265,6 +287,10 @@ fn git_time_to_rfc3339(time: gix::date::Time) -> String {
         .expect("well-known format to heap never fails")
 }
 
+/// Dates without a time of day are exported as noon UTC
 fn rustsec_date_to_rfc3339(d: &crate::advisory::Date) -> String {
-    format!("{}-{:02}-{:02}T12:00:00Z", d.year(), d.month(), d.day())
+    match d.time() {
+        Some(_) => d.to_string(),
+        None => format!("{}-{:02}-{:02}T12:00:00Z", d.year(), d.month(), d.day()),
+    }
 }
@@ -0,0 +1,117 @@
+//! Export of a whole advisory database to an OSV feed.
+
+use super::OsvAdvisory;
+use crate::{
+    database::advisory_path,
+    error::{Error, ErrorKind},
+    fs,
+    repository::git::{GitModificationTimes, GitPath, Repository},
+    Database, Set,
+};
+use serde::Serialize;
+use std::path::Path;
+
+/// Name of the file listing all exported advisories
+const INDEX_FILE: &str = "index.json";
+
+/// Entry of `index.json`
+#[derive(Serialize)]
+struct IndexEntry<'a> {
+    id: &'a str,
+    modified: &'a str,
+}
+
+/// Exports every advisory in the database (including withdrawn ones) to
+/// `<id>.json` in the given directory, which is created if missing.
+///
+/// Additionally writes an `index.json` listing the ID and modification time of
+/// each advisory, which allows mirrors to sync incrementally.
+///
+/// The database must be a checkout of a git repository, since modification
+/// times are taken from its history.
+pub fn export_all(db: &Database, out_dir: &Path) -> Result<(), Error> {
+    export(db, out_dir, false)
+}
+
+/// Like [`export_all`], but also deletes the files of advisories which were
+/// exported previously and aren't part of the database anymore.
+pub fn export_all_and_prune(db: &Database, out_dir: &Path) -> Result<(), Error> {
+    export(db, out_dir, true)
+}
+
+fn export(db: &Database, out_dir: &Path, prune: bool) -> Result<(), Error> {
+    let repo = Repository::open(db.path())?;
+    let mod_times = GitModificationTimes::new(&repo)?;
+
+    fs::create_dir_all(out_dir)?;
+
+    let mut advisories = vec![];
+
+    for advisory in db.iter() {
+        let path = advisory_path(advisory).ok_or_else(|| {
+            format_err!(
+                ErrorKind::BadParam,
+                "{} was not loaded from a collection directory",
+                advisory.id()
+            )
+        })?;
+        let git_path = GitPath::new(&repo, &path)?;
+        advisories.push(OsvAdvisory::from_rustsec(
+            advisory.clone(),
+            &mod_times,
+            git_path,
+        ));
+    }
+
+    advisories.sort_by(|a, b| a.id().cmp(b.id()));
+
+    for osv in &advisories {
+        write_json(&out_dir.join(format!("{}.json", osv.id())), osv)?;
+    }
+
+    let index: Vec<_> = advisories
+        .iter()
+        .map(|osv| IndexEntry {
+            id: osv.id().as_str(),
+            modified: osv.modified(),
+        })
+        .collect();
+    write_json(&out_dir.join(INDEX_FILE), &index)?;
+
+    if prune {
+        let exported: Set<_> = advisories
+            .iter()
+            .map(|osv| format!("{}.json", osv.id()))
+            .collect();
+
+        for entry in fs::read_dir(out_dir)? {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            if file_name.ends_with(".json")
+                && file_name != INDEX_FILE
+                && !exported.contains(file_name)
+            {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| {
+        format_err!(
+            ErrorKind::Io,
+            "couldn't serialize {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+
+    fs::write(path, json)?;
+    Ok(())
+}
@@ -0,0 +1,415 @@
+//! Import of advisories from OSV JSON.
+
+use crate::{
+    advisory::{
+        affected::FunctionPath, Affected, Category, Cwe, Date, Id, Informational, License,
+        Metadata, Reference, ReferenceKind, Versions,
+    },
+    collection::Collection,
+    error::{Error, ErrorKind},
+    package, Advisory,
+};
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use url::Url;
+
+/// Ecosystem of crates published on crates.io
+const ECOSYSTEM: &str = "crates.io";
+
+/// Converts an advisory in the format defined by <https://github.com/google/osv>
+/// into a RustSec advisory.
+///
+/// The advisory must affect a single package from the crates.io ecosystem.
+/// Its affected ranges must be of the `SEMVER` or `ECOSYSTEM` type, which
+/// are equivalent for crates.io since all crate versions are semver.
+pub fn from_osv(value: &serde_json::Value) -> Result<Advisory, Error> {
+    let osv = OsvImport::deserialize(value)
+        .map_err(|e| format_err!(ErrorKind::Parse, "malformed OSV advisory: {}", e))?;
+
+    let affected = match osv.affected.as_slice() {
+        [affected] => affected,
+        [] => fail!(
+            ErrorKind::BadParam,
+            "OSV advisory {} doesn't list any affected packages",
+            osv.id
+        ),
+        _ => fail!(
+            ErrorKind::BadParam,
+            "OSV advisory {} affects multiple packages, which isn't supported",
+            osv.id
+        ),
+    };
+
+    if affected.package.ecosystem != ECOSYSTEM {
+        fail!(
+            ErrorKind::BadParam,
+            "unsupported OSV ecosystem '{}' (only '{}' is supported)",
+            affected.package.ecosystem,
+            ECOSYSTEM
+        );
+    }
+
+    let package: package::Name = affected.package.name.parse().map_err(|e| {
+        format_err!(
+            ErrorKind::Parse,
+            "invalid package name '{}': {}",
+            affected.package.name,
+            e
+        )
+    })?;
+
+    let date = match &osv.published {
+        Some(published) => rfc3339_to_date(published)?,
+        None => fail!(
+            ErrorKind::BadParam,
+            "OSV advisory {} has no publication date",
+            osv.id
+        ),
+    };
+
+    let withdrawn = osv.withdrawn.as_deref().map(rfc3339_to_date).transpose()?;
+
+    // Prefer CVSS v3 vectors, which are more widely supported by tooling
+    let cvss = ["CVSS_V3", "CVSS_V4"]
+        .iter()
+        .find_map(|kind| osv.severity.iter().find(|severity| severity.kind == *kind))
+        .map(|severity| {
+            severity.score.parse::<cvss::Cvss>().map_err(|e| {
+                format_err!(
+                    ErrorKind::Parse,
+                    "invalid CVSS vector '{}': {}",
+                    severity.score,
+                    e
+                )
+            })
+        })
+        .transpose()?;
+
+    let license = match &osv.database_specific.license {
+        Some(license) => license.parse()?,
+        None => License::default(),
+    };
+
+    let affects = &affected.ecosystem_specific.affects;
+    let affected_section =
+        if affects.arch.is_empty() && affects.os.is_empty() && affects.functions.is_empty() {
+            None
+        } else {
+            Some(Affected {
+                arch: affects.arch.clone(),
+                os: affects.os.clone(),
+                functions: affects
+                    .functions
+                    .iter()
+                    .map(|function| (function.clone(), vec![]))
+                    .collect(),
+            })
+        };
+
+    let metadata = Metadata {
+        id: osv.id,
+        package,
+        title: osv.summary,
+        description: osv.details,
+        mitigation: None,
+        date,
+        aliases: osv.aliases,
+        related: osv.related,
+        collection: Some(Collection::Crates),
+        categories: affected.database_specific.categories.clone(),
+        cwe: osv.database_specific.cwe_ids,
+        keywords: vec![],
+        cvss,
+        informational: affected.database_specific.informational.clone(),
+        references: osv
+            .references
+            .into_iter()
+            .map(|reference| {
+                // Reference types RustSec doesn't distinguish are kept as web pages
+                let kind = reference.kind.parse().unwrap_or(ReferenceKind::Web);
+                Reference::new(kind, reference.url)
+            })
+            .collect(),
+        source: None,
+        url: None,
+        withdrawn,
+        license,
+        extra: Default::default(),
+    };
+
+    Ok(Advisory {
+        metadata,
+        affected: affected_section,
+        versions: versions_for_ranges(&affected.ranges)?,
+        raw_toml: None,
+        collection_dir: None,
+    })
+}
+
+/// Converts the affected OSV ranges of a package into the patched and
+/// unaffected version requirements of a RustSec advisory.
+///
+/// This is the inverse of [`ranges_for_advisory`](super::ranges_for_advisory):
+/// everything before the first affected range is unaffected, and everything
+/// after an affected range (up to the next one) is patched. Ranges listed
+/// more than once are only taken into account once, while ranges which
+/// otherwise overlap are rejected.
+fn versions_for_ranges(ranges: &[OsvImportRange]) -> Result<Versions, Error> {
+    let mut affected: Vec<(Option<Version>, Option<Bound>)> = vec![];
+
+    for range in ranges {
+        if range.kind != "SEMVER" && range.kind != "ECOSYSTEM" {
+            fail!(
+                ErrorKind::BadParam,
+                "unsupported OSV range type '{}' (only 'SEMVER' and 'ECOSYSTEM' are supported)",
+                range.kind
+            );
+        }
+
+        for event in &range.events {
+            match event {
+                OsvImportEvent::Introduced(version) => {
+                    affected.push((parse_introduced(version)?, None));
+                }
+                OsvImportEvent::Fixed(version) | OsvImportEvent::LastAffected(version) => {
+                    let Some((_, end @ None)) = affected.last_mut() else {
+                        fail!(
+                            ErrorKind::BadParam,
+                            "OSV range ends at {} without being introduced",
+                            version
+                        );
+                    };
+
+                    let version = parse_version(version)?;
+                    *end = Some(match event {
+                        OsvImportEvent::Fixed(_) => Bound::Exclusive(version),
+                        _ => Bound::Inclusive(version),
+                    });
+                }
+            }
+        }
+    }
+
+    if affected.is_empty() {
+        fail!(ErrorKind::BadParam, "OSV advisory has no affected ranges");
+    }
+
+    // Unbounded starts sort first. The same range may be listed more than once,
+    // e.g. both as a `SEMVER` and an `ECOSYSTEM` range.
+    affected.sort_by(|(a, _), (b, _)| a.cmp(b));
+    affected.dedup();
+
+    let mut patched = vec![];
+    let mut unaffected = vec![];
+
+    if let Some(start) = &affected[0].0 {
+        unaffected.push(parse_req(&format!("< {}", start))?);
+    }
+
+    for (i, (_, end)) in affected.iter().enumerate() {
+        let Some(end) = end else {
+            if i + 1 != affected.len() {
+                fail!(
+                    ErrorKind::BadParam,
+                    "OSV range without an end overlaps other ranges"
+                );
+            }
+            continue;
+        };
+
+        let lower = match end {
+            Bound::Exclusive(version) => format!(">= {}", version),
+            Bound::Inclusive(version) => format!("> {}", version),
+        };
+
+        let req = match affected.get(i + 1) {
+            Some((Some(next_start), _)) if end.is_before(next_start) => {
+                format!("{}, < {}", lower, next_start)
+            }
+            // Ranges which are adjacent to each other leave no versions patched in between
+            Some((Some(next_start), _)) if end.is_at(next_start) => continue,
+            Some(_) => fail!(ErrorKind::BadParam, "OSV ranges overlap each other"),
+            None => lower,
+        };
+
+        patched.push(parse_req(&req)?);
+    }
+
+    Versions::new(patched, unaffected)
+}
+
+/// End of an affected range
+#[derive(PartialEq)]
+enum Bound {
+    /// The version is the first one which isn't affected (`fixed`)
+    Exclusive(Version),
+
+    /// The version is the last one which is affected (`last_affected`)
+    Inclusive(Version),
+}
+
+impl Bound {
+    /// Are there versions between this end of a range and the given start of the next one?
+    fn is_before(&self, start: &Version) -> bool {
+        match self {
+            Bound::Exclusive(version) | Bound::Inclusive(version) => version < start,
+        }
+    }
+
+    /// Does the next range start with the first version after this end of a range?
+    fn is_at(&self, start: &Version) -> bool {
+        matches!(self, Bound::Exclusive(version) if version == start)
+    }
+}
+
+/// Parse the start of an affected range, where `0` means "all versions"
+fn parse_introduced(version: &str) -> Result<Option<Version>, Error> {
+    if version == "0" {
+        return Ok(None);
+    }
+
+    let version = parse_version(version)?;
+
+    // The lowest possible version, emitted by earlier versions of our own OSV export
+    if version == Version::parse("0.0.0-0").unwrap() {
+        Ok(None)
+    } else {
+        Ok(Some(version))
+    }
+}
+
+fn parse_version(version: &str) -> Result<Version, Error> {
+    Version::parse(version)
+        .map_err(|e| format_err!(ErrorKind::Version, "invalid version '{}': {}", version, e))
+}
+
+fn parse_req(req: &str) -> Result<VersionReq, Error> {
+    VersionReq::parse(req).map_err(|e| {
+        format_err!(
+            ErrorKind::Version,
+            "invalid version requirement '{}': {}",
+            req,
+            e
+        )
+    })
+}
+
+/// Convert an RFC 3339 timestamp into a [`Date`].
+///
+/// Noon UTC is how dates without a time of day are exported, so it's
+/// converted back into just the date. This loses the time of advisories
+/// which were actually published at exactly noon UTC, as the two can't be
+/// told apart; they end up with just the date they were published on.
+fn rfc3339_to_date(timestamp: &str) -> Result<Date, Error> {
+    let date: Date = timestamp.parse()?;
+
+    match date.time() {
+        Some("12:00:00Z") => Ok(date.without_time()),
+        _ => Ok(date),
+    }
+}
+
+/// Subset of the OSV schema which maps to RustSec advisories
+#[derive(Deserialize)]
+struct OsvImport {
+    id: Id,
+    published: Option<String>,
+    withdrawn: Option<String>,
+    #[serde(default)]
+    aliases: Vec<Id>,
+    #[serde(default)]
+    related: Vec<Id>,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    details: String,
+    #[serde(default)]
+    severity: Vec<OsvImportSeverity>,
+    #[serde(default)]
+    affected: Vec<OsvImportAffected>,
+    #[serde(default)]
+    references: Vec<OsvImportReference>,
+    #[serde(default)]
+    database_specific: OsvImportDatabaseSpecific,
+}
+
+#[derive(Deserialize)]
+struct OsvImportSeverity {
+    #[serde(rename = "type")]
+    kind: String,
+    score: String,
+}
+
+#[derive(Deserialize)]
+struct OsvImportAffected {
+    package: OsvImportPackage,
+    #[serde(default)]
+    ranges: Vec<OsvImportRange>,
+    #[serde(default)]
+    ecosystem_specific: OsvImportEcosystemSpecific,
+    #[serde(default)]
+    database_specific: OsvImportAffectedDatabaseSpecific,
+}
+
+#[derive(Deserialize)]
+struct OsvImportPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OsvImportRange {
+    #[serde(rename = "type")]
+    kind: String,
+    events: Vec<OsvImportEvent>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum OsvImportEvent {
+    Introduced(String),
+    Fixed(String),
+    LastAffected(String),
+}
+
+#[derive(Deserialize)]
+struct OsvImportReference {
+    #[serde(rename = "type", default)]
+    kind: String,
+    url: Url,
+}
+
+/// `ecosystem_specific` field of affected packages, as exported by RustSec
+#[derive(Default, Deserialize)]
+struct OsvImportEcosystemSpecific {
+    #[serde(default)]
+    affects: OsvImportAffects,
+}
+
+#[derive(Default, Deserialize)]
+struct OsvImportAffects {
+    #[serde(default)]
+    arch: Vec<platforms::target::Arch>,
+    #[serde(default)]
+    os: Vec<platforms::target::OS>,
+    #[serde(default)]
+    functions: Vec<FunctionPath>,
+}
+
+/// `database_specific` field of affected packages, as exported by RustSec
+#[derive(Default, Deserialize)]
+struct OsvImportAffectedDatabaseSpecific {
+    #[serde(default)]
+    categories: Vec<Category>,
+    #[serde(default)]
+    informational: Option<Informational>,
+}
+
+/// Top-level `database_specific` field, as exported by RustSec and GitHub
+#[derive(Default, Deserialize)]
+struct OsvImportDatabaseSpecific {
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    cwe_ids: Vec<Cwe>,
+}
@@ -12,6 +12,12 @@ pub struct OsvRange {
     pub introduced: Option<Version>,
     /// Exclusive
     pub fixed: Option<Version>,
+    /// Inclusive, set when the range is known to end at a particular
+    /// version rather than at the first version which isn't affected
+    /// (e.g. for `> 1.2.3` unaffected ranges).
+    ///
+    /// `fixed` is still set to the lowest version above it.
+    pub last_affected: Option<Version>,
 }
 
 impl OsvRange {
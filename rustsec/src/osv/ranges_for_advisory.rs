@@ -51,6 +51,7 @@ fn unaffected_to_osv_ranges(
         return Ok(vec![OsvRange {
             introduced: None,
             fixed: None,
+            last_affected: None,
         }]);
     }
 
@@ -91,10 +92,12 @@ fn unaffected_to_osv_ranges(
         Bound::Exclusive(v) => result.push(OsvRange {
             introduced: None,
             fixed: Some(increment(v)),
+            last_affected: Some(v.clone()),
         }),
         Bound::Inclusive(v) => result.push(OsvRange {
             introduced: None,
             fixed: Some(v.clone()),
+            last_affected: None,
         }),
     }
 
@@ -106,14 +109,15 @@ fn unaffected_to_osv_ranges(
             Bound::Exclusive(v) => v.clone(),
             Bound::Inclusive(v) => increment(v),
         };
-        let end = match &r[1].start() {
+        let (end, last_affected) = match &r[1].start() {
             Bound::Unbounded => unreachable!(),
-            Bound::Exclusive(v) => increment(v),
-            Bound::Inclusive(v) => v.clone(),
+            Bound::Exclusive(v) => (increment(v), Some(v.clone())),
+            Bound::Inclusive(v) => (v.clone(), None),
         };
         result.push(OsvRange {
             introduced: Some(start),
             fixed: Some(end),
+            last_affected,
         });
     }
 
@@ -123,10 +127,12 @@ fn unaffected_to_osv_ranges(
         Bound::Exclusive(v) => result.push(OsvRange {
             introduced: Some(v.clone()),
             fixed: None,
+            last_affected: None,
         }),
         Bound::Inclusive(v) => result.push(OsvRange {
             introduced: Some(increment(v)),
             fixed: None,
+            last_affected: None,
         }),
     }
 
@@ -79,6 +79,21 @@ impl UnaffectedRange {
         &self.end
     }
 
+    /// Does this range include the given version?
+    pub fn contains(&self, version: &Version) -> bool {
+        let above_start = match &self.start {
+            Bound::Unbounded => true,
+            Bound::Exclusive(v) => version > v,
+            Bound::Inclusive(v) => version >= v,
+        };
+        let below_end = match &self.end {
+            Bound::Unbounded => true,
+            Bound::Exclusive(v) => version < v,
+            Bound::Inclusive(v) => version <= v,
+        };
+        above_start && below_end
+    }
+
     pub fn overlaps(&self, other: &UnaffectedRange) -> bool {
         // range check for well-formed ranges is `(Start1 <= End2) && (Start2 <= End1)`
         self.start.less_or_equal(&other.end) && other.start.less_or_equal(&self.end)
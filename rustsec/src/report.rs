@@ -3,16 +3,27 @@
 //! These types map directly to the JSON report generated by `cargo-audit`,
 //! but also provide the core reporting functionality used in general.
 
+#[cfg(feature = "dependency-tree")]
+mod dependency_paths;
+pub mod junit;
+pub mod osv_scan;
+pub mod sarif;
+
+pub use self::osv_scan::to_osv_scan;
+
 use crate::{
     advisory,
     database::{Database, Query},
-    map,
+    error::{Error, ErrorKind},
+    fs, map,
+    package::{self, Package},
     platforms::target::{Arch, OS},
     vulnerability::Vulnerability,
     warning::{self, Warning},
-    Lockfile, Map,
+    Lockfile, Map, Version, VersionReq,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de, ser, Deserialize, Serialize};
+use std::{fmt, path::Path, str::FromStr};
 
 /// Vulnerability report for a given lockfile
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -37,26 +48,128 @@ pub struct Report {
 
 impl Report {
     /// Generate a report for the given advisory database and lockfile
+    ///
+    /// With the `dependency-tree` feature enabled, the dependency graph of the
+    /// lockfile is used to record how each vulnerable package is pulled in.
     pub fn generate(db: &Database, lockfile: &Lockfile, settings: &Settings) -> Self {
-        let vulnerabilities = db
-            .query_vulnerabilities(lockfile, &settings.query())
-            .into_iter()
-            .filter(|vuln| !settings.ignore.contains(&vuln.advisory.id))
-            .collect();
+        let findings = Findings::new(db, &lockfile.packages, settings);
 
-        let warnings = find_warnings(db, lockfile, settings);
+        #[cfg(feature = "dependency-tree")]
+        let findings = findings.with_paths(lockfile.dependency_tree().ok().as_ref());
 
         Self {
             #[cfg(feature = "git")]
             database: DatabaseInfo::new(db),
             lockfile: LockfileInfo::new(lockfile),
             settings: settings.clone(),
-            vulnerabilities: VulnerabilityInfo::new(vulnerabilities),
-            warnings,
+            vulnerabilities: VulnerabilityInfo {
+                below_threshold: findings.below_threshold,
+                ignored_by_policy: findings.ignored_by_policy,
+                ..VulnerabilityInfo::new(findings.vulnerabilities)
+            },
+            warnings: findings.warnings,
+        }
+    }
+
+    /// Generate the findings of a report one package of the lockfile at a
+    /// time, passing each of them to `on_finding` as soon as the package has
+    /// been checked, rather than buffering the findings for the whole lockfile.
+    ///
+    /// The vulnerabilities and warnings are the same as those listed by
+    /// [`Report::generate`], but they are ordered by package, in the order of
+    /// the lockfile, and only sorted like in a report within each package.
+    /// Vulnerabilities below the [`Settings::severity_threshold`] or ignored by
+    /// [`Settings::severity_overrides`] aren't passed on.
+    pub fn generate_streaming(
+        db: &Database,
+        lockfile: &Lockfile,
+        settings: &Settings,
+        mut on_finding: impl FnMut(Finding),
+    ) {
+        #[cfg(feature = "dependency-tree")]
+        let tree = lockfile.dependency_tree().ok();
+
+        for package in &lockfile.packages {
+            let findings = Findings::new(db, std::slice::from_ref(package), settings);
+
+            #[cfg(feature = "dependency-tree")]
+            let findings = findings.with_paths(tree.as_ref());
+
+            for vuln in findings.vulnerabilities {
+                on_finding(Finding::Vulnerability(vuln));
+            }
+
+            for warning in findings.warnings.into_values().flatten() {
+                on_finding(Finding::Warning(warning));
+            }
         }
     }
 }
 
+impl Report {
+    /// Record the given packages as yanked from their registry, e.g. as
+    /// found by `CachedIndex::find_yanked`.
+    ///
+    /// Yanked packages are reported as [`WarningKind::Yanked`](warning::WarningKind::Yanked)
+    /// warnings, separately from vulnerabilities: a release can be yanked
+    /// without a security advisory, and packages which are both yanked and
+    /// vulnerable are listed in both. Packages ignored in the report's
+    /// [`Settings`] are skipped.
+    pub fn add_yanked<'a>(&mut self, packages: impl IntoIterator<Item = &'a Package>) {
+        for package in packages {
+            if self.settings.is_package_ignored(package) {
+                continue;
+            }
+
+            push_warning(
+                &mut self.warnings,
+                Warning::new(warning::WarningKind::Yanked, package, None, None, None),
+            );
+        }
+
+        sort_warnings(&mut self.warnings);
+    }
+
+    /// Warnings about yanked packages
+    pub fn yanked(&self) -> &[Warning] {
+        self.warnings
+            .get(&warning::WarningKind::Yanked)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Iterate over the warnings of the kinds listed in
+    /// [`Settings::deny_warnings`]
+    pub fn denied_warnings(&self) -> impl Iterator<Item = &Warning> {
+        self.warnings
+            .iter()
+            .filter(|(kind, _)| self.settings.deny_warnings.contains(kind))
+            .flat_map(|(_, warnings)| warnings)
+    }
+
+    /// Did the audit fail, i.e. were any vulnerabilities found, or any
+    /// warnings of the kinds listed in [`Settings::deny_warnings`]?
+    ///
+    /// Vulnerabilities below the [`Settings::severity_threshold`] don't
+    /// count, and neither do warnings of kinds which aren't denied.
+    pub fn is_failure(&self) -> bool {
+        self.vulnerabilities.found || self.denied_warnings().next().is_some()
+    }
+
+    /// Iterate over the vulnerabilities whose severity was overridden or
+    /// which were ignored according to [`Settings::severity_overrides`],
+    /// e.g. to audit how organizational policy affected the report
+    pub fn policy_adjusted(&self) -> impl Iterator<Item = &Vulnerability> {
+        let vulnerabilities = &self.vulnerabilities;
+        vulnerabilities
+            .list
+            .iter()
+            .chain(&vulnerabilities.below_threshold)
+            .chain(&vulnerabilities.ignored_by_policy)
+            .filter(|vuln| vuln.severity_override.is_some())
+    }
+}
+
 /// Options to use when generating the report
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Settings {
@@ -69,17 +182,85 @@ pub struct Settings {
     /// Severity threshold to alert at
     pub severity: Option<advisory::Severity>,
 
-    /// List of advisory IDs to ignore
-    pub ignore: Vec<advisory::Id>,
+    /// Minimum severity of vulnerabilities which fail the audit.
+    ///
+    /// Unlike `severity`, vulnerabilities below this threshold are still
+    /// reported, in [`VulnerabilityInfo::below_threshold`].
+    #[serde(default)]
+    pub severity_threshold: Option<advisory::Severity>,
+
+    /// Consider vulnerabilities without a CVSS score to be below the
+    /// `severity_threshold` (by default they always fail the audit)
+    #[serde(default)]
+    pub exclude_unscored: bool,
+
+    /// Advisories to ignore, optionally until a given date
+    pub ignore: Vec<IgnoredAdvisory>,
+
+    /// Packages to ignore, optionally only for versions matching the given
+    /// requirement.
+    ///
+    /// Ignored packages are left out of vulnerabilities and warnings
+    /// regardless of the advisory, complementing the advisory IDs in
+    /// `ignore`. As they're never reported, expired ignores of advisories
+    /// don't result in warnings for them either.
+    #[serde(default)]
+    pub ignore_package: Vec<IgnoredPackage>,
 
     /// Types of informational advisories to generate warnings for
     pub informational_warnings: Vec<advisory::Informational>,
+
+    /// Kinds of warnings which fail the audit like vulnerabilities do (see
+    /// [`Report::is_failure`]), e.g. to fail on unsound crates while only
+    /// warning about unmaintained ones. Output formats which distinguish
+    /// failures from skipped checks (e.g. JUnit XML) report them as failures.
+    #[serde(default)]
+    pub deny_warnings: Vec<warning::WarningKind>,
+
+    /// Generate [`WarningKind::SourceNotRegistry`](warning::WarningKind::SourceNotRegistry)
+    /// warnings for packages which aren't from a registry, e.g. git dependencies
+    #[serde(default)]
+    pub warn_source_not_registry: bool,
+
+    /// CVSS v3 Temporal and Environmental metrics to score all advisories
+    /// with, e.g. `MAV:L` for a tool which isn't reachable over the network.
+    ///
+    /// The CVSS vectors of advisories are left as they are: the metrics only
+    /// change their [effective severity](Settings::effective_severity), which
+    /// `severity` and `severity_threshold` are compared against. CVSS v4
+    /// vectors carry their own Environmental metrics and aren't affected.
+    #[serde(default)]
+    pub cvss_environment: Option<cvss::v3::Environmental>,
+
+    /// CVSS v3 Temporal and Environmental metrics to score particular
+    /// advisories with, taking precedence over `cvss_environment`
+    #[serde(default)]
+    pub cvss_overrides: Map<advisory::Id, cvss::v3::Environmental>,
+
+    /// Collapse vulnerabilities of the same package whose advisories share a
+    /// CVE alias into a single one, listing the other advisories in
+    /// [`Vulnerability::duplicates`]
+    #[serde(default)]
+    pub collapse_cve_aliases: bool,
+
+    /// Overrides of the severity of particular advisories by organizational
+    /// policy, e.g. to downgrade advisories about crates which were patched
+    /// out of band, which take precedence over their CVSS vectors and the
+    /// configured CVSS metrics.
+    ///
+    /// Affected vulnerabilities record the override they were adjusted by in
+    /// [`Vulnerability::severity_override`], see [`Report::policy_adjusted`].
+    /// Overrides can be loaded from a file with
+    /// [`Settings::load_severity_overrides`].
+    #[serde(default)]
+    pub severity_overrides: Map<advisory::Id, SeverityOverride>,
 }
 
 impl Settings {
     /// Get a query which corresponds to the configured report settings.
     /// Note that queries can't filter ignored advisories, so this happens in
-    /// a separate pass
+    /// a separate pass, as does filtering by `severity` when CVSS metrics
+    /// or severity overrides are configured (see [`Settings::is_below_severity`])
     pub fn query(&self) -> Query {
         let mut query = Query::crate_scope();
 
@@ -92,11 +273,380 @@ impl Settings {
         }
 
         if let Some(severity) = self.severity {
-            query = query.severity(severity);
+            if !self.adjusts_severity() {
+                query = query.severity(severity);
+            }
         }
 
         query
     }
+
+    /// Is the advisory with the given ID ignored as of the given date?
+    ///
+    /// Ignores which expired before `today` no longer apply.
+    pub fn is_ignored(&self, id: &advisory::Id, today: &advisory::Date) -> bool {
+        self.ignore
+            .iter()
+            .any(|ignore| &ignore.id == id && !ignore.is_expired(today))
+    }
+
+    /// Is the given package ignored, either entirely or because its version
+    /// matches the requirement it's ignored for?
+    pub fn is_package_ignored(&self, package: &Package) -> bool {
+        self.ignore_package
+            .iter()
+            .any(|ignore| ignore.matches(package))
+    }
+
+    /// Was the advisory with the given ID ignored, but all of its ignores
+    /// have expired as of the given date?
+    pub fn ignore_expired(&self, id: &advisory::Id, today: &advisory::Date) -> bool {
+        self.ignore.iter().any(|ignore| &ignore.id == id) && !self.is_ignored(id, today)
+    }
+
+    /// Severity of the given advisory after applying the configured severity
+    /// override or CVSS Temporal and Environmental metrics, or its own
+    /// severity if none apply.
+    ///
+    /// Advisories without a CVSS vector have no severity, unless it's overridden.
+    pub fn effective_severity(&self, advisory: &advisory::Metadata) -> Option<advisory::Severity> {
+        self.adjusted_severity(advisory)
+            .or_else(|| advisory.cvss.as_ref().map(|cvss| cvss.severity()))
+    }
+
+    /// Is the effective severity of the given advisory below the configured
+    /// `severity`, i.e. is it left out of the report?
+    ///
+    /// Advisories without a CVSS vector are never below it.
+    pub fn is_below_severity(&self, advisory: &advisory::Metadata) -> bool {
+        match (self.severity, self.effective_severity(advisory)) {
+            (Some(severity), Some(advisory_severity)) => advisory_severity < severity,
+            _ => false,
+        }
+    }
+
+    /// Severity of the given advisory after applying the configured severity
+    /// override or CVSS metrics, if any apply to it
+    pub(crate) fn adjusted_severity(
+        &self,
+        advisory: &advisory::Metadata,
+    ) -> Option<advisory::Severity> {
+        if let Some(SeverityOverride::Severity(severity)) =
+            self.severity_overrides.get(&advisory.id)
+        {
+            return Some(*severity);
+        }
+
+        let metrics = self
+            .cvss_overrides
+            .get(&advisory.id)
+            .or(self.cvss_environment.as_ref())?;
+
+        match &advisory.cvss {
+            Some(cvss::Cvss::V3(base)) => Some(metrics.severity(base)),
+            _ => None,
+        }
+    }
+
+    /// Are any CVSS Temporal or Environmental metrics or severity overrides
+    /// configured, which can change the severity of advisories?
+    fn adjusts_severity(&self) -> bool {
+        self.cvss_environment.is_some()
+            || !self.cvss_overrides.is_empty()
+            || !self.severity_overrides.is_empty()
+    }
+
+    /// Load [`Settings::severity_overrides`] from a policy file, in addition
+    /// to those already configured. Overrides in the file take precedence.
+    ///
+    /// The file is a TOML table of advisory IDs and their overrides:
+    ///
+    /// ```toml
+    /// "RUSTSEC-2001-0001" = "low"
+    /// "RUSTSEC-2002-0001" = "critical"
+    /// "RUSTSEC-2003-0001" = "ignore"
+    /// ```
+    pub fn load_severity_overrides(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let toml = fs::read_to_string(path)?;
+        let overrides: Map<advisory::Id, SeverityOverride> =
+            toml::from_str(&toml).map_err(|e| {
+                format_err!(
+                    ErrorKind::Parse,
+                    "error parsing severity overrides {}: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+
+        self.severity_overrides.extend(overrides);
+        Ok(())
+    }
+
+    /// Is the given vulnerability below the configured `severity_threshold`,
+    /// i.e. reported without failing the audit?
+    pub fn is_below_threshold(&self, vuln: &Vulnerability) -> bool {
+        let Some(threshold) = self.severity_threshold else {
+            return false;
+        };
+
+        match self.effective_severity(&vuln.advisory) {
+            Some(severity) => severity < threshold,
+            None => self.exclude_unscored,
+        }
+    }
+}
+
+/// Advisory which is ignored when generating reports
+///
+/// In configuration files this is either a plain advisory ID, which is
+/// ignored indefinitely, or a table with an `id` and the date the ignore
+/// `expires` on:
+///
+/// ```toml
+/// ignore = ["RUSTSEC-2001-0001", { id = "RUSTSEC-2002-0001", expires = "2030-01-01" }]
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(from = "IgnoredAdvisoryRepr", into = "IgnoredAdvisoryRepr")]
+pub struct IgnoredAdvisory {
+    /// ID of the ignored advisory
+    pub id: advisory::Id,
+
+    /// Last day on which the advisory is ignored, if any
+    pub expires: Option<advisory::Date>,
+}
+
+impl IgnoredAdvisory {
+    /// Has this ignore expired as of the given date?
+    pub fn is_expired(&self, today: &advisory::Date) -> bool {
+        self.expires
+            .as_ref()
+            .map_or(false, |expires| expires < today)
+    }
+}
+
+impl From<advisory::Id> for IgnoredAdvisory {
+    fn from(id: advisory::Id) -> Self {
+        Self { id, expires: None }
+    }
+}
+
+/// Package to ignore, see [`Settings::ignore_package`]
+///
+/// In configuration files this is a table with the `name` of the package, and
+/// optionally the `version` requirement the ignore is limited to:
+///
+/// ```toml
+/// ignore_package = [{ name = "foo" }, { name = "bar", version = "< 1.2" }]
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct IgnoredPackage {
+    /// Name of the ignored package
+    pub name: package::Name,
+
+    /// Versions of the package which are ignored, or all of them if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<VersionReq>,
+}
+
+impl IgnoredPackage {
+    /// Does this ignore apply to the given package?
+    pub fn matches(&self, package: &Package) -> bool {
+        self.name == package.name
+            && self
+                .version
+                .as_ref()
+                .map_or(true, |req| req.matches(&package.version))
+    }
+}
+
+impl From<package::Name> for IgnoredPackage {
+    fn from(name: package::Name) -> Self {
+        Self {
+            name,
+            version: None,
+        }
+    }
+}
+
+/// Serialized form of [`IgnoredAdvisory`]
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum IgnoredAdvisoryRepr {
+    Id(advisory::Id),
+    Expiring {
+        id: advisory::Id,
+        expires: advisory::Date,
+    },
+}
+
+impl From<IgnoredAdvisoryRepr> for IgnoredAdvisory {
+    fn from(repr: IgnoredAdvisoryRepr) -> Self {
+        match repr {
+            IgnoredAdvisoryRepr::Id(id) => id.into(),
+            IgnoredAdvisoryRepr::Expiring { id, expires } => Self {
+                id,
+                expires: Some(expires),
+            },
+        }
+    }
+}
+
+impl From<IgnoredAdvisory> for IgnoredAdvisoryRepr {
+    fn from(ignore: IgnoredAdvisory) -> Self {
+        match ignore.expires {
+            Some(expires) => Self::Expiring {
+                id: ignore.id,
+                expires,
+            },
+            None => Self::Id(ignore.id),
+        }
+    }
+}
+
+/// Override of the severity of an advisory by organizational policy, see
+/// [`Settings::severity_overrides`]
+///
+/// In configuration files this is either the severity to report the advisory
+/// with, or `ignore`:
+///
+/// ```toml
+/// [severity_overrides]
+/// "RUSTSEC-2001-0001" = "low"
+/// "RUSTSEC-2002-0001" = "critical"
+/// "RUSTSEC-2003-0001" = "ignore"
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SeverityOverride {
+    /// Report the advisory with the given severity, which may be lower
+    /// (a downgrade) or higher (an escalation) than its own
+    Severity(advisory::Severity),
+
+    /// Leave the advisory out of the vulnerabilities found, listing it in
+    /// [`VulnerabilityInfo::ignored_by_policy`] instead
+    Ignore,
+}
+
+impl fmt::Display for SeverityOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Severity(severity) => severity.fmt(f),
+            Self::Ignore => f.write_str("ignore"),
+        }
+    }
+}
+
+impl FromStr for SeverityOverride {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if s == "ignore" {
+            return Ok(Self::Ignore);
+        }
+
+        s.parse().map(Self::Severity).map_err(|_| {
+            format_err!(
+                ErrorKind::Parse,
+                "invalid severity override: {} (expected a severity or `ignore`)",
+                s
+            )
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for SeverityOverride {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use de::Error;
+        let string = String::deserialize(deserializer)?;
+        string.parse().map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for SeverityOverride {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
+
+/// Vulnerability or warning passed on by [`Report::generate_streaming`]
+#[derive(Clone, Debug)]
+pub enum Finding {
+    /// Vulnerability which would be listed in [`VulnerabilityInfo::list`]
+    Vulnerability(Vulnerability),
+
+    /// Warning which would be listed in the [`Report::warnings`]
+    Warning(Warning),
+}
+
+/// Vulnerabilities and warnings found in some of the packages of a lockfile,
+/// sorted like in a [`Report`]
+struct Findings {
+    vulnerabilities: Vec<Vulnerability>,
+    below_threshold: Vec<Vulnerability>,
+    ignored_by_policy: Vec<Vulnerability>,
+    warnings: WarningInfo,
+}
+
+impl Findings {
+    /// Find the vulnerabilities and warnings in the given packages
+    fn new(db: &Database, packages: &[Package], settings: &Settings) -> Self {
+        let today = advisory::Date::today();
+        let mut warnings = find_package_warnings(db, packages, settings);
+
+        let (mut ignored_by_policy, vulnerabilities): (Vec<_>, Vec<_>) = db
+            .vulnerabilities_with_overrides(packages, settings)
+            .into_iter()
+            .partition(|vuln| vuln.severity_override == Some(SeverityOverride::Ignore));
+
+        for vuln in &vulnerabilities {
+            if settings.ignore_expired(&vuln.advisory.id, &today) {
+                push_warning(
+                    &mut warnings,
+                    Warning::new(
+                        warning::WarningKind::IgnoreExpired,
+                        &vuln.package,
+                        Some(vuln.advisory.clone()),
+                        vuln.affected.clone(),
+                        Some(vuln.versions.clone()),
+                    ),
+                );
+            }
+        }
+
+        let vulnerabilities = if settings.collapse_cve_aliases {
+            collapse_cve_aliases(vulnerabilities)
+        } else {
+            vulnerabilities
+        };
+
+        let (mut below_threshold, mut vulnerabilities): (Vec<_>, Vec<_>) = vulnerabilities
+            .into_iter()
+            .partition(|vuln| settings.is_below_threshold(vuln));
+        sort_vulnerabilities(&mut vulnerabilities);
+        sort_vulnerabilities(&mut below_threshold);
+        sort_vulnerabilities(&mut ignored_by_policy);
+        sort_warnings(&mut warnings);
+
+        Self {
+            vulnerabilities,
+            below_threshold,
+            ignored_by_policy,
+            warnings,
+        }
+    }
+
+    /// Record how each vulnerable package is pulled in according to the
+    /// dependency tree of the lockfile, if it could be computed. Paths aren't
+    /// recorded for vulnerabilities ignored by policy.
+    #[cfg(feature = "dependency-tree")]
+    fn with_paths(mut self, tree: Option<&cargo_lock::dependency::Tree>) -> Self {
+        if let Some(tree) = tree {
+            dependency_paths::populate(tree, &mut self.vulnerabilities);
+            dependency_paths::populate(tree, &mut self.below_threshold);
+        }
+
+        self
+    }
 }
 
 /// Information about the advisory database
@@ -157,15 +707,38 @@ pub struct VulnerabilityInfo {
 
     /// List of detected vulnerabilities
     pub list: Vec<Vulnerability>,
+
+    /// Vulnerabilities which were detected, but are below the configured
+    /// [`Settings::severity_threshold`] and thus not counted as found
+    #[serde(
+        default,
+        rename = "below-threshold",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub below_threshold: Vec<Vulnerability>,
+
+    /// Vulnerabilities which were detected, but are ignored according to
+    /// [`Settings::severity_overrides`] and thus not counted as found
+    #[serde(
+        default,
+        rename = "ignored-by-policy",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub ignored_by_policy: Vec<Vulnerability>,
 }
 
 impl VulnerabilityInfo {
-    /// Create new vulnerability info
-    pub fn new(list: Vec<Vulnerability>) -> Self {
+    /// Create new vulnerability info, sorting the vulnerabilities by advisory
+    /// ID and package so that reports are reproducible
+    pub fn new(mut list: Vec<Vulnerability>) -> Self {
+        sort_vulnerabilities(&mut list);
+
         Self {
             found: !list.is_empty(),
             count: list.len(),
             list,
+            below_threshold: vec![],
+            ignored_by_policy: vec![],
         }
     }
 }
@@ -175,18 +748,29 @@ pub type WarningInfo = Map<warning::WarningKind, Vec<Warning>>;
 
 /// Find warnings from the given advisory [`Database`] and [`Lockfile`]
 pub fn find_warnings(db: &Database, lockfile: &Lockfile, settings: &Settings) -> WarningInfo {
+    find_package_warnings(db, &lockfile.packages, settings)
+}
+
+/// Find warnings about the given packages of a lockfile
+fn find_package_warnings(db: &Database, packages: &[Package], settings: &Settings) -> WarningInfo {
     let query = settings.query().informational(true);
+    let today = advisory::Date::today();
 
     let mut warnings = WarningInfo::default();
 
     // TODO(tarcieri): abstract `Cargo.lock` query logic between vulnerabilities/warnings
-    for advisory_vuln in db.query_vulnerabilities(lockfile, &query) {
+    for advisory_vuln in db.packages_vulnerabilities(packages, &query) {
         let advisory = &advisory_vuln.advisory;
 
-        if settings.ignore.contains(&advisory.id) {
+        if settings.is_ignored(&advisory.id, &today)
+            || settings.is_package_ignored(&advisory_vuln.package)
+            || settings.is_below_severity(advisory)
+        {
             continue;
         }
 
+        let ignore_expired = settings.ignore_expired(&advisory.id, &today);
+
         if settings
             .informational_warnings
             .iter()
@@ -210,14 +794,106 @@ pub fn find_warnings(db: &Database, lockfile: &Lockfile, settings: &Settings) ->
                 Some(advisory_vuln.versions.clone()),
             );
 
-            match warnings.entry(warning.kind) {
-                map::Entry::Occupied(entry) => (*entry.into_mut()).push(warning),
-                map::Entry::Vacant(entry) => {
-                    entry.insert(vec![warning]);
-                }
+            if ignore_expired {
+                push_warning(
+                    &mut warnings,
+                    Warning {
+                        kind: warning::WarningKind::IgnoreExpired,
+                        ..warning.clone()
+                    },
+                );
+            }
+
+            push_warning(&mut warnings, warning);
+        }
+    }
+
+    if settings.warn_source_not_registry {
+        for package in packages {
+            // Path dependencies and workspace members have no source at all
+            if package
+                .source
+                .as_ref()
+                .map_or(false, |source| !source.is_registry())
+                && !settings.is_package_ignored(package)
+            {
+                push_warning(
+                    &mut warnings,
+                    Warning::new(
+                        warning::WarningKind::SourceNotRegistry,
+                        package,
+                        None,
+                        None,
+                        None,
+                    ),
+                );
             }
         }
     }
 
     warnings
 }
+
+/// Collapse vulnerabilities of the same package whose advisories share a CVE
+/// alias into the one with the lowest advisory ID
+fn collapse_cve_aliases(mut vulnerabilities: Vec<Vulnerability>) -> Vec<Vulnerability> {
+    sort_vulnerabilities(&mut vulnerabilities);
+    let mut collapsed: Vec<Vulnerability> = Vec::with_capacity(vulnerabilities.len());
+
+    for vuln in vulnerabilities {
+        let same_cve = collapsed.iter_mut().find(|other| {
+            other.package.name == vuln.package.name
+                && other.package.version == vuln.package.version
+                && other
+                    .advisory
+                    .cve_aliases()
+                    .any(|cve| vuln.advisory.cve_aliases().any(|alias| alias == cve))
+        });
+
+        match same_cve {
+            Some(other) => other.duplicates.push(vuln.advisory.id),
+            None => collapsed.push(vuln),
+        }
+    }
+
+    collapsed
+}
+
+/// Sort vulnerabilities by advisory ID, then by package
+fn sort_vulnerabilities(vulnerabilities: &mut [Vulnerability]) {
+    vulnerabilities.sort_by(|a, b| {
+        (&a.advisory.id, &a.package.name, &a.package.version).cmp(&(
+            &b.advisory.id,
+            &b.package.name,
+            &b.package.version,
+        ))
+    });
+}
+
+/// Sort the warnings of each kind by advisory ID, then by package.
+///
+/// Warnings without an advisory (e.g. about yanked packages) come first.
+fn sort_warnings(warnings: &mut WarningInfo) {
+    for warnings in warnings.values_mut() {
+        warnings.sort_by(|a, b| warning_key(a).cmp(&warning_key(b)));
+    }
+}
+
+/// Key warnings are sorted by
+fn warning_key(warning: &Warning) -> (Option<&advisory::Id>, &package::Name, &Version) {
+    (
+        warning.advisory.as_ref().map(|advisory| &advisory.id),
+        &warning.package.name,
+        &warning.package.version,
+    )
+}
+
+/// Add a warning to the list of warnings of its kind
+fn push_warning(warnings: &mut WarningInfo, warning: Warning) {
+    match warnings.entry(warning.kind) {
+        map::Entry::Occupied(entry) => (*entry.into_mut()).push(warning),
+        map::Entry::Vacant(entry) => {
+            entry.insert(vec![warning]);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Dependency paths leading to vulnerable packages
+
+use crate::{
+    package::{self, Package},
+    vulnerability::Vulnerability,
+    Map, Set,
+};
+use cargo_lock::dependency::{
+    graph::{EdgeDirection, NodeIndex},
+    Dependency, Tree,
+};
+use std::collections::VecDeque;
+
+/// Record the [`Vulnerability::paths`] of the given vulnerabilities, using
+/// the dependency tree of their lockfile
+pub(super) fn populate(tree: &Tree, vulns: &mut [Vulnerability]) {
+    for vuln in vulns {
+        vuln.paths = find(tree, &vuln.package);
+    }
+}
+
+/// Find a shortest path from every root package which depends on `package`
+fn find(tree: &Tree, package: &Package) -> Vec<Vec<package::Name>> {
+    let graph = tree.graph();
+    let Some(&target) = tree.nodes().get(&Dependency::from(package)) else {
+        return vec![];
+    };
+
+    // Breadth-first search towards the roots, remembering the step back towards
+    // `package` for every dependent; each root is thus reached via a shortest path
+    let mut next_hop: Map<NodeIndex, NodeIndex> = Map::new();
+    let mut visited = Set::from([target]);
+    let mut queue = VecDeque::from([target]);
+    let mut paths = vec![];
+
+    while let Some(node) = queue.pop_front() {
+        let mut dependents = graph
+            .neighbors_directed(node, EdgeDirection::Incoming)
+            .peekable();
+
+        if dependents.peek().is_none() {
+            let mut path = vec![graph[node].name.clone()];
+            let mut current = node;
+
+            while let Some(&next) = next_hop.get(&current) {
+                path.push(graph[next].name.clone());
+                current = next;
+            }
+
+            paths.push(path);
+            continue;
+        }
+
+        for dependent in dependents {
+            if visited.insert(dependent) {
+                next_hop.insert(dependent, node);
+                queue.push_back(dependent);
+            }
+        }
+    }
+
+    paths.sort();
+    paths
+}
@@ -0,0 +1,161 @@
+//! Export of reports as JUnit XML
+//!
+//! Many CI systems display JUnit XML test results, which lets audit findings
+//! show up next to failing unit tests. Every package in the lockfile becomes a
+//! test case, which fails if the package is vulnerable.
+//!
+//! Warnings fail a test case if their kind is listed in
+//! [`Settings::deny_warnings`](super::Settings::deny_warnings), and mark it as
+//! skipped otherwise.
+
+use super::Report;
+use crate::{package::Package, Lockfile};
+use std::fmt::Write;
+
+/// Name of the generated test suite
+const SUITE_NAME: &str = "rustsec";
+
+/// Outcome of auditing a single package
+#[derive(Default)]
+struct TestCase {
+    /// Findings which fail the test case: `(type, message, details)`
+    failures: Vec<(String, String, String)>,
+
+    /// Messages of findings which don't fail the test case
+    skipped: Vec<String>,
+}
+
+/// Convert a [`Report`] on the given [`Lockfile`] into a JUnit XML test suite
+///
+/// The lockfile is needed to list packages without any findings as passing
+/// test cases, since the report itself only contains findings.
+pub fn to_junit(report: &Report, lockfile: &Lockfile) -> String {
+    let mut cases: Vec<(&Package, TestCase)> = lockfile
+        .packages
+        .iter()
+        .map(|package| (package, TestCase::default()))
+        .collect();
+
+    for vuln in &report.vulnerabilities.list {
+        if let Some(case) = case_for(&mut cases, &vuln.package) {
+            case.failures.push((
+                "vulnerability".to_owned(),
+                format!("{}: {}", vuln.advisory.id, vuln.advisory.title),
+                vuln.advisory.description.clone(),
+            ));
+        }
+    }
+
+    for warning in report.warnings.values().flatten() {
+        let Some(case) = case_for(&mut cases, &warning.package) else {
+            continue;
+        };
+
+        let (message, details) = match &warning.advisory {
+            Some(advisory) => (
+                format!("{}: {}", advisory.id, advisory.title),
+                advisory.description.clone(),
+            ),
+            None => (format!("{} package", warning.kind), String::new()),
+        };
+
+        if report.settings.deny_warnings.contains(&warning.kind) {
+            case.failures
+                .push((warning.kind.to_string(), message, details));
+        } else {
+            case.skipped.push(message);
+        }
+    }
+
+    let failures = cases
+        .iter()
+        .filter(|(_, case)| !case.failures.is_empty())
+        .count();
+    let skipped = cases
+        .iter()
+        .filter(|(_, case)| case.failures.is_empty() && !case.skipped.is_empty())
+        .count();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        xml,
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">",
+        SUITE_NAME,
+        cases.len(),
+        failures,
+        skipped
+    )
+    .unwrap();
+
+    for (package, case) in &cases {
+        let name = escape(&format!("{} {}", package.name, package.version));
+        write!(
+            xml,
+            "  <testcase classname=\"{}\" name=\"{}\"",
+            SUITE_NAME, name
+        )
+        .unwrap();
+
+        if case.failures.is_empty() && case.skipped.is_empty() {
+            xml.push_str("/>\n");
+            continue;
+        }
+
+        xml.push_str(">\n");
+
+        if case.failures.is_empty() {
+            writeln!(
+                xml,
+                "    <skipped message=\"{}\"/>",
+                escape(&case.skipped.join("; "))
+            )
+            .unwrap();
+        }
+
+        for (kind, message, details) in &case.failures {
+            writeln!(
+                xml,
+                "    <failure type=\"{}\" message=\"{}\">{}</failure>",
+                escape(kind),
+                escape(message),
+                escape(details)
+            )
+            .unwrap();
+        }
+
+        xml.push_str("  </testcase>\n");
+    }
+
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+/// Find the test case of the given package
+fn case_for<'a>(
+    cases: &'a mut [(&Package, TestCase)],
+    package: &Package,
+) -> Option<&'a mut TestCase> {
+    cases
+        .iter_mut()
+        .find(|(pkg, _)| *pkg == package)
+        .map(|(_, case)| case)
+}
+
+/// Escape a string for use in XML attributes and text
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
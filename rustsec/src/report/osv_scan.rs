@@ -0,0 +1,230 @@
+//! Export of reports as OSV scan results
+//!
+//! The types in this module follow the [JSON output of OSV-Scanner], so that
+//! tools consuming its results (e.g. vulnerability dashboards) can import the
+//! findings of an audit as well. Every vulnerable package is listed with the
+//! advisories affecting it, which are reduced to their ID, aliases, summary and
+//! severity rather than full OSV records. They are meant to be serialized with
+//! a JSON serializer such as `serde_json`.
+//!
+//! [JSON output of OSV-Scanner]: https://google.github.io/osv-scanner/output/#json-output
+
+use super::Report;
+use crate::{
+    advisory,
+    package::{self, Package},
+    vulnerability::Vulnerability,
+    Map, Version,
+};
+use serde::Serialize;
+
+/// Path the scanned source is reported at: the lockfile the vulnerable packages are listed in
+const LOCKFILE_PATH: &str = "Cargo.lock";
+
+/// Ecosystem of the packages, as named by OSV
+const ECOSYSTEM: &str = "crates.io";
+
+/// Convert the vulnerabilities of a [`Report`] into OSV scan results
+///
+/// Vulnerable packages are sorted by name and version. Each vulnerability
+/// makes up a group of its own, which also lists the advisories collapsed into
+/// it by [`Settings::collapse_cve_aliases`](super::Settings::collapse_cve_aliases).
+/// Vulnerabilities below the [`Settings::severity_threshold`](super::Settings::severity_threshold)
+/// are included, while those ignored by policy and warnings aren't.
+pub fn to_osv_scan(report: &Report) -> ScanResults {
+    let mut packages: Map<(&package::Name, &Version), PackageVulnerabilities> = Map::new();
+
+    let vulnerabilities = &report.vulnerabilities;
+    for vuln in vulnerabilities
+        .list
+        .iter()
+        .chain(&vulnerabilities.below_threshold)
+    {
+        let package = packages
+            .entry((&vuln.package.name, &vuln.package.version))
+            .or_insert_with(|| PackageVulnerabilities::new(&vuln.package));
+
+        package
+            .vulnerabilities
+            .push(VulnerabilitySummary::new(&vuln.advisory));
+        package.groups.push(Group::new(vuln));
+    }
+
+    let results = if packages.is_empty() {
+        vec![]
+    } else {
+        vec![SourceResults {
+            source: Source {
+                path: LOCKFILE_PATH.to_owned(),
+                kind: "lockfile".to_owned(),
+            },
+            packages: packages.into_values().collect(),
+        }]
+    };
+
+    ScanResults { results }
+}
+
+/// Top-level object of OSV scan results
+#[derive(Clone, Debug, Serialize)]
+pub struct ScanResults {
+    /// Results for each scanned source: a single lockfile, or none at all if
+    /// no vulnerabilities were found
+    pub results: Vec<SourceResults>,
+}
+
+/// Vulnerable packages found in a scanned source
+#[derive(Clone, Debug, Serialize)]
+pub struct SourceResults {
+    /// The scanned source
+    pub source: Source,
+
+    /// Vulnerable packages
+    pub packages: Vec<PackageVulnerabilities>,
+}
+
+/// Scanned source, i.e. a lockfile
+#[derive(Clone, Debug, Serialize)]
+pub struct Source {
+    /// Path of the source
+    pub path: String,
+
+    /// Kind of source
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// Vulnerable package and the advisories affecting it
+#[derive(Clone, Debug, Serialize)]
+pub struct PackageVulnerabilities {
+    /// The vulnerable package
+    pub package: PackageInfo,
+
+    /// Advisories affecting the package
+    pub vulnerabilities: Vec<VulnerabilitySummary>,
+
+    /// Groups of advisories describing the same vulnerability
+    pub groups: Vec<Group>,
+}
+
+impl PackageVulnerabilities {
+    fn new(package: &Package) -> Self {
+        Self {
+            package: PackageInfo {
+                name: package.name.to_string(),
+                version: package.version.to_string(),
+                ecosystem: ECOSYSTEM.to_owned(),
+            },
+            vulnerabilities: vec![],
+            groups: vec![],
+        }
+    }
+}
+
+/// Name and version of a package
+#[derive(Clone, Debug, Serialize)]
+pub struct PackageInfo {
+    /// Name of the package
+    pub name: String,
+
+    /// Version of the package
+    pub version: String,
+
+    /// Ecosystem of the package, i.e. `crates.io`
+    pub ecosystem: String,
+}
+
+/// Advisory affecting a package
+#[derive(Clone, Debug, Serialize)]
+pub struct VulnerabilitySummary {
+    /// Advisory ID
+    pub id: String,
+
+    /// IDs the advisory is also known as, e.g. CVEs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+
+    /// Advisory title
+    pub summary: String,
+
+    /// CVSS vector of the advisory, if any
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub severity: Vec<Severity>,
+}
+
+impl VulnerabilitySummary {
+    fn new(advisory: &advisory::Metadata) -> Self {
+        Self {
+            id: advisory.id.to_string(),
+            aliases: advisory.aliases.iter().map(ToString::to_string).collect(),
+            summary: advisory.title.clone(),
+            severity: advisory.cvss.iter().map(Severity::new).collect(),
+        }
+    }
+}
+
+/// Severity of an advisory as an OSV `severity` entry
+#[derive(Clone, Debug, Serialize)]
+pub struct Severity {
+    /// Kind of severity score, e.g. `CVSS_V3`
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// The CVSS vector
+    pub score: String,
+}
+
+impl Severity {
+    fn new(cvss: &cvss::Cvss) -> Self {
+        let kind = match cvss {
+            cvss::Cvss::V3(_) => "CVSS_V3",
+            cvss::Cvss::V4(_) => "CVSS_V4",
+        };
+
+        Self {
+            kind: kind.to_owned(),
+            score: cvss.to_string(),
+        }
+    }
+}
+
+/// Advisories describing the same vulnerability of a package
+#[derive(Clone, Debug, Serialize)]
+pub struct Group {
+    /// IDs of the advisories in the group
+    pub ids: Vec<String>,
+
+    /// IDs the vulnerability is known as, including those of the advisories
+    pub aliases: Vec<String>,
+
+    /// CVSS score of the vulnerability, e.g. `9.8`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_severity: Option<String>,
+}
+
+impl Group {
+    fn new(vuln: &Vulnerability) -> Self {
+        let ids: Vec<String> = std::iter::once(&vuln.advisory.id)
+            .chain(&vuln.duplicates)
+            .map(ToString::to_string)
+            .collect();
+
+        let mut aliases = ids.clone();
+        for alias in &vuln.advisory.aliases {
+            let alias = alias.to_string();
+            if !aliases.contains(&alias) {
+                aliases.push(alias);
+            }
+        }
+
+        Self {
+            ids,
+            aliases,
+            max_severity: vuln
+                .advisory
+                .cvss
+                .as_ref()
+                .map(|cvss| format!("{:.1}", cvss.score())),
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Export of reports in the Static Analysis Results Interchange Format (SARIF)
+//!
+//! SARIF is consumed by e.g. GitHub code scanning. The types in this module
+//! cover the subset of [SARIF 2.1.0] needed to describe a [`Report`], and
+//! are meant to be serialized with a JSON serializer such as `serde_json`.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use super::Report;
+use crate::{
+    advisory::{self, Severity},
+    package::Package,
+    warning::WarningKind,
+    Map,
+};
+use serde::Serialize;
+
+/// JSON schema of SARIF 2.1.0 logs
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// SARIF version of the logs generated by this module
+const VERSION: &str = "2.1.0";
+
+/// Location all results point to: the lockfile the vulnerable packages are listed in
+const LOCKFILE_URI: &str = "Cargo.lock";
+
+/// Convert a [`Report`] into a SARIF log
+///
+/// Every advisory becomes a rule, and every vulnerability or warning a result
+/// pointing at `Cargo.lock`. Vulnerabilities are reported at a level derived from
+/// their CVSS severity, warnings (e.g. yanked or unmaintained crates) at the
+/// `warning` level.
+pub fn to_sarif(report: &Report) -> Log {
+    let mut rules = Map::new();
+    let mut results = vec![];
+
+    for vuln in &report.vulnerabilities.list {
+        let rule = Rule::for_advisory(&vuln.advisory);
+        let level = Level::for_severity(vuln.advisory.cvss.as_ref().map(|cvss| cvss.severity()));
+        let message = format!(
+            "{}: {}",
+            package_version(&vuln.package),
+            vuln.advisory.title
+        );
+
+        results.push(Finding::new(&rule.id, level, message));
+        rules.entry(rule.id.clone()).or_insert(rule);
+    }
+
+    for warning in report.warnings.values().flatten() {
+        let (rule, message) = match &warning.advisory {
+            Some(advisory) => (
+                Rule::for_advisory(advisory),
+                format!("{}: {}", package_version(&warning.package), advisory.title),
+            ),
+            None => (
+                Rule::for_warning_kind(warning.kind),
+                format!(
+                    "{}: {} package",
+                    package_version(&warning.package),
+                    warning.kind
+                ),
+            ),
+        };
+
+        results.push(Finding::new(&rule.id, Level::Warning, message));
+        rules.entry(rule.id.clone()).or_insert(rule);
+    }
+
+    Log {
+        schema: SCHEMA.to_owned(),
+        version: VERSION.to_owned(),
+        runs: vec![Run {
+            tool: Tool {
+                driver: ToolComponent {
+                    name: "rustsec".to_owned(),
+                    information_uri: "https://rustsec.org".to_owned(),
+                    rules: rules.into_values().collect(),
+                },
+            },
+            results,
+        }],
+    }
+}
+
+/// Name and version of a package, e.g. `base64 0.5.1`
+fn package_version(package: &Package) -> String {
+    format!("{} {}", package.name, package.version)
+}
+
+/// Top-level SARIF object
+#[derive(Clone, Debug, Serialize)]
+pub struct Log {
+    /// URI of the JSON schema for this log
+    #[serde(rename = "$schema")]
+    pub schema: String,
+
+    /// SARIF version
+    pub version: String,
+
+    /// Runs of analysis tools: always a single one
+    pub runs: Vec<Run>,
+}
+
+/// Results of a single run of an analysis tool
+#[derive(Clone, Debug, Serialize)]
+pub struct Run {
+    /// Tool which produced the results
+    pub tool: Tool,
+
+    /// Findings of the tool
+    pub results: Vec<Finding>,
+}
+
+/// Analysis tool
+#[derive(Clone, Debug, Serialize)]
+pub struct Tool {
+    /// Main component of the tool
+    pub driver: ToolComponent,
+}
+
+/// Component of an analysis tool
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolComponent {
+    /// Name of the component
+    pub name: String,
+
+    /// Web page with information about the component
+    pub information_uri: String,
+
+    /// Rules the results refer to, i.e. advisories
+    pub rules: Vec<Rule>,
+}
+
+/// Rule which was violated: an advisory, or a kind of warning without one
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    /// Advisory ID, or warning kind
+    pub id: String,
+
+    /// Advisory title
+    pub short_description: Message,
+
+    /// Advisory description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_description: Option<Message>,
+
+    /// Web page with more information about the advisory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help_uri: Option<String>,
+}
+
+impl Rule {
+    fn for_advisory(advisory: &advisory::Metadata) -> Self {
+        Self {
+            id: advisory.id.to_string(),
+            short_description: Message::new(&advisory.title),
+            full_description: Some(Message::new(&advisory.description)),
+            help_uri: advisory
+                .url
+                .as_ref()
+                .map(ToString::to_string)
+                .or_else(|| advisory.id.url()),
+        }
+    }
+
+    fn for_warning_kind(kind: WarningKind) -> Self {
+        Self {
+            id: kind.to_string(),
+            short_description: Message::new(format!("{} package", kind)),
+            full_description: None,
+            help_uri: None,
+        }
+    }
+}
+
+/// A SARIF `result`: a single vulnerability or warning
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// ID of the [`Rule`] this is about
+    pub rule_id: String,
+
+    /// Severity of the finding
+    pub level: Level,
+
+    /// Description of the finding
+    pub message: Message,
+
+    /// Where the finding was made
+    pub locations: Vec<Location>,
+}
+
+impl Finding {
+    fn new(rule_id: &str, level: Level, message: String) -> Self {
+        Self {
+            rule_id: rule_id.to_owned(),
+            level,
+            message: Message::new(message),
+            locations: vec![Location {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation {
+                        uri: LOCKFILE_URI.to_owned(),
+                    },
+                },
+            }],
+        }
+    }
+}
+
+/// Severity of a [`Finding`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// Serious problem
+    Error,
+
+    /// Problem which should be looked into
+    Warning,
+
+    /// Minor problem
+    Note,
+}
+
+impl Level {
+    /// Level of a vulnerability with the given CVSS severity
+    pub fn for_severity(severity: Option<Severity>) -> Self {
+        match severity {
+            Some(Severity::None | Severity::Low) => Level::Note,
+            Some(Severity::Medium) => Level::Warning,
+            // Vulnerabilities which weren't scored are assumed to be serious
+            Some(Severity::High | Severity::Critical) | None => Level::Error,
+        }
+    }
+}
+
+/// Plain text message
+#[derive(Clone, Debug, Serialize)]
+pub struct Message {
+    /// Text of the message
+    pub text: String,
+}
+
+impl Message {
+    fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+}
+
+/// Location of a [`Finding`]
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    /// Location within a file
+    pub physical_location: PhysicalLocation,
+}
+
+/// Location within a file
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    /// File the location is in
+    pub artifact_location: ArtifactLocation,
+}
+
+/// File a [`Location`] is in
+#[derive(Clone, Debug, Serialize)]
+pub struct ArtifactLocation {
+    /// Path of the file, relative to the project root
+    pub uri: String,
+}
@@ -9,6 +9,7 @@ mod modification_time;
 mod repository;
 
 pub use self::{commit::Commit, commit_hash::CommitHash, repository::Repository};
+use std::time::Duration;
 use tame_index::external::gix;
 
 #[cfg(feature = "osv-export")]
@@ -17,6 +18,9 @@ pub use self::{gitpath::GitPath, modification_time::GitModificationTimes};
 /// Location of the RustSec advisory database for crates.io
 pub const DEFAULT_URL: &str = "https://github.com/RustSec/advisory-db.git";
 
+/// How long to wait for the filesystem lock on a repository by default
+pub(crate) const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
 #[inline]
 pub(crate) fn gix_time_to_time(time: gix::date::Time) -> time::OffsetDateTime {
     time::OffsetDateTime::from_unix_timestamp(time.seconds)
@@ -6,7 +6,7 @@ use crate::{
     error::{Error, ErrorKind},
     repository::{
         git::{CommitHash, Repository},
-        signature::Signature,
+        signature::{Signature, TrustedKey},
     },
 };
 use std::time::{Duration, SystemTime};
@@ -32,7 +32,6 @@ pub struct Commit {
     pub timestamp: time::OffsetDateTime,
 
     /// Signature on the commit (mandatory for Repository::fetch)
-    // TODO: actually verify signatures
     pub signature: Option<Signature>,
 
     /// Signed data to verify along with this commit
@@ -47,6 +46,36 @@ impl Commit {
             .head_commit()
             .map_err(|err| format_err!(ErrorKind::Repo, "unable to locate head commit: {}", err))?;
 
+        Self::from_gix_commit(commit)
+    }
+
+    /// Get information about the commit a branch, tag or commit hash points to
+    ///
+    /// Remote branches take precedence over tags, which take precedence over anything else.
+    pub(crate) fn from_rev(repo: &Repository, rev: &str) -> Result<Self, Error> {
+        let candidates = [
+            format!("refs/remotes/origin/{}", rev),
+            format!("refs/tags/{}", rev),
+            rev.to_owned(),
+        ];
+
+        let commit = candidates
+            .iter()
+            .find_map(|spec| {
+                repo.repo
+                    .rev_parse_single(format!("{}^{{commit}}", spec).as_str())
+                    .ok()
+            })
+            .ok_or_else(|| format_err!(ErrorKind::Repo, "unable to resolve git ref '{}'", rev))?
+            .object()
+            .map_err(|err| format_err!(ErrorKind::Repo, "unable to locate commit: {}", err))?
+            .try_into_commit()
+            .map_err(|err| format_err!(ErrorKind::Repo, "'{}' is not a commit: {}", rev, err))?;
+
+        Self::from_gix_commit(commit)
+    }
+
+    fn from_gix_commit(commit: gix::Commit<'_>) -> Result<Self, Error> {
         // Since we are pulling multiple pieces from the commit it's better to do this once
         let cref = commit.decode().map_err(|err| {
             format_err!(
@@ -118,6 +147,18 @@ impl Commit {
         self.signed_data.as_ref().map(|bytes| bytes.as_ref())
     }
 
+    /// Verify this commit was signed by one of the given trusted keys
+    pub fn verify_signature(&self, trusted_keys: &[TrustedKey]) -> Result<(), Error> {
+        match (&self.signature, self.raw_signed_bytes()) {
+            (Some(signature), Some(signed_data)) => signature.verify(signed_data, trusted_keys),
+            _ => fail!(
+                ErrorKind::Signature,
+                "commit {} is not signed",
+                self.commit_id
+            ),
+        }
+    }
+
     /// Reset the repository's state to match this commit
     pub(crate) fn reset(&self, repo: &Repository) -> Result<(), Error> {
         let repo = &repo.repo;
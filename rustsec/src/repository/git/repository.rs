@@ -1,7 +1,7 @@
 //! Git repositories
 use tame_index::external::gix;
 
-use super::{Commit, DEFAULT_URL};
+use super::{Commit, CommitHash, DEFAULT_LOCK_TIMEOUT, DEFAULT_URL};
 use crate::{
     error::{Error, ErrorKind},
     fs,
@@ -17,11 +17,16 @@ const ADVISORY_DB_DIRECTORY: &str = "advisory-db";
 /// Refspec used to fetch updates from remote advisory databases
 const REF_SPEC: &str = "+HEAD:refs/remotes/origin/HEAD";
 
+/// Refspecs used when checking out a specific branch, tag or commit
+const ALL_REF_SPECS: &[&str] = &[
+    REF_SPEC,
+    "+refs/heads/*:refs/remotes/origin/*",
+    "+refs/tags/*:refs/tags/*",
+];
+
 /// The direction of the remote
 const DIR: gix::remote::Direction = gix::remote::Direction::Fetch;
 
-const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
-
 /// Git repository for a Rust advisory DB.
 #[cfg_attr(docsrs, doc(cfg(feature = "git")))]
 pub struct Repository {
@@ -39,11 +44,37 @@ impl Repository {
             .join(ADVISORY_DB_DIRECTORY)
     }
 
+    /// Location where a clone of the advisory database at the given URL is kept
+    ///
+    /// This is [`Repository::default_path`] for the default database, and a
+    /// sibling directory named after the URL for any other one. Like the index
+    /// directories of Cargo, the name ends with a hash of the full URL, so that
+    /// URLs which only differ in punctuation are kept apart, while URLs of the
+    /// same repository (e.g. with and without a `.git` suffix) share a clone.
+    pub fn path_for_url(url: &str) -> PathBuf {
+        let url = canonical_url(url);
+        if url == canonical_url(DEFAULT_URL) {
+            return Self::default_path();
+        }
+
+        let name: String = url
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+
+        Self::default_path().with_file_name(format!(
+            "{}-{}-{:016x}",
+            ADVISORY_DB_DIRECTORY,
+            name,
+            url_hash(&url)
+        ))
+    }
+
     /// Fetch the default repository.
     ///
     /// ## Locking
     /// This function will wait for up to 5 minutes for the filesystem lock on the repository.
-    /// It will fail with [`rustsec::Error::LockTimeout`](Error) if the lock is still held
+    /// It will fail with an error of kind [`ErrorKind::LockTimeout`] if the lock is still held
     /// after that time. Use [Repository::fetch] if you need to configure locking behavior.
     ///
     /// Regardless of the timeout, this function relies on `panic = unwind` to avoid leaving stale locks
@@ -60,10 +91,12 @@ impl Repository {
 
     /// Create a new [`Repository`] with the given URL and path, and fetch its contents.
     ///
+    /// The URL must use the `https://` scheme.
+    ///
     /// ## Locking
     ///
     /// This function will wait for up to `lock_timeout` for the filesystem lock on the repository.
-    /// It will fail with [`rustsec::Error::LockTimeout`](Error) if the lock is still held
+    /// It will fail with an error of kind [`ErrorKind::LockTimeout`] if the lock is still held
     /// after that time.
     ///
     /// If `lock_timeout` is set to `std::time::Duration::from_secs(0)`, it will not wait at all,
@@ -78,7 +111,71 @@ impl Repository {
         ensure_fresh: bool,
         lock_timeout: Duration,
     ) -> Result<Self, Error> {
-        if !url.starts_with("https://") {
+        Self::fetch_inner(
+            url,
+            into_path.into(),
+            None,
+            ensure_fresh,
+            lock_timeout,
+            false,
+        )
+    }
+
+    /// Create a new [`Repository`] with the given URL and path, fetch its contents,
+    /// and check out the given branch, tag or commit instead of the remote `HEAD`.
+    ///
+    /// Since a pinned ref is not expected to move, the repository is not checked for staleness.
+    ///
+    /// Otherwise this behaves like [`Repository::fetch`], including locking.
+    pub fn fetch_ref<P: Into<PathBuf>>(
+        url: &str,
+        into_path: P,
+        git_ref: &str,
+        lock_timeout: Duration,
+    ) -> Result<Self, Error> {
+        Self::fetch_inner(
+            url,
+            into_path.into(),
+            Some(git_ref),
+            false,
+            lock_timeout,
+            false,
+        )
+    }
+
+    /// Fetch the advisory database at the given URL into [`Repository::path_for_url`],
+    /// checking out `git_ref` if given, see [`Database::fetch_from`](crate::Database::fetch_from).
+    ///
+    /// Unlike [`Repository::fetch`], local `file://` URLs are supported, and an
+    /// existing clone of another repository is never reused.
+    pub(crate) fn fetch_custom(url: &str, git_ref: Option<&str>) -> Result<Self, Error> {
+        Self::fetch_inner(
+            url,
+            Self::path_for_url(url),
+            git_ref,
+            git_ref.is_none(),
+            DEFAULT_LOCK_TIMEOUT,
+            true,
+        )
+    }
+
+    fn fetch_inner(
+        url: &str,
+        path: PathBuf,
+        git_ref: Option<&str>,
+        ensure_fresh: bool,
+        lock_timeout: Duration,
+        custom: bool,
+    ) -> Result<Self, Error> {
+        if custom {
+            if !url.starts_with("https://") && !url.starts_with("file://") {
+                fail!(
+                    ErrorKind::BadParam,
+                    "expected {} to start with https:// or file://",
+                    url
+                );
+            }
+        } else if !url.starts_with("https://") {
             fail!(
                 ErrorKind::BadParam,
                 "expected {} to start with https://",
@@ -86,7 +183,11 @@ impl Repository {
             );
         }
 
-        let path = into_path.into();
+        let ref_specs = if git_ref.is_some() {
+            ALL_REF_SPECS
+        } else {
+            &[REF_SPEC]
+        };
 
         if let Some(parent) = path.parent() {
             if !parent.is_dir() {
@@ -146,12 +247,30 @@ impl Repository {
             .map(|repo| repo.to_thread_local())
             .filter(|repo| {
                 repo.find_remote("origin").map_or(false, |remote| {
-                    remote
-                        .url(DIR)
-                        .map_or(false, |remote_url| remote_url.to_bstring() == url)
+                    remote.url(DIR).map_or(false, |remote_url| {
+                        canonical_url(&remote_url.to_bstring().to_string()) == canonical_url(url)
+                    })
                 })
-            })
-            .or_else(|| gix::open_opts(&path, open_with_complete_config).ok());
+            });
+
+            let repo = match repo {
+                Some(repo) => Some(repo),
+                // Never reuse a clone of another repository at the location of a
+                // custom database, as fetching it would silently load a different
+                // advisory database
+                None if custom => {
+                    if gix::open_opts(&path, open_with_complete_config).is_ok() {
+                        fail!(
+                            ErrorKind::Repo,
+                            "{} contains a clone of a repository other than {}; remove it to clone afresh",
+                            path.display(),
+                            url
+                        );
+                    }
+                    None
+                }
+                None => gix::open_opts(&path, open_with_complete_config).ok(),
+            };
 
             let res = if let Some(repo) = repo {
                 (repo, None)
@@ -165,7 +284,7 @@ impl Repository {
                     })?
                     .with_remote_name("origin")
                     .map_err(|err| format_err!(ErrorKind::Repo, "invalid remote name: {}", err))?
-                    .configure_remote(|remote| Ok(remote.with_refspecs([REF_SPEC], DIR)?))
+                    .configure_remote(move |remote| Ok(remote.with_refspecs(ref_specs, DIR)?))
                     .fetch_then_checkout(&mut progress, should_interrupt)
                     .map_err(|err| format_err!(ErrorKind::Repo, "failed to fetch repo: {}", err))?;
 
@@ -195,13 +314,20 @@ impl Repository {
             // If we didn't open a fresh repo we need to peform a fetch ourselves, and
             // do the work of updating the HEAD to point at the latest remote HEAD, which
             // gix doesn't currently do.
-            Self::perform_fetch(&mut repo)?;
+            Self::perform_fetch(&mut repo, ref_specs)?;
         }
 
         repo.object_cache_size_if_unset(4 * 1024 * 1024);
         let repo = Self { repo };
 
-        let latest_commit = Commit::from_repo_head(&repo)?;
+        let latest_commit = match git_ref {
+            Some(git_ref) => {
+                let commit = Commit::from_rev(&repo, git_ref)?;
+                repo.detach_head(&commit)?;
+                commit
+            }
+            None => Commit::from_repo_head(&repo)?,
+        };
         latest_commit.reset(&repo)?;
 
         // Ensure that the upstream repository hasn't gone stale
@@ -260,7 +386,130 @@ impl Repository {
         lookup().unwrap_or_default()
     }
 
-    fn perform_fetch(repo: &mut gix::Repository) -> Result<(), Error> {
+    /// Paths of the files which were added, modified or deleted between the
+    /// given commits, relative to the root of the repository
+    pub(crate) fn changed_paths(
+        &self,
+        from: CommitHash,
+        to: CommitHash,
+    ) -> Result<Vec<PathBuf>, Error> {
+        use gix::{bstr::ByteVec, diff::tree::recorder::Change};
+
+        let db = &self.repo.objects;
+        let mut from_buf = Vec::new();
+        let mut to_buf = Vec::new();
+        let from_tree = self.tree_iter(from, &mut from_buf)?;
+        let to_tree = self.tree_iter(to, &mut to_buf)?;
+
+        let mut recorder = gix::diff::tree::Recorder::default();
+        gix::diff::tree::Changes::from(Some(from_tree))
+            .needed_to_obtain(
+                to_tree,
+                &mut gix::diff::tree::State::default(),
+                |oid, buf| {
+                    use gix::odb::pack::FindExt;
+                    db.find(oid, buf)
+                        .map_err(|err| {
+                            format_err!(ErrorKind::Repo, "failed to find tree '{}': {}", oid, err)
+                        })?
+                        .0
+                        .try_into_tree_iter()
+                        .ok_or_else(|| format_err!(ErrorKind::Repo, "'{}' is not a tree", oid))
+                },
+                &mut recorder,
+            )
+            .map_err(|err| {
+                format_err!(
+                    ErrorKind::Repo,
+                    "failed to diff commit {} to {}: {}",
+                    from,
+                    to,
+                    err
+                )
+            })?;
+
+        // Paths which aren't valid UTF-8 are skipped, as they can't be advisory paths
+        Ok(recorder
+            .records
+            .into_iter()
+            .filter_map(|change| match change {
+                Change::Addition { path, .. }
+                | Change::Modification { path, .. }
+                | Change::Deletion { path, .. } => Vec::from(path).into_path_buf().ok(),
+            })
+            .collect())
+    }
+
+    /// Find the root tree of the given commit
+    fn tree_iter<'a>(
+        &self,
+        commit: CommitHash,
+        buf: &'a mut Vec<u8>,
+    ) -> Result<gix::objs::TreeRefIter<'a>, Error> {
+        use gix::prelude::Find;
+
+        let db = &self.repo.objects;
+        let tree_id = db
+            .try_find(commit.to_gix(), buf)
+            .map_err(|err| {
+                format_err!(
+                    ErrorKind::Repo,
+                    "failed to find commit '{}': {}",
+                    commit,
+                    err
+                )
+            })?
+            .ok_or_else(|| format_err!(ErrorKind::Repo, "commit '{}' not present", commit))?
+            .decode()
+            .map_err(|err| {
+                format_err!(
+                    ErrorKind::Repo,
+                    "unable to decode commit '{}': {}",
+                    commit,
+                    err
+                )
+            })?
+            .into_commit()
+            .ok_or_else(|| format_err!(ErrorKind::Repo, "'{}' is not a commit", commit))?
+            .tree();
+
+        db.try_find(tree_id, buf)
+            .map_err(|err| {
+                format_err!(
+                    ErrorKind::Repo,
+                    "failed to find tree '{}': {}",
+                    tree_id,
+                    err
+                )
+            })?
+            .and_then(|tree| tree.try_into_tree_iter())
+            .ok_or_else(|| format_err!(ErrorKind::Repo, "tree '{}' not present", tree_id))
+    }
+
+    /// Point `HEAD` directly at the given commit
+    fn detach_head(&self, commit: &Commit) -> Result<(), Error> {
+        use gix::refs::transaction as tx;
+
+        self.repo
+            .edit_reference(tx::RefEdit {
+                change: tx::Change::Update {
+                    log: tx::LogChange {
+                        mode: tx::RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: "".into(),
+                    },
+                    expected: tx::PreviousValue::Any,
+                    new: gix::refs::Target::Peeled(commit.commit_id.to_gix()),
+                },
+                name: "HEAD".try_into().unwrap(),
+                deref: false,
+            })
+            .map_err(|err| format_err!(ErrorKind::Repo, "failed to update HEAD: {}", err))?;
+
+        Ok(())
+    }
+
+    fn perform_fetch(repo: &mut gix::Repository, ref_specs: &[&str]) -> Result<(), Error> {
         let mut config = repo.config_snapshot_mut();
         config
             .set_raw_value("committer", None, "name", "rustsec")
@@ -284,7 +533,7 @@ impl Repository {
         })?;
 
         remote
-            .replace_refspecs(Some(REF_SPEC), DIR)
+            .replace_refspecs(ref_specs.iter().copied(), DIR)
             .expect("valid statically known refspec");
 
         // Perform the actual fetch
@@ -354,3 +603,33 @@ impl Repository {
         Ok(())
     }
 }
+
+/// Canonical form of a git URL, so that URLs of the same repository compare
+/// equal regardless of a trailing slash or `.git` suffix, and of case on GitHub
+/// (like the canonical URLs of Cargo)
+fn canonical_url(url: &str) -> String {
+    let mut canonical = match url::Url::parse(url) {
+        Ok(parsed) if parsed.host_str() == Some("github.com") => {
+            parsed.as_str().to_ascii_lowercase()
+        }
+        Ok(parsed) => parsed.to_string(),
+        Err(_) => url.to_owned(),
+    };
+
+    while canonical.ends_with('/') {
+        canonical.pop();
+    }
+    if let Some(stripped) = canonical.strip_suffix(".git") {
+        canonical.truncate(stripped.len());
+    }
+
+    canonical
+}
+
+/// Stable 64-bit FNV-1a hash of a URL, which unlike the hasher of the standard
+/// library is guaranteed not to change between Rust releases
+fn url_hash(url: &str) -> u64 {
+    url.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -2,7 +2,18 @@
 
 use crate::error::Error;
 
-/// Digital signatures (in OpenPGP format) on commits to the repository
+#[cfg(feature = "git")]
+use {
+    crate::error::ErrorKind,
+    ssh_key::{HashAlg, PublicKey, SshSig},
+    std::{fmt, str::FromStr},
+};
+
+/// Namespace git uses when creating SSH signatures
+#[cfg(feature = "git")]
+const SSH_NAMESPACE: &str = "git";
+
+/// Digital signatures (in OpenPGP or SSH format) on commits to the repository
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Signature(Vec<u8>);
 
@@ -12,6 +23,36 @@ impl Signature {
         // TODO: actually verify the signature is well-structured
         Ok(Signature(bytes.into()))
     }
+
+    /// Verify this is a signature over `signed_data` made by one of the
+    /// given trusted keys.
+    ///
+    /// Only SSH signatures can be verified: OpenPGP signatures are rejected
+    /// like signatures made by untrusted keys.
+    #[cfg(feature = "git")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "git")))]
+    pub fn verify(&self, signed_data: &[u8], trusted_keys: &[TrustedKey]) -> Result<(), Error> {
+        let signature = SshSig::from_pem(&self.0).map_err(|err| {
+            format_err!(
+                ErrorKind::Signature,
+                "unsupported or malformed signature: {}",
+                err
+            )
+        })?;
+
+        if trusted_keys
+            .iter()
+            .any(|key| key.0.verify(SSH_NAMESPACE, signed_data, &signature).is_ok())
+        {
+            return Ok(());
+        }
+
+        fail!(
+            ErrorKind::Signature,
+            "signature by {} is not valid for any trusted key",
+            signature.public_key().fingerprint(HashAlg::Sha256)
+        )
+    }
 }
 
 impl AsRef<[u8]> for Signature {
@@ -19,3 +60,39 @@ impl AsRef<[u8]> for Signature {
         self.0.as_ref()
     }
 }
+
+/// Public key trusted to sign commits to the repository.
+///
+/// Parsed from the OpenSSH public key format, i.e. a line of a
+/// `~/.ssh/id_ed25519.pub` file such as `ssh-ed25519 AAAAC3Nza... comment`.
+/// Ed25519 keys are supported.
+#[cfg(feature = "git")]
+#[cfg_attr(docsrs, doc(cfg(feature = "git")))]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrustedKey(PublicKey);
+
+#[cfg(feature = "git")]
+impl TrustedKey {
+    /// Get the SHA-256 fingerprint of this key, as displayed by `ssh-keygen -l`
+    pub fn fingerprint(&self) -> String {
+        self.0.fingerprint(HashAlg::Sha256).to_string()
+    }
+}
+
+#[cfg(feature = "git")]
+impl FromStr for TrustedKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        PublicKey::from_openssh(s.trim())
+            .map(TrustedKey)
+            .map_err(|err| format_err!(ErrorKind::Parse, "invalid SSH public key: {}", err))
+    }
+}
+
+#[cfg(feature = "git")]
+impl fmt::Display for TrustedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.to_openssh().map_err(|_| fmt::Error)?)
+    }
+}
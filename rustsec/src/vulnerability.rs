@@ -4,6 +4,7 @@
 use crate::{
     advisory::{self, affected::FunctionPath, Advisory},
     package::Package,
+    report,
 };
 use serde::{Deserialize, Serialize};
 
@@ -21,6 +22,32 @@ pub struct Vulnerability {
 
     /// Vulnerable package
     pub package: Package,
+
+    /// Severity of the advisory after applying the CVSS metrics of the report
+    /// settings, if any applied (see
+    /// [`Settings::effective_severity`](crate::report::Settings::effective_severity))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_severity: Option<advisory::Severity>,
+
+    /// Override of the severity of the advisory by organizational policy
+    /// which was applied to this vulnerability (see
+    /// [`Settings::severity_overrides`](crate::report::Settings::severity_overrides))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity_override: Option<report::SeverityOverride>,
+
+    /// Other advisories about the same CVE, which were collapsed into this
+    /// vulnerability (see [`Settings::collapse_cve_aliases`](crate::report::Settings::collapse_cve_aliases))
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<advisory::Id>,
+
+    /// Chains of dependencies through which the vulnerable package is pulled in,
+    /// each starting at a root package and ending at the vulnerable one.
+    ///
+    /// Only populated by [`Report::generate`](crate::Report::generate).
+    #[cfg(feature = "dependency-tree")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dependency-tree")))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<Vec<crate::package::Name>>,
 }
 
 impl Vulnerability {
@@ -31,6 +58,11 @@ impl Vulnerability {
             versions: advisory.versions.clone(),
             affected: advisory.affected.clone(),
             package: package.clone(),
+            effective_severity: None,
+            severity_override: None,
+            duplicates: vec![],
+            #[cfg(feature = "dependency-tree")]
+            paths: vec![],
         }
     }
 
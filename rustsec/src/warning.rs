@@ -61,6 +61,16 @@ impl Warning {
     pub fn is_yanked(&self) -> bool {
         self.kind == WarningKind::Yanked
     }
+
+    /// Is this a warning about an expired advisory ignore?
+    pub fn is_ignore_expired(&self) -> bool {
+        self.kind == WarningKind::IgnoreExpired
+    }
+
+    /// Is this a warning about a package which isn't from a registry?
+    pub fn is_source_not_registry(&self) -> bool {
+        self.kind == WarningKind::SourceNotRegistry
+    }
 }
 
 /// Kinds of warnings
@@ -82,6 +92,15 @@ pub enum WarningKind {
     /// Yanked packages
     #[serde(rename = "yanked")]
     Yanked,
+
+    /// Advisories which were ignored, but the ignore has expired
+    #[serde(rename = "ignore-expired")]
+    IgnoreExpired,
+
+    /// Packages from a source other than a registry (e.g. git), which can't
+    /// be checked for yanked releases and aren't covered by advisories
+    #[serde(rename = "source-not-registry")]
+    SourceNotRegistry,
 }
 
 impl WarningKind {
@@ -92,6 +111,8 @@ impl WarningKind {
             Self::Unmaintained => "unmaintained",
             Self::Unsound => "unsound",
             Self::Yanked => "yanked",
+            Self::IgnoreExpired => "ignore-expired",
+            Self::SourceNotRegistry => "source-not-registry",
         }
     }
 }
@@ -105,6 +126,8 @@ impl FromStr for WarningKind {
             "unmaintained" => WarningKind::Unmaintained,
             "unsound" => WarningKind::Unsound,
             "yanked" => WarningKind::Yanked,
+            "ignore-expired" => WarningKind::IgnoreExpired,
+            "source-not-registry" => WarningKind::SourceNotRegistry,
             other => fail!(ErrorKind::Parse, "invalid warning type: {}", other),
         })
     }
@@ -2,8 +2,8 @@
 
 #![warn(rust_2018_idioms, unused_qualifications)]
 
-use rustsec::advisory::{Category, License};
-use std::path::Path;
+use rustsec::advisory::{Category, IdKind, License, Reference, ReferenceKind};
+use std::{fs, path::Path};
 
 /// Load example advisory from the filesystem
 fn load_advisory(case: &str) -> rustsec::Advisory {
@@ -72,6 +72,65 @@ fn parse_aliases() {
     let alias = &load_advisory("v3").metadata.aliases[0];
     assert!(alias.is_cve());
     assert_eq!(alias.year().unwrap(), 2001);
+
+    let advisory = rustsec::Advisory::load_file(
+        "./tests/support/advisory-db/crates/other/RUSTSEC-2002-0001.md",
+    )
+    .unwrap();
+    let metadata = &advisory.metadata;
+
+    let cves: Vec<_> = metadata.cve_aliases().map(|id| id.as_str()).collect();
+    assert_eq!(cves, ["CVE-2002-1234"]);
+
+    let ghsas: Vec<_> = metadata.ghsa_aliases().map(|id| id.as_str()).collect();
+    assert_eq!(ghsas, ["GHSA-7p6r-xq2p-6mvh"]);
+
+    // Unknown kinds of IDs are kept as they are
+    let others: Vec<_> = metadata.aliases_of_kind(IdKind::Other).collect();
+    assert_eq!(others.len(), 1);
+    assert_eq!(others[0].to_string(), "OSV-2002-1");
+}
+
+/// URLs of GHSA and CVE aliases
+#[test]
+fn alias_urls() {
+    let advisory: rustsec::Advisory = r#"```toml
+[advisory]
+id = "RUSTSEC-2001-2102"
+package = "base"
+date = "2001-02-03"
+aliases = ["GHSA-7p6r-xq2p-6mvh", "CVE-2001-2102", "OSV-2001-1", "GHSA-4mmc-49vf-jmcp", "CVE-2001-2103"]
+
+[versions]
+patched = []
+```
+
+# Known by many names
+
+Details.
+"#
+    .parse()
+    .unwrap();
+
+    assert_eq!(
+        advisory.ghsa_urls(),
+        [
+            "https://github.com/advisories/GHSA-7p6r-xq2p-6mvh",
+            "https://github.com/advisories/GHSA-4mmc-49vf-jmcp"
+        ]
+    );
+    assert_eq!(
+        advisory.cve_urls(),
+        [
+            "https://cve.mitre.org/cgi-bin/cvename.cgi?name=CVE-2001-2102",
+            "https://cve.mitre.org/cgi-bin/cvename.cgi?name=CVE-2001-2103"
+        ]
+    );
+
+    // The base advisory only has a CVE alias
+    let advisory = load_advisory("v3");
+    assert!(advisory.ghsa_urls().is_empty());
+    assert_eq!(advisory.cve_urls().len(), 1);
 }
 
 /// Parsing of CVSS v3.1 severity vector strings
@@ -83,7 +142,9 @@ fn parse_cvss_vector_string() {
         rustsec::advisory::Severity::Critical
     );
 
-    let cvss = advisory.metadata.cvss.unwrap();
+    let Some(cvss::Cvss::V3(cvss)) = advisory.metadata.cvss else {
+        panic!("expected a CVSS v3 vector");
+    };
     assert_eq!(cvss.av.unwrap(), cvss::v3::base::AttackVector::Network);
     assert_eq!(cvss.ac.unwrap(), cvss::v3::base::AttackComplexity::Low);
     assert_eq!(cvss.pr.unwrap(), cvss::v3::base::PrivilegesRequired::None);
@@ -95,6 +156,55 @@ fn parse_cvss_vector_string() {
     assert_eq!(cvss.score().value(), 10.0);
 }
 
+/// Breaking CVSS v3 severity down into individual metrics and subscores
+#[test]
+fn cvss_v3_metrics() {
+    let advisory = load_advisory("v3");
+    assert!(advisory.metadata.cvss_v4().is_none());
+
+    let base = advisory.metadata.cvss_v3().unwrap();
+    assert_eq!(base.av, Some(cvss::v3::base::AttackVector::Network));
+    assert_eq!(base.pr, Some(cvss::v3::base::PrivilegesRequired::None));
+    assert_eq!(base.s, Some(cvss::v3::base::Scope::Changed));
+    assert_eq!(base.exploitability().roundup().value(), 3.9);
+    assert!((base.impact().value() - 0.9148).abs() < 1e-4);
+
+    // Advisories without a CVSS vector have no metrics
+    let advisory = rustsec::Advisory::load_file(
+        "./tests/support/advisory-db/crates/unclassified/RUSTSEC-2003-0001.md",
+    )
+    .unwrap();
+    assert!(advisory.metadata.cvss_v3().is_none());
+    assert!(advisory.metadata.cvss_v4().is_none());
+}
+
+/// Parsing of CVSS v4.0 severity vector strings
+#[test]
+fn parse_cvss_v4_vector_string() {
+    let vector = "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N/E:U";
+    let advisory: rustsec::Advisory = fs::read_to_string("./tests/support/example_advisory_v3.md")
+        .unwrap()
+        .replace("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H", vector)
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        advisory.severity().unwrap(),
+        rustsec::advisory::Severity::High
+    );
+
+    assert!(advisory.metadata.cvss_v3().is_none());
+    let metrics = advisory.metadata.cvss_v4().unwrap();
+    assert_eq!(metrics.get(cvss::v4::MetricType::AV), Some("N"));
+    assert_eq!(metrics.get(cvss::v4::MetricType::VC), Some("H"));
+    assert_eq!(metrics.get(cvss::v4::MetricType::SC), Some("N"));
+
+    let cvss = advisory.metadata.cvss.unwrap();
+    assert!(matches!(cvss, cvss::Cvss::V4(_)));
+    assert_eq!(cvss.to_string(), vector);
+    assert_eq!(cvss.score(), 8.1);
+}
+
 /// Parsing of patched version reqs
 #[test]
 fn parse_patched_version_reqs() {
@@ -104,3 +214,330 @@ fn parse_patched_version_reqs() {
     assert!(req.matches(&"1.2.3".parse().unwrap()));
     assert!(req.matches(&"1.2.4".parse().unwrap()));
 }
+
+/// Evaluating patched and unaffected version ranges
+#[test]
+fn affected_versions() {
+    let req = |s: &str| s.parse::<rustsec::VersionReq>().unwrap();
+    let versions = rustsec::advisory::Versions::new(
+        vec![req(">= 2.0.0"), req("^1.5.2")],
+        vec![req("< 1.0.0")],
+    )
+    .unwrap();
+
+    for (version, affected) in [
+        ("0.9.9", false),
+        ("1.0.0-alpha", false),
+        ("1.0.0", true),
+        ("1.5.1", true),
+        ("1.5.2", false),
+        ("1.9.0", false),
+        ("2.0.0-beta", true),
+        ("2.0.0", false),
+        ("2.1.0-rc.1", false),
+    ] {
+        let parsed = version.parse().unwrap();
+        assert_eq!(versions.is_affected(&parsed), affected, "{version}");
+        assert_eq!(versions.is_vulnerable(&parsed), affected, "{version}");
+        assert_eq!(versions.is_affected_str(version).unwrap(), affected);
+    }
+
+    // Versions which aren't valid semver can't be evaluated
+    let err = versions.is_affected_str("1.0.0-").unwrap_err();
+    assert_eq!(err.kind(), rustsec::ErrorKind::Version);
+    assert!(err.to_string().contains("1.0.0-"));
+
+    // Overlapping patched and unaffected ranges are rejected
+    let err =
+        rustsec::advisory::Versions::new(vec![req(">= 1.2.3")], vec![req("^1.5")]).unwrap_err();
+    assert_eq!(err.kind(), rustsec::ErrorKind::BadParam);
+}
+
+/// Evaluating pre-release versions against patched ranges, with and without
+/// including pre-releases in the ranges
+#[test]
+fn prerelease_versions() {
+    let req = |s: &str| s.parse::<rustsec::VersionReq>().unwrap();
+    let without_prerelease_bound =
+        rustsec::advisory::Versions::new(vec![req(">= 2.0.0"), req("^1.5.2")], vec![]).unwrap();
+    let with_prerelease_bound =
+        rustsec::advisory::Versions::new(vec![req(">= 2.0.0-rc.2")], vec![]).unwrap();
+
+    // (version, affected with pre-releases included, affected without)
+    for (version, included, excluded) in [
+        ("1.5.3-alpha", false, true),
+        ("2.0.0-rc.1", true, true),
+        ("2.0.0", false, false),
+        ("2.1.0-rc.1", false, true),
+    ] {
+        let parsed = version.parse().unwrap();
+        assert_eq!(
+            without_prerelease_bound.is_affected_with_prereleases(&parsed, true),
+            included,
+            "{version}"
+        );
+        assert_eq!(
+            without_prerelease_bound.is_affected_with_prereleases(&parsed, false),
+            excluded,
+            "{version}"
+        );
+        assert_eq!(without_prerelease_bound.is_affected(&parsed), included);
+    }
+
+    for (version, included, excluded) in [
+        ("2.0.0-rc.1", true, true),
+        ("2.0.0-rc.2", false, false),
+        ("2.0.0-rc.10", false, false),
+        ("2.0.0", false, false),
+        ("2.1.0-rc.1", false, true),
+    ] {
+        let parsed = version.parse().unwrap();
+        assert_eq!(
+            with_prerelease_bound.is_affected_with_prereleases(&parsed, true),
+            included,
+            "{version}"
+        );
+        assert_eq!(
+            with_prerelease_bound.is_affected_with_prereleases(&parsed, false),
+            excluded,
+            "{version}"
+        );
+    }
+}
+
+/// Withdrawn advisories survive a round trip through TOML
+#[test]
+fn parse_withdrawn() {
+    let advisory = rustsec::Advisory::load_file(
+        "./tests/support/advisory-db/crates/retracted/RUSTSEC-2004-0001.md",
+    )
+    .unwrap();
+    assert_eq!(
+        advisory.metadata.withdrawn.as_ref().unwrap().as_str(),
+        "2004-02-01"
+    );
+
+    let toml = toml::to_string(&advisory.metadata).unwrap();
+    let metadata: rustsec::advisory::Metadata = toml::from_str(&toml).unwrap();
+    assert_eq!(metadata, advisory.metadata);
+
+    assert!(load_advisory("v3").metadata.withdrawn.is_none());
+}
+
+/// Related advisories are kept apart from aliases and survive a round trip
+/// through TOML
+#[test]
+fn parse_related() {
+    let advisory: rustsec::Advisory = r#"```toml
+[advisory]
+id = "RUSTSEC-2001-2102"
+package = "base"
+date = "2001-02-03"
+aliases = ["CVE-2001-2102"]
+related = ["RUSTSEC-2001-2101", "CVE-2001-2101", "GHSA-7p6r-xq2p-6mvh"]
+
+[versions]
+patched = []
+```
+
+# Related to the base advisory
+
+Details.
+"#
+    .parse()
+    .unwrap();
+
+    let related: Vec<_> = advisory
+        .metadata
+        .related
+        .iter()
+        .map(|id| (id.as_str(), id.kind()))
+        .collect();
+    assert_eq!(
+        related,
+        [
+            ("RUSTSEC-2001-2101", IdKind::RustSec),
+            ("CVE-2001-2101", IdKind::Cve),
+            ("GHSA-7p6r-xq2p-6mvh", IdKind::Ghsa)
+        ]
+    );
+    assert_eq!(advisory.metadata.aliases.len(), 1);
+
+    let toml = toml::to_string(&advisory.metadata).unwrap();
+    let metadata: rustsec::advisory::Metadata = toml::from_str(&toml).unwrap();
+    assert_eq!(metadata, advisory.metadata);
+
+    assert!(load_advisory("v3").metadata.related.is_empty());
+}
+
+/// The TOML front matter is only retained on request
+#[test]
+fn raw_toml() {
+    let front_matter = "[advisory]\n# Reported by email\nid   = \"RUSTSEC-2001-2101\"\npackage = 'base'\ndate = \"2001-02-03\" # approximate\n\n[versions]\npatched = [\n    \">= 1.2.3\",\n]";
+    let advisory_data = format!("```toml\n{front_matter}\n```\n\n# Title\n\nDescription.\n");
+
+    let advisory: rustsec::Advisory = advisory_data.parse().unwrap();
+    assert_eq!(advisory.raw_toml(), None);
+
+    let advisory = rustsec::Advisory::parse_with_raw_toml(&advisory_data).unwrap();
+    assert_eq!(advisory.raw_toml(), Some(front_matter));
+    assert_eq!(advisory.id().as_str(), "RUSTSEC-2001-2101");
+
+    let advisory = rustsec::Advisory::load_file_with_raw_toml(Path::new(
+        "./tests/support/example_advisory_v4.md",
+    ))
+    .unwrap();
+    assert!(advisory
+        .raw_toml()
+        .unwrap()
+        .starts_with("id = \"RUSTSEC-2001-2101\"\npackage = \"base\"\n"));
+}
+
+#[test]
+fn classify_versions() {
+    use rustsec::advisory::VersionStatus::{Affected, Patched, Unaffected};
+
+    // Patched in `>= 1.2.3`, with `0.1.2` unaffected
+    let advisory = load_advisory("v4");
+    let versions: Vec<rustsec::Version> = ["0.1.1", "0.1.2", "1.2.2", "1.2.3", "2.0.0-rc.1"]
+        .iter()
+        .map(|version| version.parse().unwrap())
+        .collect();
+
+    let statuses: Vec<_> = advisory
+        .classify_versions(&versions)
+        .into_iter()
+        .map(|(version, status)| (version.to_string(), status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("0.1.1".to_owned(), Affected),
+            ("0.1.2".to_owned(), Unaffected),
+            ("1.2.2".to_owned(), Affected),
+            ("1.2.3".to_owned(), Patched),
+            ("2.0.0-rc.1".to_owned(), Patched),
+        ]
+    );
+}
+
+/// References are plain URLs, or tables giving their kind explicitly
+#[test]
+fn parse_references() {
+    let advisory: rustsec::Advisory = "```toml\n[advisory]\nid = \"RUSTSEC-2001-2101\"\npackage = \"base\"\ndate = \"2001-02-03\"\nreferences = [\n    \"https://github.com/example/base/issues/1\",\n    \"https://example.com/writeup\",\n    { type = \"FIX\", url = \"https://github.com/example/base/pull/2\" },\n]\n\n[versions]\npatched = [\">= 1.2.3\"]\n```\n\n# Title\n\nDescription.\n"
+        .parse()
+        .unwrap();
+
+    let kinds: Vec<_> = advisory
+        .metadata
+        .references
+        .iter()
+        .map(|reference| reference.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ReferenceKind::Report,
+            ReferenceKind::Web,
+            ReferenceKind::Fix
+        ]
+    );
+
+    // Only references of an unexpected kind are serialized as tables
+    let json = serde_json::to_value(&advisory.metadata.references).unwrap();
+    assert_eq!(json[1], "https://example.com/writeup");
+    assert_eq!(json[2]["type"], "FIX");
+    let references: Vec<Reference> = serde_json::from_value(json).unwrap();
+    assert_eq!(references, advisory.metadata.references);
+
+    // The `url` of an advisory comes first among all of its references
+    let advisory = load_advisory("v4");
+    let references: Vec<_> = advisory.metadata.all_references().collect();
+    assert_eq!(
+        references,
+        [Reference::new(
+            ReferenceKind::Web,
+            "https://www.youtube.com/watch?v=jQE66WA2s-A"
+                .parse()
+                .unwrap()
+        )]
+    );
+
+    // Unknown kinds are rejected
+    let invalid = "```toml\n[advisory]\nid = \"RUSTSEC-2001-2101\"\npackage = \"base\"\ndate = \"2001-02-03\"\nreferences = [{ type = \"BLOG\", url = \"https://example.com\" }]\n\n[versions]\npatched = []\n```\n\n# Title\n";
+    assert!(invalid.parse::<rustsec::Advisory>().is_err());
+}
+
+#[test]
+fn minimal_compatible_fix() {
+    let req = |s: &str| s.parse::<rustsec::VersionReq>().unwrap();
+    let version = |s: &str| s.parse::<rustsec::Version>().unwrap();
+    let available = |versions: &[&str]| versions.iter().map(|v| version(v)).collect::<Vec<_>>();
+
+    // Patched in a compatible version as well as in the next major version
+    let versions =
+        rustsec::advisory::Versions::new(vec![req(">= 2.0.0"), req("^1.5.2")], vec![]).unwrap();
+    let published = available(&["1.4.0", "1.5.3-alpha", "1.6.0", "1.5.2", "2.0.0"]);
+    assert_eq!(
+        versions.minimal_compatible_fix(&version("1.2.3"), &published),
+        Some(version("1.5.2"))
+    );
+    assert_eq!(
+        versions.minimal_compatible_fix(&version("1.6.0"), &published),
+        Some(version("1.6.0"))
+    );
+    assert_eq!(
+        versions.minimal_compatible_fix(&version("2.0.0-rc.1"), &published),
+        Some(version("2.0.0"))
+    );
+
+    // Only fixed in the next major version
+    let versions = rustsec::advisory::Versions::new(vec![req(">= 2.0.0")], vec![]).unwrap();
+    assert_eq!(
+        versions.minimal_compatible_fix(&version("1.2.3"), &published),
+        None
+    );
+
+    // Minor versions are breaking before 1.0.0
+    let versions = rustsec::advisory::Versions::new(vec![req(">= 0.4.2")], vec![]).unwrap();
+    let published = available(&["0.3.9", "0.4.2", "0.5.0"]);
+    assert_eq!(
+        versions.minimal_compatible_fix(&version("0.3.1"), &published),
+        None
+    );
+    assert_eq!(
+        versions.minimal_compatible_fix(&version("0.4.0"), &published),
+        Some(version("0.4.2"))
+    );
+}
+
+/// Keys which aren't part of the advisory format don't prevent loading
+#[test]
+fn unknown_keys() {
+    let advisory: rustsec::Advisory = "```toml\n[advisory]\nid = \"RUSTSEC-2001-2101\"\npackage = \"base\"\ndate = \"2001-02-03\"\nlicense = \"CC-BY-4.0\"\nreviewed = true\n\n[advisory.provenance]\nsource = \"import\"\n\n[versions]\npatched = [\">= 1.2.3\"]\n```\n\n# Title\n\nDescription.\n"
+        .parse()
+        .unwrap();
+
+    assert_eq!(advisory.metadata.license, License::CcBy40);
+    let keys: Vec<_> = advisory.metadata.extra.keys().collect();
+    assert_eq!(keys, ["provenance", "reviewed"]);
+    assert_eq!(
+        advisory.metadata.extra["reviewed"],
+        toml::Value::Boolean(true)
+    );
+    assert_eq!(
+        advisory.metadata.extra["provenance"]["source"].as_str(),
+        Some("import")
+    );
+
+    // Advisories without unknown keys have none
+    assert!(load_advisory("v4").metadata.extra.is_empty());
+
+    // Non-finite floats are rejected, as NaN would make the advisory unequal to itself
+    for value in ["nan", "[1.0, -inf]", "{ score = nan }"] {
+        let err = format!("```toml\n[advisory]\nid = \"RUSTSEC-2001-2101\"\npackage = \"base\"\ndate = \"2001-02-03\"\nscore = {value}\n\n[versions]\npatched = []\n```\n\n# Title\n\nDescription.\n")
+            .parse::<rustsec::Advisory>()
+            .unwrap_err();
+        assert!(err.to_string().contains("non-finite float"), "{err}");
+    }
+}
@@ -0,0 +1,210 @@
+//! Tests for looking up yanked crates in an alternate registry index
+//!
+//! These live in their own test binary because they point `CARGO_HOME`
+//! at a temporary directory for the whole process.
+#![cfg(feature = "sparse-index")]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use once_cell::sync::Lazy;
+use rustsec::{
+    registry::{CachedIndex, IndexKind},
+    ErrorKind, Lockfile,
+};
+use std::time::Duration;
+use support::{create_git_index, CARGO_HOME};
+
+#[cfg(feature = "git-index")]
+use rustsec::registry::{IndexSnapshot, ProgressEvent};
+#[cfg(feature = "git-index")]
+use std::sync::{Arc, Mutex};
+
+mod support;
+
+const REGISTRY_URL: &str = "sparse+https://registry.example.com/index/";
+
+const LOCKFILE: &str = r#"
+version = 3
+
+[[package]]
+name = "base64"
+version = "0.5.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "internal-crate"
+version = "1.0.0"
+source = "sparse+https://registry.example.com/index/"
+"#;
+
+#[test]
+fn open_alternate_sparse_registry() {
+    Lazy::force(&CARGO_HOME);
+
+    let lockfile: Lockfile = LOCKFILE.parse().unwrap();
+    let mut index = CachedIndex::open_with_url(REGISTRY_URL, Duration::from_secs(0)).unwrap();
+
+    // `base64` comes from crates.io, so it must be skipped instead of reported as missing
+    let results = index.find_yanked(&lockfile.packages);
+    assert_eq!(results.len(), 1);
+
+    let err = results.into_iter().next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert!(err.to_string().contains("internal-crate"));
+
+    // In strict offline mode the miss in the local sparse cache is reported as such
+    index.set_offline_strict(true);
+    let results = index.find_yanked(&lockfile.packages);
+    assert_eq!(results.len(), 1);
+
+    let err = results.into_iter().next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotCached);
+
+    let name = "internal-crate".parse().unwrap();
+    assert_eq!(
+        index.versions(&name).unwrap_err().kind(),
+        ErrorKind::NotCached
+    );
+}
+
+#[test]
+fn invalid_registry_url() {
+    let err = CachedIndex::open_with_url("not a url", Duration::from_secs(0))
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), ErrorKind::BadParam);
+}
+
+#[test]
+fn index_kind() {
+    Lazy::force(&CARGO_HOME);
+
+    let index = CachedIndex::open_with_url(REGISTRY_URL, Duration::from_secs(0)).unwrap();
+    assert_eq!(index.kind(), IndexKind::SparseCached);
+
+    // Sparse indices aren't contacted until crates are looked up
+    let cache_dir = tempfile::tempdir().unwrap();
+    let index = CachedIndex::builder()
+        .url(REGISTRY_URL)
+        .cache_dir(cache_dir.path())
+        .fetch(Duration::from_secs(0))
+        .unwrap();
+    assert_eq!(index.kind(), IndexKind::SparseRemote);
+}
+
+#[test]
+fn git_index() {
+    Lazy::force(&CARGO_HOME);
+
+    let index_dir = tempfile::tempdir().unwrap();
+    let url = create_git_index(index_dir.path(), &[]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let result = CachedIndex::builder()
+        .url(&url)
+        .cache_dir(cache_dir.path())
+        .fetch(Duration::from_secs(0));
+
+    #[cfg(feature = "git-index")]
+    assert_eq!(result.unwrap().kind(), IndexKind::Git);
+
+    // Without git support, git indices are rejected rather than misread
+    #[cfg(not(feature = "git-index"))]
+    {
+        let err = result.err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::Registry);
+        assert!(err.to_string().contains("`git-index` feature"));
+
+        let err = CachedIndex::open_with_url(&url, Duration::from_secs(0))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::Registry);
+    }
+}
+
+#[cfg(feature = "git-index")]
+#[test]
+fn git_fetch_progress() {
+    Lazy::force(&CARGO_HOME);
+
+    let index_dir = tempfile::tempdir().unwrap();
+    let url = create_git_index(index_dir.path(), &[]);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let index = CachedIndex::builder()
+        .url(&url)
+        .cache_dir(cache_dir.path())
+        .progress(move |event: &ProgressEvent| sink.lock().unwrap().push(event.clone()))
+        .fetch(Duration::from_secs(0))
+        .unwrap();
+
+    assert_eq!(index.kind(), IndexKind::Git);
+    assert!(!events.lock().unwrap().is_empty());
+}
+
+/// Lookups pinned to a commit of a git index keep giving the same answers
+/// after later fetches update the index
+#[cfg(feature = "git-index")]
+#[test]
+fn git_index_pinned_to_commit() {
+    Lazy::force(&CARGO_HOME);
+
+    let index_dir = tempfile::tempdir().unwrap();
+    let url = create_git_index(index_dir.path(), &[]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let name = "pinned".parse().unwrap();
+
+    let publish = |yanked: bool| {
+        support::write_index_crate(index_dir.path(), "pinned", &[("1.0.0", yanked)]);
+        support::git(index_dir.path(), &["add", "."]);
+        support::git(index_dir.path(), &["commit", "-q", "-m", "Publish"]);
+    };
+
+    publish(false);
+    let index = CachedIndex::builder()
+        .url(&url)
+        .cache_dir(cache_dir.path())
+        .fetch(Duration::from_secs(0))
+        .unwrap();
+    assert!(!index.is_version_yanked(&name, "1.0.0").unwrap());
+    let commit = index.commit().unwrap();
+    drop(index);
+
+    publish(true);
+    let index = CachedIndex::builder()
+        .url(&url)
+        .cache_dir(cache_dir.path())
+        .fetch(Duration::from_secs(0))
+        .unwrap();
+    assert!(index.is_version_yanked(&name, "1.0.0").unwrap());
+    assert_ne!(index.commit().unwrap(), commit);
+    drop(index);
+
+    let snapshot = IndexSnapshot::GitCommit(commit.clone());
+    let pinned = CachedIndex::open_pinned(
+        Some(&url),
+        Some(cache_dir.path()),
+        &snapshot,
+        Duration::from_secs(0),
+    )
+    .unwrap();
+    assert_eq!(pinned.kind(), IndexKind::Git);
+    assert_eq!(pinned.commit(), Some(commit));
+    assert!(!pinned.is_version_yanked(&name, "1.0.0").unwrap());
+    assert!(pinned
+        .versions(&"missing".parse().unwrap())
+        .unwrap()
+        .is_none());
+
+    // Commits which were never fetched can't be pinned to
+    let unknown = IndexSnapshot::GitCommit("0".repeat(40));
+    let err = CachedIndex::open_pinned(
+        Some(&url),
+        Some(cache_dir.path()),
+        &unknown,
+        Duration::from_secs(0),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
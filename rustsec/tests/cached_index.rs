@@ -0,0 +1,1009 @@
+//! Tests for looking up yanked crates in the crates.io index
+#![cfg(feature = "sparse-index")]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use rustsec::{
+    package,
+    registry::{CachedIndex, FetchOutcome, IndexKind, IndexSnapshot},
+    ErrorKind, Lockfile,
+};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn cached_index_is_send_and_sync() {
+    assert_send_sync::<CachedIndex>();
+}
+
+/// Query overlapping sets of packages from several threads at once
+/// (has online dependency on crates.io)
+#[test]
+fn find_yanked_concurrently() {
+    const THREADS: usize = 8;
+
+    let lockfile = Lockfile::load(Path::new("./tests/support/cratesio_cargo.lock")).unwrap();
+    let index = CachedIndex::fetch(None, LOCK_TIMEOUT).unwrap();
+
+    let summarize = |index: &CachedIndex, skip: usize| {
+        // Every thread starts at a different package, but covers all of them
+        let packages = lockfile.packages.iter().cycle().skip(skip);
+
+        index
+            .find_yanked(packages.take(lockfile.packages.len()))
+            .into_iter()
+            .map(|res| res.map(|pkg| pkg.name.to_string()).map_err(|e| e.kind()))
+            .collect::<Vec<_>>()
+    };
+
+    let results: Vec<Vec<Result<String, ErrorKind>>> = thread::scope(|s| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|n| {
+                let index = &index;
+                s.spawn(move || summarize(index, n))
+            })
+            .collect();
+
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    for result in &results[1..] {
+        assert_eq!(result, &results[0]);
+    }
+}
+
+/// Check for yanked crates from within an existing Tokio runtime
+/// (has online dependency on crates.io)
+#[tokio::test]
+async fn find_yanked_async_in_runtime() {
+    let lockfile = Lockfile::load(Path::new("./tests/support/cratesio_cargo.lock")).unwrap();
+    let index = CachedIndex::fetch(None, LOCK_TIMEOUT).unwrap();
+
+    let yanked_async: Vec<_> = index
+        .find_yanked_async(&lockfile.packages)
+        .await
+        .into_iter()
+        .map(|res| res.map(|pkg| pkg.name.to_string()).map_err(|e| e.kind()))
+        .collect();
+
+    // Results are cached, so the blocking variant must agree without a network round trip
+    let yanked_blocking: Vec<_> = index
+        .find_yanked(&lockfile.packages)
+        .into_iter()
+        .map(|res| res.map(|pkg| pkg.name.to_string()).map_err(|e| e.kind()))
+        .collect();
+
+    assert_eq!(yanked_async, yanked_blocking);
+
+    for package in &lockfile.packages {
+        let yanked = matches!(index.is_yanked_async(package).await, Ok(true));
+        assert_eq!(yanked, yanked_async.contains(&Ok(package.name.to_string())));
+    }
+}
+
+/// List all published versions of a crate (has online dependency on crates.io)
+#[test]
+fn versions() {
+    let index = CachedIndex::fetch(None, LOCK_TIMEOUT).unwrap();
+
+    let versions = index.versions(&"base64".parse().unwrap()).unwrap().unwrap();
+    assert!(versions.contains_key("0.5.1"));
+    assert!(versions.contains_key("0.21.0"));
+
+    let missing = index.versions(&"this-crate-does-not-exist-rustsec".parse().unwrap());
+    assert!(missing.unwrap().is_none());
+}
+
+/// Yank status saved to disk is used instead of querying the index again
+/// Sparse registry which nothing listens on, so that any request to the index
+/// itself fails, and lookups can only be answered from a seeded cache
+const UNREACHABLE_REGISTRY_URL: &str = "sparse+http://127.0.0.1:1/index/";
+
+/// Current time in seconds since the UNIX epoch, as recorded in cache files
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Write a cache file of the unreachable registry with the given crate entries
+fn write_cache(path: &Path, crates: &str) {
+    fs::write(
+        path,
+        format!("version = 1\nsource = \"{UNREACHABLE_REGISTRY_URL}\"\n{crates}"),
+    )
+    .unwrap();
+}
+
+/// Index of the unreachable registry, kept in the given directory
+fn unreachable_index(cache_dir: &Path) -> CachedIndex {
+    CachedIndex::builder()
+        .url(UNREACHABLE_REGISTRY_URL)
+        .cache_dir(cache_dir)
+        .fetch(LOCK_TIMEOUT)
+        .unwrap()
+}
+
+/// Index of the unreachable registry, kept in the given directory, with the
+/// given crate entries loaded from a cache file
+fn seeded_index(cache_dir: &Path, crates: &str) -> CachedIndex {
+    let cache_path = cache_dir.join("cache.toml");
+    write_cache(&cache_path, crates);
+
+    let index = unreachable_index(cache_dir);
+    index
+        .load_cache(&cache_path, Duration::from_secs(60 * 60))
+        .unwrap();
+    index
+}
+
+#[test]
+fn save_and_load_cache() {
+    const TTL: Duration = Duration::from_secs(60 * 60);
+
+    let lockfile: Lockfile = format!(
+        r#"
+version = 3
+
+[[package]]
+name = "fresh"
+version = "1.0.0"
+source = "{UNREACHABLE_REGISTRY_URL}"
+
+[[package]]
+name = "expired"
+version = "1.0.0"
+source = "{UNREACHABLE_REGISTRY_URL}"
+"#
+    )
+    .parse()
+    .unwrap();
+
+    let now = unix_now();
+
+    let dir = tempfile::tempdir().unwrap();
+    let seed_path = dir.path().join("seed.toml");
+    write_cache(
+        &seed_path,
+        &format!(
+            r#"
+[crates.fresh]
+fetched_at = {now}
+
+[crates.fresh.versions]
+"1.0.0" = true
+
+[crates.expired]
+fetched_at = 0
+
+[crates.expired.versions]
+"1.0.0" = true
+"#
+        ),
+    );
+
+    let index = unreachable_index(dir.path());
+    index.load_cache(&seed_path, TTL).unwrap();
+
+    let saved_path = dir.path().join("saved.toml");
+    index.save_cache(&saved_path).unwrap();
+
+    let index = unreachable_index(dir.path());
+    index.load_cache(&saved_path, TTL).unwrap();
+
+    let results = index.find_yanked(&lockfile.packages);
+    let fresh = results
+        .iter()
+        .find_map(|res| res.as_ref().ok())
+        .expect("cached crate is reported as yanked");
+    assert_eq!(fresh.name.as_str(), "fresh");
+
+    // The expired entry was discarded, so it had to be looked up in the (unreachable) index
+    assert!(results.iter().any(|res| res.is_err()));
+}
+
+/// Yanked packages are reported in lockfile order, each only once
+#[test]
+fn find_yanked_ordered() {
+    let lockfile: Lockfile = format!(
+        r#"
+version = 3
+
+[[package]]
+name = "zeta"
+version = "1.0.0"
+source = "{UNREACHABLE_REGISTRY_URL}"
+
+[[package]]
+name = "alpha"
+version = "1.0.0"
+source = "{UNREACHABLE_REGISTRY_URL}"
+
+[[package]]
+name = "middle"
+version = "1.0.0"
+source = "{UNREACHABLE_REGISTRY_URL}"
+"#
+    )
+    .parse()
+    .unwrap();
+
+    let now = unix_now();
+
+    let dir = tempfile::tempdir().unwrap();
+    let index = seeded_index(
+        dir.path(),
+        &format!(
+            r#"
+[crates.zeta]
+fetched_at = {now}
+versions = {{ "1.0.0" = true }}
+
+[crates.alpha]
+fetched_at = {now}
+versions = {{ "1.0.0" = true }}
+
+[crates.middle]
+fetched_at = {now}
+versions = {{ "1.0.0" = false }}
+"#
+        ),
+    );
+
+    // Every package is passed twice, to check deduplication
+    let packages = lockfile.packages.iter().chain(&lockfile.packages);
+    let names = |results: Vec<Result<&rustsec::package::Package, rustsec::Error>>| {
+        results
+            .into_iter()
+            .map(|res| res.unwrap().name.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(index.find_yanked_ordered(packages.clone())),
+        ["zeta", "alpha"]
+    );
+    assert_eq!(names(index.find_yanked(packages)), ["alpha", "zeta"]);
+}
+
+/// Versions are looked up by their string representation
+#[test]
+fn is_version_yanked() {
+    let now = unix_now();
+
+    let dir = tempfile::tempdir().unwrap();
+    let index = seeded_index(
+        dir.path(),
+        &format!(
+            r#"
+[crates.alpha]
+fetched_at = {now}
+versions = {{ "1.0.0" = true, "1.0.1" = false }}
+
+[crates.deleted]
+fetched_at = {now}
+versions = {{}}
+"#
+        ),
+    );
+
+    let name = "alpha".parse().unwrap();
+    assert!(index.is_version_yanked(&name, "1.0.0").unwrap());
+    assert!(!index.is_version_yanked(&name, "1.0.1").unwrap());
+    assert_eq!(
+        index.is_version_yanked(&name, "2.0.0").unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+
+    // A crate whose versions were all deleted is told apart from a missing version
+    let name = "deleted".parse().unwrap();
+    let err = index.is_version_yanked(&name, "1.0.0").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert!(err
+        .to_string()
+        .contains("deleted has no published versions"));
+}
+
+/// Only the yanked status of the highest version counts
+#[test]
+fn is_latest_yanked() {
+    let now = unix_now();
+
+    let dir = tempfile::tempdir().unwrap();
+    let index = seeded_index(
+        dir.path(),
+        &format!(
+            r#"
+[crates.latest-yanked]
+fetched_at = {now}
+versions = {{ "1.0.0" = false, "1.10.0" = true, "1.9.0" = false, "2.0.0-rc.1" = false, "latest" = false }}
+
+[crates.older-yanked]
+fetched_at = {now}
+versions = {{ "1.0.0" = true, "1.0.1" = false }}
+
+[crates.prerelease]
+fetched_at = {now}
+versions = {{ "0.1.0-alpha.1" = false, "0.1.0-alpha.2" = true }}
+
+[crates.invalid]
+fetched_at = {now}
+versions = {{ "latest" = false }}
+
+[crates.deleted]
+fetched_at = {now}
+versions = {{}}
+"#
+        ),
+    );
+    let is_latest_yanked = |name: &str| index.is_latest_yanked(&name.parse().unwrap());
+
+    // Versions are compared as semver rather than strings, and pre-releases
+    // only count if there are no releases
+    assert!(is_latest_yanked("latest-yanked").unwrap());
+    assert!(!is_latest_yanked("older-yanked").unwrap());
+    assert!(is_latest_yanked("prerelease").unwrap());
+
+    let err = is_latest_yanked("invalid").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Version);
+    assert_eq!(
+        is_latest_yanked("deleted").unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+}
+
+/// Lenient lookups fall back to the name with hyphens and underscores swapped
+#[test]
+fn versions_lenient() {
+    let now = unix_now();
+
+    let dir = tempfile::tempdir().unwrap();
+    let index = seeded_index(
+        dir.path(),
+        &format!(
+            r#"
+[crates.hyphen-name]
+fetched_at = {now}
+versions = {{ "1.0.0" = false }}
+
+[crates.hyphen_name]
+fetched_at = {now}
+
+[crates.underscore_name]
+fetched_at = {now}
+versions = {{ "0.1.0" = true }}
+
+[crates.underscore-name]
+fetched_at = {now}
+
+[crates.missing-name]
+fetched_at = {now}
+
+[crates.missing_name]
+fetched_at = {now}
+"#
+        ),
+    );
+    let lookup = |name: &str| index.versions_lenient(&name.parse().unwrap()).unwrap();
+
+    // Exact names aren't flagged
+    let found = lookup("hyphen-name").unwrap();
+    assert_eq!(found.name.as_str(), "hyphen-name");
+    assert!(!found.fuzzy);
+
+    // Both forms resolve to the crate in the index
+    let found = lookup("hyphen_name").unwrap();
+    assert_eq!(found.name.as_str(), "hyphen-name");
+    assert!(found.fuzzy);
+    assert_eq!(found.versions.get("1.0.0"), Some(&false));
+
+    let found = lookup("underscore-name").unwrap();
+    assert_eq!(found.name.as_str(), "underscore_name");
+    assert!(found.fuzzy);
+    assert_eq!(found.versions.get("0.1.0"), Some(&true));
+
+    assert!(lookup("missing-name").is_none());
+
+    // Strict lookups are unaffected
+    assert!(index
+        .versions(&"hyphen_name".parse().unwrap())
+        .unwrap()
+        .is_none());
+}
+
+/// Packages are sorted into yanked, not yanked and unknown
+#[test]
+fn find_yanked_detailed() {
+    let lockfile: Lockfile = format!(
+        r#"
+version = 3
+
+[[package]]
+name = "alpha"
+version = "1.0.0"
+source = "{UNREACHABLE_REGISTRY_URL}"
+
+[[package]]
+name = "alpha"
+version = "2.0.0"
+source = "{UNREACHABLE_REGISTRY_URL}"
+
+[[package]]
+name = "middle"
+version = "1.0.0"
+source = "{UNREACHABLE_REGISTRY_URL}"
+
+[[package]]
+name = "base64"
+version = "0.5.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#
+    )
+    .parse()
+    .unwrap();
+
+    let now = unix_now();
+
+    let dir = tempfile::tempdir().unwrap();
+    let index = seeded_index(
+        dir.path(),
+        &format!(
+            r#"
+[crates.alpha]
+fetched_at = {now}
+versions = {{ "1.0.0" = true }}
+
+[crates.middle]
+fetched_at = {now}
+versions = {{ "1.0.0" = false }}
+"#
+        ),
+    );
+
+    let report = index.find_yanked_detailed(&lockfile.packages);
+    assert!(report.index_error.is_none());
+
+    let describe = |packages: &[&rustsec::package::Package]| {
+        packages
+            .iter()
+            .map(|pkg| format!("{} {}", pkg.name, pkg.version))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(describe(&report.yanked), ["alpha 1.0.0"]);
+    assert_eq!(describe(&report.not_yanked), ["middle 1.0.0"]);
+
+    // The version missing from the index can't be checked, while the package
+    // from crates.io isn't published to this registry at all
+    assert_eq!(report.unknown.len(), 1);
+    let (package, error) = &report.unknown[0];
+    assert_eq!(package.version.to_string(), "2.0.0");
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+}
+
+/// Sparse index served from a local port, which fails every request until
+/// it's told to be `available`. Every crate has a single yanked version
+/// `1.0.0`, except for crates named `missing`, which don't exist, and crates
+/// named `unresponsive`, whose requests are never answered.
+struct LocalRegistry {
+    url: String,
+    available: Arc<AtomicBool>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl LocalRegistry {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let registry = Self {
+            url: format!("sparse+http://{}/index/", listener.local_addr().unwrap()),
+            available: Arc::new(AtomicBool::new(false)),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        };
+
+        let available = Arc::clone(&registry.available);
+        let requests = Arc::clone(&registry.requests);
+        thread::spawn(move || {
+            // Connections of unanswered requests are kept open until the registry stops
+            let mut unanswered = Vec::new();
+
+            for stream in listener.incoming() {
+                let stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).unwrap();
+                // Skip the headers
+                for line in reader.by_ref().lines() {
+                    if line.unwrap().is_empty() {
+                        break;
+                    }
+                }
+
+                let path = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_owned();
+                let name = path.rsplit('/').next().unwrap_or_default().to_owned();
+                requests.lock().unwrap().push(path);
+
+                if name == "unresponsive" {
+                    unanswered.push(stream);
+                    continue;
+                }
+
+                let (status, body) = if !available.load(Ordering::SeqCst) {
+                    ("500 Internal Server Error", String::new())
+                } else if name == "missing" {
+                    ("404 Not Found", String::new())
+                } else {
+                    (
+                        // Crates are only written to the local cache along with a revision
+                        "200 OK\r\nETag: \"1\"",
+                        format!(
+                            r#"{{"name":"{name}","vers":"1.0.0","deps":[],"cksum":"{}","features":{{}},"yanked":true}}"#,
+                            "0".repeat(64)
+                        ),
+                    )
+                };
+
+                write!(
+                    &stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+
+        registry
+    }
+
+    /// Lockfile depending on version `1.0.0` of the given crates from this registry
+    fn lockfile(&self, names: &[&str]) -> Lockfile {
+        let mut lockfile = String::from("version = 3\n");
+        for name in names {
+            lockfile.push_str(&format!(
+                "\n[[package]]\nname = \"{name}\"\nversion = \"1.0.0\"\nsource = \"{}\"\n",
+                self.url
+            ));
+        }
+        lockfile.parse().unwrap()
+    }
+
+    /// Index of this registry, with crates cached in the given directory
+    /// rather than in the Cargo home directory
+    fn index(&self, cache_dir: &Path) -> CachedIndex {
+        CachedIndex::builder()
+            .url(&self.url)
+            .cache_dir(cache_dir)
+            .fetch(LOCK_TIMEOUT)
+            .unwrap()
+    }
+
+    /// Number of requests made to this registry so far
+    fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+}
+
+/// A failed lookup is retried on the next one instead of being cached, while
+/// a crate which doesn't exist is only looked up once
+#[test]
+fn transient_errors_are_not_cached() {
+    let registry = LocalRegistry::start();
+    let lockfile = registry.lockfile(&["flaky"]);
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut index = registry.index(cache_dir.path());
+    index.set_request_timeout(None);
+
+    let results = index.find_yanked(&lockfile.packages);
+    assert!(!results.is_empty());
+    assert!(results.iter().all(Result::is_err));
+
+    // Once the registry recovers, the crate is looked up again
+    registry.available.store(true, Ordering::SeqCst);
+    let results = index.find_yanked(&lockfile.packages);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].as_ref().unwrap().name.as_str(), "flaky");
+
+    // Crates which don't exist are cached like any other
+    let missing = "missing".parse().unwrap();
+    assert!(index.versions(&missing).unwrap().is_none());
+    let lookups = registry.request_count();
+    assert!(index.versions(&missing).unwrap().is_none());
+    assert_eq!(registry.request_count(), lookups);
+}
+
+/// Requests which don't complete within the request timeout are reported as timeouts
+#[test]
+fn request_timeout_is_reported() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let lockfile = registry.lockfile(&["unresponsive", "alpha"]);
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut index = registry.index(cache_dir.path());
+    index.set_request_timeout(Some(Duration::from_millis(500)));
+
+    let results = index.find_yanked(&lockfile.packages);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().name.as_str(), "alpha");
+    assert_eq!(results[1].as_ref().unwrap_err().kind(), ErrorKind::Timeout);
+}
+
+/// Lookups of prefetched crates are answered from the cache
+#[test]
+fn prefetch() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let lockfile = registry.lockfile(&["alpha", "beta", "missing"]);
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let index = registry.index(cache_dir.path());
+    index
+        .prefetch(lockfile.packages.iter().map(|pkg| &pkg.name))
+        .unwrap();
+    let prefetched = registry.request_count();
+    assert!(prefetched >= 3);
+
+    let results = index.find_yanked(&lockfile.packages);
+    assert_eq!(results.len(), 3);
+    assert!(results[..2].iter().all(Result::is_ok));
+    assert_eq!(results[2].as_ref().unwrap_err().kind(), ErrorKind::NotFound);
+    assert!(index.versions(&"alpha".parse().unwrap()).unwrap().is_some());
+
+    assert_eq!(registry.request_count(), prefetched);
+}
+
+/// Every distinct crate looked up is cached, including missing ones
+#[test]
+fn cached_names() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let lockfile = registry.lockfile(&["beta", "alpha", "missing"]);
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let index = registry.index(cache_dir.path());
+    assert_eq!(index.cache_len(), 0);
+    assert!(index.cached_names().is_empty());
+
+    index.find_yanked(&lockfile.packages);
+    assert_eq!(index.cache_len(), 3);
+    let names: Vec<_> = index
+        .cached_names()
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    assert_eq!(names, ["alpha", "beta", "missing"]);
+}
+
+/// The fetch hook fires once per crate fetched, and not for cached crates
+#[test]
+fn fetch_hook() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let lockfile = registry.lockfile(&["alpha", "beta", "missing"]);
+
+    let fetches = Arc::new(Mutex::new(Vec::new()));
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut index = registry.index(cache_dir.path());
+    let recorded = Arc::clone(&fetches);
+    index.set_fetch_hook(Some(Arc::new(
+        move |name: &package::Name, outcome: FetchOutcome| {
+            recorded.lock().unwrap().push((name.to_string(), outcome));
+        },
+    )));
+
+    index.versions(&"alpha".parse().unwrap()).unwrap();
+    index.find_yanked(&lockfile.packages);
+    index.find_yanked(&lockfile.packages);
+
+    let mut fetches = fetches.lock().unwrap().clone();
+    fetches.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        fetches,
+        [
+            ("alpha".to_owned(), FetchOutcome::Found),
+            ("beta".to_owned(), FetchOutcome::Found),
+            ("missing".to_owned(), FetchOutcome::NotFound),
+        ]
+    );
+}
+
+/// Crates are requested in batches of at most the configured size
+#[test]
+fn max_concurrent_requests() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let lockfile = registry.lockfile(&["gamma", "alpha", "epsilon", "beta", "delta"]);
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut index = registry.index(cache_dir.path());
+    index.set_max_concurrent_requests(Some(2));
+    assert_eq!(index.find_yanked(&lockfile.packages).len(), 5);
+
+    // Requests within a batch may arrive in any order, but batches are sequential
+    let requested: Vec<String> = registry
+        .requests
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|path| path.rsplit('/').next().unwrap().to_owned())
+        .collect();
+    let batches: Vec<Vec<String>> = requested
+        .chunks(2)
+        .map(|batch| {
+            let mut batch = batch.to_vec();
+            batch.sort();
+            batch
+        })
+        .collect();
+    assert_eq!(
+        batches,
+        [
+            vec!["alpha", "beta"],
+            vec!["delta", "epsilon"],
+            vec!["gamma"]
+        ]
+    );
+}
+
+/// Whether a file with the given name exists somewhere below `dir`
+fn contains_file(dir: &Path, name: &str) -> bool {
+    fs::read_dir(dir).unwrap().any(|entry| {
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_dir() {
+            contains_file(&entry.path(), name)
+        } else {
+            entry.file_name() == name
+        }
+    })
+}
+
+/// Crates fetched into a custom cache directory are read back from there,
+/// rather than from the Cargo home directory
+#[test]
+fn custom_cache_dir() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let name = "cached".parse().unwrap();
+
+    let index = CachedIndex::builder()
+        .url(&registry.url)
+        .cache_dir(cache_dir.path())
+        .fetch(LOCK_TIMEOUT)
+        .unwrap();
+    assert!(index.versions(&name).unwrap().is_some());
+    assert!(contains_file(cache_dir.path(), "cached"));
+
+    let index =
+        CachedIndex::open_with_cache_dir(Some(&registry.url), cache_dir.path(), LOCK_TIMEOUT)
+            .unwrap();
+    assert_eq!(index.kind(), IndexKind::SparseCached);
+    assert!(index.versions(&name).unwrap().is_some());
+
+    // Nothing has been fetched into another directory
+    let other_dir = tempfile::tempdir().unwrap();
+    let index =
+        CachedIndex::open_with_cache_dir(Some(&registry.url), other_dir.path(), LOCK_TIMEOUT)
+            .unwrap();
+    assert!(index.versions(&name).unwrap().is_none());
+}
+
+/// Set the modification time of all files in the given directory and its subdirectories
+fn set_modification_time(dir: &Path, time: SystemTime) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_dir() {
+            set_modification_time(&entry.path(), time);
+        } else {
+            filetime::set_file_mtime(entry.path(), filetime::FileTime::from_system_time(time))
+                .unwrap();
+        }
+    }
+}
+
+/// The age of a sparse index is that of the most recently cached crate
+#[test]
+fn index_age() {
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    // Nothing has been cached yet
+    let index =
+        CachedIndex::open_with_cache_dir(Some(&registry.url), cache_dir.path(), LOCK_TIMEOUT)
+            .unwrap();
+    assert_eq!(index.index_age().unwrap(), None);
+
+    let index = CachedIndex::builder()
+        .url(&registry.url)
+        .cache_dir(cache_dir.path())
+        .fetch(LOCK_TIMEOUT)
+        .unwrap();
+    index.versions(&"cached".parse().unwrap()).unwrap().unwrap();
+    set_modification_time(cache_dir.path(), SystemTime::now() - 30 * DAY);
+
+    let index =
+        CachedIndex::open_with_cache_dir(Some(&registry.url), cache_dir.path(), LOCK_TIMEOUT)
+            .unwrap();
+    let age = index.index_age().unwrap().unwrap();
+    assert!(age >= 30 * DAY && age < 31 * DAY, "unexpected age {age:?}");
+}
+
+/// An index pinned to a saved cache answers lookups from it alone, however
+/// old it is and whatever the registry says now
+#[test]
+fn pinned_sparse_snapshot() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let lockfile = registry.lockfile(&["alpha", "beta"]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let alpha = "alpha".parse().unwrap();
+
+    // Every crate of the registry is yanked now, but wasn't back then
+    let snapshot_path = cache_dir.path().join("snapshot.toml");
+    fs::write(
+        &snapshot_path,
+        format!(
+            r#"
+version = 1
+source = "{}"
+
+[crates.alpha]
+fetched_at = 0
+versions = {{ "1.0.0" = false }}
+
+[crates.beta]
+fetched_at = 0
+versions = {{ "1.0.0" = false }}
+"#,
+            registry.url
+        ),
+    )
+    .unwrap();
+
+    let open_pinned = |snapshot: &IndexSnapshot| {
+        CachedIndex::open_pinned(
+            Some(&registry.url),
+            Some(cache_dir.path()),
+            snapshot,
+            LOCK_TIMEOUT,
+        )
+    };
+
+    let snapshot = IndexSnapshot::SparseCache(snapshot_path);
+    let index = open_pinned(&snapshot).unwrap();
+    assert_eq!(index.kind(), IndexKind::SparseCached);
+    assert_eq!(index.commit(), None);
+    assert!(index.find_yanked(&lockfile.packages).is_empty());
+
+    // Crates missing from the snapshot aren't looked up in the registry
+    let err = index.versions(&"gamma".parse().unwrap()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotCached);
+    let results = index.find_yanked(&registry.lockfile(&["gamma"]).packages);
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0].as_ref().unwrap_err().kind(),
+        ErrorKind::NotCached
+    );
+    let gamma = "gamma".parse().unwrap();
+    assert_eq!(
+        index.is_version_yanked(&gamma, "1.0.0").unwrap_err().kind(),
+        ErrorKind::NotCached
+    );
+    assert_eq!(registry.request_count(), 0);
+
+    // A snapshot saved from a live index gives the same answers once the registry is gone
+    let live = registry.index(cache_dir.path());
+    assert_eq!(live.find_yanked(&lockfile.packages).len(), 2);
+    let saved_path = cache_dir.path().join("saved.toml");
+    live.save_cache(&saved_path).unwrap();
+    registry.available.store(false, Ordering::SeqCst);
+
+    let snapshot = IndexSnapshot::SparseCache(saved_path);
+    for _ in 0..2 {
+        let index = open_pinned(&snapshot).unwrap();
+        assert!(index.is_version_yanked(&alpha, "1.0.0").unwrap());
+        assert_eq!(index.find_yanked(&lockfile.packages).len(), 2);
+    }
+
+    // Snapshots have to exist, and can't be taken from another kind of index
+    let missing = IndexSnapshot::SparseCache(cache_dir.path().join("missing.toml"));
+    let err = open_pinned(&missing).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::BadParam);
+
+    let commit = IndexSnapshot::GitCommit("0".repeat(40));
+    let err = open_pinned(&commit).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::BadParam);
+}
+
+/// Only the registry packages of a lockfile are looked up
+#[test]
+fn check_lockfile() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let lockfile: Lockfile = format!(
+        r#"version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+
+[[package]]
+name = "alpha"
+version = "1.0.0"
+source = "{url}"
+
+[[package]]
+name = "forked"
+version = "1.0.0"
+source = "git+https://github.com/example/forked?branch=fix#2a5e9d1b3c4f60718293a4b5c6d7e8f901234567"
+
+[[package]]
+name = "base"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#,
+        url = registry.url
+    )
+    .parse()
+    .unwrap();
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let index = registry.index(cache_dir.path());
+    let yanked: Vec<_> = index
+        .check_lockfile(&lockfile)
+        .into_iter()
+        .map(|res| res.unwrap().name.to_string())
+        .collect();
+    assert_eq!(yanked, ["alpha"]);
+
+    let requests = registry.requests.lock().unwrap();
+    for skipped in ["/app", "/forked", "/base"] {
+        assert!(!requests.iter().any(|path| path.ends_with(skipped)));
+    }
+}
+
+/// Assuming HTTP/2 support breaks registries (or proxies) which only speak HTTP/1.1
+#[test]
+fn http2_prior_knowledge() {
+    let registry = LocalRegistry::start();
+    registry.available.store(true, Ordering::SeqCst);
+    let lockfile = registry.lockfile(&["alpha"]);
+
+    let fetch = |http2_prior_knowledge: bool| {
+        let mut index = CachedIndex::builder()
+            .url(&registry.url)
+            .http2_prior_knowledge(http2_prior_knowledge)
+            .fetch(LOCK_TIMEOUT)
+            .unwrap();
+        index.set_request_timeout(None);
+        index
+    };
+
+    let results = fetch(true).find_yanked(&lockfile.packages);
+    assert!(!results.is_empty());
+    assert!(results.iter().all(Result::is_err));
+
+    let results = fetch(false).find_yanked(&lockfile.packages);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].as_ref().unwrap().name.as_str(), "alpha");
+}
@@ -0,0 +1,130 @@
+//! Tests for fetching an advisory database other than the default one
+//!
+//! These live in their own test binary because they point `CARGO_HOME`
+//! at a temporary directory for the whole process.
+#![cfg(feature = "git")]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use once_cell::sync::Lazy;
+use rustsec::{advisory, repository::git, Database, ErrorKind};
+use std::{fs, path::Path, time::Duration};
+use support::{git, CARGO_HOME};
+
+mod support;
+
+const FIXTURE_PATH: &str = "./tests/support/advisory-db";
+
+/// Copy an advisory from the fixture database into the given repository and commit it
+fn commit_advisory(repo: &Path, advisory: &str) {
+    let target = repo.join(advisory);
+    fs::create_dir_all(target.parent().unwrap()).unwrap();
+    fs::copy(Path::new(FIXTURE_PATH).join(advisory), target).unwrap();
+
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", &format!("Add {}", advisory)]);
+}
+
+fn ids(db: &Database) -> Vec<String> {
+    let mut ids: Vec<_> = db
+        .iter()
+        .map(|advisory| advisory.id().to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn fetch_from_local_repository() {
+    Lazy::force(&CARGO_HOME);
+
+    let origin = tempfile::tempdir().unwrap();
+    git(origin.path(), &["init", "-q"]);
+    commit_advisory(origin.path(), "crates/base/RUSTSEC-2001-2101.md");
+    git(origin.path(), &["tag", "v1"]);
+    commit_advisory(origin.path(), "crates/other/RUSTSEC-2002-0001.md");
+
+    let url = format!("file://{}", origin.path().display());
+
+    let db = Database::fetch_from(&url, None).unwrap();
+    assert_eq!(ids(&db), ["RUSTSEC-2001-2101", "RUSTSEC-2002-0001"]);
+
+    let db = Database::fetch_from(&url, Some("v1")).unwrap();
+    assert_eq!(ids(&db), ["RUSTSEC-2001-2101"]);
+    let id: advisory::Id = "RUSTSEC-2001-2101".parse().unwrap();
+    assert!(db.get(&id).is_some());
+
+    // A repository without advisories in the expected place is rejected
+    let not_a_db = tempfile::tempdir().unwrap();
+    git(not_a_db.path(), &["init", "-q"]);
+    fs::write(
+        not_a_db.path().join("README.md"),
+        "Not an advisory database",
+    )
+    .unwrap();
+    git(not_a_db.path(), &["add", "."]);
+    git(not_a_db.path(), &["commit", "-q", "-m", "Initial commit"]);
+
+    let url = format!("file://{}", not_a_db.path().display());
+    let err = Database::fetch_from(&url, None).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::Repo);
+}
+
+/// Databases whose URLs only differ in punctuation are kept in separate clones,
+/// and an existing clone of another repository is never reused
+#[test]
+fn fetch_from_similar_urls() {
+    Lazy::force(&CARGO_HOME);
+
+    let origins = tempfile::tempdir().unwrap();
+    let hyphenated = origins.path().join("a-b");
+    let nested = origins.path().join("a").join("b");
+
+    for (origin, advisory) in [
+        (&hyphenated, "crates/base/RUSTSEC-2001-2101.md"),
+        (&nested, "crates/other/RUSTSEC-2002-0001.md"),
+    ] {
+        fs::create_dir_all(origin).unwrap();
+        git(origin, &["init", "-q"]);
+        commit_advisory(origin, advisory);
+    }
+
+    let hyphenated_url = format!("file://{}", hyphenated.display());
+    let nested_url = format!("file://{}", nested.display());
+    assert_ne!(
+        git::Repository::path_for_url(&hyphenated_url),
+        git::Repository::path_for_url(&nested_url)
+    );
+
+    let db = Database::fetch_from(&hyphenated_url, None).unwrap();
+    assert_eq!(ids(&db), ["RUSTSEC-2001-2101"]);
+    let db = Database::fetch_from(&nested_url, None).unwrap();
+    assert_eq!(ids(&db), ["RUSTSEC-2002-0001"]);
+    let db = Database::fetch_from(&hyphenated_url, None).unwrap();
+    assert_eq!(ids(&db), ["RUSTSEC-2001-2101"]);
+
+    // URLs of the same repository share a clone
+    assert_eq!(
+        git::Repository::path_for_url(&format!("{hyphenated_url}.git")),
+        git::Repository::path_for_url(&format!("{hyphenated_url}/"))
+    );
+
+    // A clone whose origin was changed to another repository isn't reused
+    git(
+        &git::Repository::path_for_url(&hyphenated_url),
+        &["remote", "set-url", "origin", &nested_url],
+    );
+    let err = Database::fetch_from(&hyphenated_url, None).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::Repo);
+
+    // Only custom databases can be fetched from local repositories
+    let checkout = tempfile::tempdir().unwrap();
+    let err = git::Repository::fetch(
+        &nested_url,
+        checkout.path().join("advisory-db"),
+        true,
+        Duration::from_secs(0),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.kind(), ErrorKind::BadParam);
+}
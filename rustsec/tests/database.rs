@@ -2,8 +2,11 @@
 
 use cargo_lock::Lockfile;
 use once_cell::sync::Lazy;
-use rustsec::{database::Query, repository::git::Repository, Database};
-use std::{path::Path, sync::Mutex};
+use rustsec::{database::Query, repository::git::Repository, Database, ErrorKind};
+use std::{fs, path::Path, sync::Mutex, time::Duration};
+use support::git;
+
+mod support;
 
 static DEFAULT_DATABASE: Lazy<Mutex<Database>> = Lazy::new(|| {
     Mutex::new(
@@ -32,3 +35,95 @@ fn query_vulnerabilitie() {
     let vuln = db.vulnerabilities(&lockfile);
     assert_eq!(vuln_all, vuln);
 }
+
+/// Copy an advisory from the fixture database into the given directory
+fn copy_advisory(dir: &Path, package: &str, id: &str) {
+    let path = format!("crates/{}/{}.md", package, id);
+    let target = dir.join(&path);
+    fs::create_dir_all(target.parent().unwrap()).unwrap();
+    fs::copy(Path::new("./tests/support/advisory-db").join(&path), target).unwrap();
+}
+
+#[test]
+fn update_from_repo() {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    copy_advisory(dir.path(), "base", "RUSTSEC-2001-2101");
+    copy_advisory(dir.path(), "other", "RUSTSEC-2002-0001");
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "Add advisories"]);
+
+    let mut db = Database::load_from_repo(&Repository::open(dir.path()).unwrap()).unwrap();
+    let first_commit = db.latest_commit().unwrap().commit_id;
+    assert_eq!(db.iter().count(), 2);
+
+    // Change one advisory and add another one
+    let other = dir.path().join("crates/other/RUSTSEC-2002-0001.md");
+    let contents = fs::read_to_string(&other).unwrap();
+    fs::write(&other, contents.replace("# Use-after-free", "# Updated")).unwrap();
+    copy_advisory(dir.path(), "unclassified", "RUSTSEC-2003-0001");
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "Update advisories"]);
+
+    // Unchanged advisories aren't parsed again, so corrupting one on disk
+    // without committing it has no effect
+    let base = dir.path().join("crates/base/RUSTSEC-2001-2101.md");
+    fs::write(&base, "not an advisory").unwrap();
+
+    db.update_from_repo(&Repository::open(dir.path()).unwrap())
+        .unwrap();
+
+    let latest_commit = db.latest_commit().unwrap().commit_id;
+    assert_ne!(latest_commit, first_commit);
+    assert!(db.load_errors().is_empty());
+
+    let mut ids: Vec<_> = db
+        .iter()
+        .map(|advisory| advisory.id().to_string())
+        .collect();
+    ids.sort();
+    assert_eq!(
+        ids,
+        [
+            "RUSTSEC-2001-2101",
+            "RUSTSEC-2002-0001",
+            "RUSTSEC-2003-0001"
+        ]
+    );
+
+    let updated = db.get(&"RUSTSEC-2002-0001".parse().unwrap()).unwrap();
+    assert!(updated.title().starts_with("Updated"));
+    assert_eq!(db.find_by_alias("CVE-2002-1234").len(), 1);
+
+    // Deleting an advisory removes it from the database
+    git(dir.path(), &["checkout", "-q", "--", "."]);
+    git(
+        dir.path(),
+        &["rm", "-q", "crates/unclassified/RUSTSEC-2003-0001.md"],
+    );
+    git(dir.path(), &["commit", "-q", "-m", "Remove advisory"]);
+
+    db.update_from_repo(&Repository::open(dir.path()).unwrap())
+        .unwrap();
+    assert_eq!(db.iter().count(), 2);
+    assert!(db.get(&"RUSTSEC-2003-0001".parse().unwrap()).is_none());
+}
+
+#[test]
+fn fetch_with_held_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let checkout = dir.path().join("advisory-db");
+
+    // Hold the lock on the checkout like another fetch in progress would
+    fs::write(dir.path().join("advisory-db.rustsec.lock"), "").unwrap();
+
+    let err = Repository::fetch(
+        "https://example.com/advisory-db.git",
+        &checkout,
+        true,
+        Duration::from_secs(0),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LockTimeout);
+    assert!(!checkout.exists());
+}
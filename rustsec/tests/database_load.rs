@@ -0,0 +1,533 @@
+//! Tests for loading advisory databases from the filesystem
+
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use rustsec::{
+    advisory::{self, Id, Informational},
+    database::Query,
+    Collection, Database, ErrorKind,
+};
+use std::{collections::BTreeSet, fs, path::Path};
+
+/// Database with one malformed advisory among valid ones
+const BROKEN_FIXTURE_PATH: &str = "./tests/support/advisory-db-broken";
+
+#[test]
+fn skips_malformed_advisories() {
+    let db = Database::open(Path::new(BROKEN_FIXTURE_PATH)).unwrap();
+
+    let mut ids: Vec<_> = db
+        .iter()
+        .map(|advisory| advisory.id().to_string())
+        .collect();
+    ids.sort();
+    assert_eq!(ids, ["RUSTSEC-2001-2101", "RUSTSEC-2002-0001"]);
+
+    let errors = db.load_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].0.ends_with("crates/broken/RUSTSEC-2006-0001.md"));
+    assert_eq!(errors[0].1.kind(), ErrorKind::Parse);
+}
+
+#[test]
+fn strict_mode_fails_on_malformed_advisories() {
+    let err = Database::open_strict(Path::new(BROKEN_FIXTURE_PATH)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Parse);
+}
+
+#[test]
+fn valid_database_has_no_load_errors() {
+    let path = Path::new("./tests/support/advisory-db");
+    assert!(Database::open(path).unwrap().load_errors().is_empty());
+    assert_eq!(Database::open_strict(path).unwrap().iter().count(), 5);
+}
+
+#[test]
+fn open_dir() {
+    let db = Database::open_dir(Path::new("./tests/support/advisory-db")).unwrap();
+    assert_eq!(db.iter().count(), 5);
+
+    // A directory which isn't laid out like the advisory database
+    let dir = tempfile::tempdir().unwrap();
+    write_advisory(dir.path(), "advisories", "base", "RUSTSEC-2001-0001", "");
+    let err = Database::open_dir(dir.path()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Repo);
+    assert!(err.to_string().contains("`crates` or `rust` directory"));
+
+    // Advisories must be in package directories
+    write_advisory(dir.path(), "crates", "base", "RUSTSEC-2001-0001", "");
+    assert_eq!(Database::open_dir(dir.path()).unwrap().iter().count(), 1);
+    fs::write(dir.path().join("crates/RUSTSEC-2001-0002.md"), "").unwrap();
+    let err = Database::open_dir(dir.path()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Repo);
+    assert!(err
+        .to_string()
+        .contains("expected only package directories"));
+
+    let err = Database::open_dir(&dir.path().join("missing")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn lazy_iteration_matches_eager_loading() {
+    let path = Path::new("./tests/support/advisory-db");
+
+    let mut eager: Vec<_> = Database::open(path).unwrap().into_iter().collect();
+    let mut lazy: Vec<_> = Database::iter_lazy(path).collect::<Result<_, _>>().unwrap();
+
+    eager.sort_by(|a, b| a.id().cmp(b.id()));
+    lazy.sort_by(|a, b| a.id().cmp(b.id()));
+    assert_eq!(lazy, eager);
+
+    // Malformed advisories are yielded as errors without ending iteration
+    let results: Vec<_> = Database::iter_lazy(Path::new(BROKEN_FIXTURE_PATH)).collect();
+    assert_eq!(results.len(), 3);
+    let errors: Vec<_> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind(), ErrorKind::Parse);
+}
+
+#[test]
+fn statistics() {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let stats = db.statistics();
+
+    assert_eq!(stats.total, 5);
+    assert_eq!(stats.with_cvss, 1);
+    assert_eq!(stats.without_cvss, 4);
+    assert_eq!(stats.withdrawn, 1);
+    assert_eq!(
+        stats.informational.into_iter().collect::<Vec<_>>(),
+        [(Informational::Unmaintained, 1)]
+    );
+    assert_eq!(
+        stats.by_year.into_iter().collect::<Vec<_>>(),
+        [(2001, 1), (2002, 1), (2003, 1), (2004, 1), (2005, 1)]
+    );
+}
+
+#[test]
+fn diff() {
+    let old = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let new = Database::open(Path::new("./tests/support/advisory-db-updated")).unwrap();
+
+    // The description of `RUSTSEC-2001-2101` was edited, which doesn't count
+    let diff = old.diff(&new);
+    assert_eq!(ids(&diff.added), ["RUSTSEC-2006-0001"]);
+    assert_eq!(ids(&diff.modified), ["RUSTSEC-2002-0001"]);
+    assert!(diff.removed.is_empty());
+
+    let diff = new.diff(&old);
+    assert!(diff.added.is_empty());
+    assert_eq!(ids(&diff.removed), ["RUSTSEC-2006-0001"]);
+    assert_eq!(ids(&diff.modified), ["RUSTSEC-2002-0001"]);
+
+    assert!(old.diff(&old).is_empty());
+}
+
+fn ids(ids: &BTreeSet<Id>) -> Vec<String> {
+    ids.iter().map(ToString::to_string).collect()
+}
+
+/// Write an advisory to the given collection directory of a database
+fn write_advisory(db_dir: &Path, collection: &str, package: &str, id: &str, extra: &str) {
+    let package_dir = db_dir.join(collection).join(package);
+    fs::create_dir_all(&package_dir).unwrap();
+    fs::write(
+        package_dir.join(format!("{id}.md")),
+        format!(
+            "```toml\n[advisory]\nid = \"{id}\"\npackage = \"{package}\"\ndate = \"2001-01-01\"\n{extra}\n[versions]\npatched = []\n```\n\n# Vulnerability in {package}\n\nDetails.\n"
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn duplicate_ids() {
+    let dir = tempfile::tempdir().unwrap();
+    write_advisory(dir.path(), "crates", "base", "RUSTSEC-2001-0001", "");
+    write_advisory(dir.path(), "crates", "other", "RUSTSEC-2001-0001", "");
+    write_advisory(dir.path(), "crates", "other", "RUSTSEC-2001-0002", "");
+
+    let db = Database::open(dir.path()).unwrap();
+    assert_eq!(db.iter().count(), 2);
+    assert_eq!(db.duplicate_ids(), [&"RUSTSEC-2001-0001".parse().unwrap()]);
+
+    let errors = db.load_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].0.ends_with("RUSTSEC-2001-0001.md"));
+    assert!(errors[0]
+        .1
+        .to_string()
+        .contains("duplicate advisory ID: RUSTSEC-2001-0001"));
+
+    let err = Database::open_strict(dir.path()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Parse);
+
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    assert!(db.duplicate_ids().is_empty());
+}
+
+#[test]
+fn collections() {
+    let dir = tempfile::tempdir().unwrap();
+    write_advisory(dir.path(), "crates", "base", "RUSTSEC-2001-0001", "");
+    write_advisory(dir.path(), "rust", "std", "RUSTSEC-2001-0002", "");
+    write_advisory(dir.path(), "tooling", "rustup", "RUSTSEC-2001-0003", "");
+
+    let collections = |db: &Database| -> Vec<(String, Option<Collection>)> {
+        let mut collections: Vec<_> = db
+            .iter()
+            .map(|advisory| (advisory.id().to_string(), advisory.collection()))
+            .collect();
+        collections.sort();
+        collections
+    };
+
+    // Only the known collections are loaded by default
+    let db = Database::open(dir.path()).unwrap();
+    assert_eq!(
+        collections(&db),
+        [
+            ("RUSTSEC-2001-0001".to_owned(), Some(Collection::Crates)),
+            ("RUSTSEC-2001-0002".to_owned(), Some(Collection::Rust)),
+        ]
+    );
+
+    let db = Database::open_with_collections(dir.path(), &["crates", "rust", "tooling"]).unwrap();
+    assert!(db.load_errors().is_empty());
+    assert_eq!(
+        collections(&db),
+        [
+            ("RUSTSEC-2001-0001".to_owned(), Some(Collection::Crates)),
+            ("RUSTSEC-2001-0002".to_owned(), Some(Collection::Rust)),
+            ("RUSTSEC-2001-0003".to_owned(), Some(Collection::Other)),
+        ]
+    );
+
+    // The directory of each advisory is known, including for other collections
+    let dir_of = |id: &str| db.get(&id.parse().unwrap()).unwrap().collection_dir();
+    assert_eq!(dir_of("RUSTSEC-2001-0001"), Some("crates"));
+    assert_eq!(dir_of("RUSTSEC-2001-0002"), Some("rust"));
+    assert_eq!(dir_of("RUSTSEC-2001-0003"), Some("tooling"));
+
+    let query = Query::new()
+        .collection(Collection::Other)
+        .package_name("rustup".parse().unwrap());
+    assert_eq!(db.query(&query).len(), 1);
+
+    let query = Query::new()
+        .collection(Collection::Rust)
+        .package_name("std".parse().unwrap());
+    assert_eq!(db.query(&query)[0].id().as_str(), "RUSTSEC-2001-0002");
+}
+
+#[test]
+fn merge() {
+    let mut db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+
+    // Internal advisories, one of which replaces an official one
+    let dir = tempfile::tempdir().unwrap();
+    write_advisory(
+        dir.path(),
+        "crates",
+        "other",
+        "RUSTSEC-2002-0001",
+        "aliases = [\"CVE-2002-9999\"]",
+    );
+    write_advisory(
+        dir.path(),
+        "crates",
+        "internal",
+        "RUSTSEC-2020-0001",
+        "aliases = [\"CVE-2020-9999\"]",
+    );
+    db.merge(Database::open(dir.path()).unwrap());
+
+    assert_eq!(db.iter().count(), 6);
+    assert_eq!(db.path(), Path::new("./tests/support/advisory-db"));
+
+    let replaced = db.get(&"RUSTSEC-2002-0001".parse().unwrap()).unwrap();
+    assert_eq!(replaced.title(), "Vulnerability in other");
+    assert!(db.get(&"RUSTSEC-2020-0001".parse().unwrap()).is_some());
+
+    let ids = |advisories: Vec<&rustsec::Advisory>| -> Vec<String> {
+        let mut ids: Vec<_> = advisories
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    // Aliases of the replaced advisory are gone
+    assert_eq!(
+        ids(db.find_by_alias("CVE-2002-1234")),
+        ["RUSTSEC-2004-0001"]
+    );
+    assert_eq!(
+        ids(db.find_by_alias("CVE-2002-9999")),
+        ["RUSTSEC-2002-0001"]
+    );
+    assert_eq!(
+        ids(db.find_by_alias("CVE-2020-9999")),
+        ["RUSTSEC-2020-0001"]
+    );
+
+    for package in ["other", "internal"] {
+        let query = Query::crate_scope().package_name(package.parse().unwrap());
+        assert_eq!(db.query(&query).len(), 1, "{package}");
+    }
+}
+
+#[test]
+fn advisories_for_crate() {
+    let dir = tempfile::tempdir().unwrap();
+    let package_dir = dir.path().join("crates").join("base");
+    fs::create_dir_all(&package_dir).unwrap();
+
+    for (id, date, patched, extra) in [
+        ("RUSTSEC-2003-0001", "2003-01-01", ">= 3.0.0", ""),
+        ("RUSTSEC-2001-0001", "2001-01-01", ">= 1.0.0", ""),
+        (
+            "RUSTSEC-2002-0001",
+            "2002-01-01",
+            ">= 2.0.0",
+            "withdrawn = \"2002-02-01\"\n",
+        ),
+    ] {
+        fs::write(
+            package_dir.join(format!("{id}.md")),
+            format!(
+                "```toml\n[advisory]\nid = \"{id}\"\npackage = \"base\"\ndate = \"{date}\"\n{extra}\n[versions]\npatched = [\"{patched}\"]\n```\n\n# Vulnerability in base\n\nDetails.\n"
+            ),
+        )
+        .unwrap();
+    }
+    write_advisory(dir.path(), "crates", "other", "RUSTSEC-2004-0001", "");
+    write_advisory(dir.path(), "rust", "base", "RUSTSEC-2005-0001", "");
+
+    let db = Database::open(dir.path()).unwrap();
+    assert!(db.load_errors().is_empty());
+
+    // All advisories of the crate, whichever versions they affect, by date
+    let advisories = db.advisories_for_crate(&"base".parse().unwrap());
+    let found: Vec<_> = advisories
+        .iter()
+        .map(|advisory| (advisory.id().as_str(), advisory.withdrawn()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("RUSTSEC-2001-0001", false),
+            ("RUSTSEC-2002-0001", true),
+            ("RUSTSEC-2003-0001", false),
+        ]
+    );
+
+    assert!(db
+        .advisories_for_crate(&"unknown".parse().unwrap())
+        .is_empty());
+}
+
+#[test]
+fn search() {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let ids = |advisories: Vec<&rustsec::Advisory>| -> Vec<String> {
+        advisories
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect()
+    };
+
+    // Match in the title only
+    assert_eq!(ids(db.search("UNMAINTAINED")), ["RUSTSEC-2005-0001"]);
+
+    // Match in the description only
+    assert_eq!(
+        ids(db.search("while it is borrowed")),
+        ["RUSTSEC-2002-0001"]
+    );
+
+    // Words may be spread across title and description
+    assert_eq!(ids(db.search("abandoned archived")), ["RUSTSEC-2005-0001"]);
+
+    // No match at all
+    assert!(db.search("http smuggling").is_empty());
+    assert!(db.search("  ").is_empty());
+
+    // Matches in the title are ranked above matches in the description
+    let dir = tempfile::tempdir().unwrap();
+    for (package, id, title, description) in [
+        (
+            "proxy",
+            "RUSTSEC-2001-0001",
+            "Header parsing bug",
+            "Allows HTTP request smuggling.",
+        ),
+        (
+            "server",
+            "RUSTSEC-2001-0002",
+            "HTTP request smuggling",
+            "Details.",
+        ),
+    ] {
+        let package_dir = dir.path().join("crates").join(package);
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(
+            package_dir.join(format!("{id}.md")),
+            format!(
+                "```toml\n[advisory]\nid = \"{id}\"\npackage = \"{package}\"\ndate = \"2001-01-01\"\n\n[versions]\npatched = []\n```\n\n# {title}\n\n{description}\n"
+            ),
+        )
+        .unwrap();
+    }
+
+    let db = Database::open(dir.path()).unwrap();
+    assert_eq!(
+        ids(db.search("request smuggling")),
+        ["RUSTSEC-2001-0002", "RUSTSEC-2001-0001"]
+    );
+}
+
+#[test]
+fn advisory_path_mismatch() {
+    let dir = tempfile::tempdir().unwrap();
+    write_advisory(dir.path(), "crates", "base", "RUSTSEC-2001-0001", "");
+    assert!(Database::open_strict(dir.path()).is_ok());
+
+    // ID inside the file disagrees with its name
+    write_advisory(dir.path(), "crates", "other", "RUSTSEC-2001-0002", "");
+    let misnamed = dir.path().join("crates/other/RUSTSEC-2001-0003.md");
+    fs::rename(
+        dir.path().join("crates/other/RUSTSEC-2001-0002.md"),
+        &misnamed,
+    )
+    .unwrap();
+
+    // Package inside the file disagrees with its directory
+    write_advisory(dir.path(), "crates", "moved", "RUSTSEC-2001-0004", "");
+    fs::create_dir_all(dir.path().join("crates/elsewhere")).unwrap();
+    let misplaced = dir.path().join("crates/elsewhere/RUSTSEC-2001-0004.md");
+    fs::rename(
+        dir.path().join("crates/moved/RUSTSEC-2001-0004.md"),
+        &misplaced,
+    )
+    .unwrap();
+
+    let err = Database::open_strict(dir.path()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Repo);
+
+    // Leniently, the mismatching advisories are skipped and reported
+    let db = Database::open(dir.path()).unwrap();
+    let ids: Vec<_> = db
+        .iter()
+        .map(|advisory| advisory.id().to_string())
+        .collect();
+    assert_eq!(ids, ["RUSTSEC-2001-0001"]);
+
+    let mut errors: Vec<_> = db
+        .load_errors()
+        .iter()
+        .map(|(path, err)| (path.clone(), err.kind(), err.to_string()))
+        .collect();
+    errors.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(errors.len(), 2);
+
+    assert_eq!(errors[0].0, misplaced);
+    assert_eq!(errors[0].1, ErrorKind::Repo);
+    assert!(errors[0].2.contains("to be in moved directory"));
+
+    assert_eq!(errors[1].0, misnamed);
+    assert_eq!(errors[1].1, ErrorKind::Repo);
+    assert!(errors[1].2.contains("to be named"));
+}
+
+#[cfg(feature = "archive")]
+#[test]
+fn from_archive() {
+    let fixture = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+
+    // Tarballs usually nest the database in a top-level directory
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    builder
+        .append_dir_all("advisory-db-main", "./tests/support/advisory-db")
+        .unwrap();
+    let archive = builder.into_inner().unwrap().finish().unwrap();
+
+    let db = Database::from_archive(archive.as_slice()).unwrap();
+    assert!(db.load_errors().is_empty());
+    assert_eq!(db.path(), Path::new(""));
+
+    let ids = |db: &Database| -> Vec<String> {
+        let mut ids: Vec<_> = db
+            .iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+    assert_eq!(ids(&db), ids(&fixture));
+
+    let advisory = db.get(&"RUSTSEC-2002-0001".parse().unwrap()).unwrap();
+    assert_eq!(advisory, fixture.get(advisory.id()).unwrap());
+    assert_eq!(db.find_by_alias("CVE-2002-1234").len(), 2);
+}
+
+/// Rust toolchain versions can be given in several forms
+#[test]
+fn parse_rust_version() {
+    let parse = |version: &str| advisory::parse_rust_version(version).unwrap().to_string();
+
+    assert_eq!(parse("1.70.0"), "1.70.0");
+    assert_eq!(parse("1.70"), "1.70.0");
+    assert_eq!(parse("rustc 1.70.0 (90c541806 2023-05-31)"), "1.70.0");
+    assert_eq!(parse("1.72.0-nightly"), "1.72.0-nightly");
+    assert_eq!(parse("1.72-beta.2"), "1.72.0-beta.2");
+
+    let err = advisory::parse_rust_version("stable").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Version);
+}
+
+/// Advisories against the Rust toolchain are matched by its version
+#[test]
+fn toolchain_vulnerabilities() {
+    let db = Database::open(Path::new("./tests/support/toolchain-advisory-db")).unwrap();
+    let affecting = |version: &str| {
+        let version = advisory::parse_rust_version(version).unwrap();
+        db.toolchain_vulnerabilities(&version)
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect::<BTreeSet<_>>()
+    };
+
+    assert_eq!(
+        affecting("1.59"),
+        BTreeSet::from(["RUSTSEC-2006-0002".into()])
+    );
+    assert_eq!(
+        affecting("1.65.0"),
+        BTreeSet::from(["RUSTSEC-2006-0001".into(), "RUSTSEC-2006-0002".into()])
+    );
+    assert_eq!(
+        affecting("1.70.0"),
+        BTreeSet::from(["RUSTSEC-2006-0001".into()])
+    );
+    assert!(affecting("1.72.0").is_empty());
+
+    // Nightlies precede the release of their version
+    assert_eq!(
+        affecting("1.72.0-nightly"),
+        BTreeSet::from(["RUSTSEC-2006-0001".into()])
+    );
+
+    // Toolchain advisories aren't matched against crates
+    assert_eq!(db.query(&Query::crate_scope()).len(), 0);
+    assert_eq!(
+        db.query(&Query::new().collection(Collection::Rust)).len(),
+        2
+    );
+}
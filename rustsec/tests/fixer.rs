@@ -0,0 +1,347 @@
+//! Tests for planning fixes of vulnerable dependencies
+#![cfg(feature = "fix")]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use rustsec::{package::Package, Advisory, Fixer, Remediation, Vulnerability};
+use std::{collections::HashMap, fs, path::Path};
+
+#[cfg(feature = "git-index")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "git-index")]
+use rustsec::registry::CachedIndex;
+#[cfg(feature = "git-index")]
+use std::time::Duration;
+#[cfg(feature = "git-index")]
+use support::{create_git_index, CARGO_HOME};
+
+mod support;
+
+/// Vulnerability of the given version of `multi` to an advisory with the
+/// given patched versions
+fn vulnerability(id: &str, patched: &str, version: &str) -> Vulnerability {
+    let advisory: Advisory = format!(
+        "```toml\n[advisory]\nid = \"{id}\"\npackage = \"multi\"\ndate = \"2020-01-01\"\n\n[versions]\npatched = [{patched}]\n```\n\n# Vulnerability in multi\n\nDetails.\n"
+    )
+    .parse()
+    .unwrap();
+
+    let package = Package {
+        name: "multi".parse().unwrap(),
+        version: version.parse().unwrap(),
+        source: None,
+        checksum: None,
+        dependencies: vec![],
+        replace: None,
+    };
+
+    Vulnerability::new(&advisory, &package)
+}
+
+fn plan_fixes(vulnerabilities: &[Vulnerability]) -> Vec<Remediation> {
+    let dir = tempfile::tempdir().unwrap();
+    let cargo_toml = dir.path().join("Cargo.toml");
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"example\"\nversion = \"0.1.0\"\n\n[dependencies]\nmulti = \"1.4\"\n",
+    )
+    .unwrap();
+
+    let plan = Fixer::new(&cargo_toml).unwrap().plan(vulnerabilities);
+    assert!(plan.unfixable.is_empty());
+    plan.remediations
+}
+
+#[test]
+fn plan_patch_level_fix() {
+    let plan = plan_fixes(&[vulnerability(
+        "RUSTSEC-2020-0001",
+        "\">= 2.0.0\", \"^1.4.2\"",
+        "1.4.0",
+    )]);
+
+    assert_eq!(
+        plan,
+        [Remediation {
+            package: "multi".parse().unwrap(),
+            from: "1.4.0".parse().unwrap(),
+            to: "1.4.2".parse().unwrap(),
+            breaking: false,
+            yanked: vec![],
+        }]
+    );
+}
+
+#[test]
+fn plan_breaking_fix() {
+    let plan = plan_fixes(&[vulnerability("RUSTSEC-2020-0001", "\">= 2.0.0\"", "1.4.0")]);
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].to, "2.0.0".parse().unwrap());
+    assert!(plan[0].breaking);
+
+    // Minor versions are breaking before 1.0.0
+    let plan = plan_fixes(&[vulnerability("RUSTSEC-2020-0001", "\">= 0.3.1\"", "0.2.9")]);
+    assert_eq!(plan[0].to, "0.3.1".parse().unwrap());
+    assert!(plan[0].breaking);
+}
+
+#[test]
+fn plan_multiple_vulnerabilities() {
+    // The package is upgraded far enough to fix all of its vulnerabilities,
+    // ignoring those which haven't been fixed
+    let plan = plan_fixes(&[
+        vulnerability("RUSTSEC-2020-0001", "\"^1.4.2\"", "1.4.0"),
+        vulnerability("RUSTSEC-2020-0002", "\">= 1.5.0\"", "1.4.0"),
+        vulnerability("RUSTSEC-2020-0003", "", "1.4.0"),
+    ]);
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].to, "1.5.0".parse().unwrap());
+    assert!(!plan[0].breaking);
+
+    // Unfixed vulnerabilities aren't planned
+    assert!(plan_fixes(&[vulnerability("RUSTSEC-2020-0003", "", "1.4.0")]).is_empty());
+}
+
+#[test]
+fn plan_intersecting_patched_ranges() {
+    // 1.4.2 is only patched against the first advisory and 2.0.0 only against
+    // the second, so the lowest version patched against both is picked
+    let plan = plan_fixes(&[
+        vulnerability("RUSTSEC-2020-0001", "\"^1.4.2\", \">= 2.1.0\"", "1.4.0"),
+        vulnerability("RUSTSEC-2020-0002", "\">= 2.0.0\"", "1.4.0"),
+    ]);
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].to, "2.1.0".parse().unwrap());
+    assert!(plan[0].breaking);
+}
+
+#[test]
+fn plan_disjoint_patched_ranges() {
+    let dir = tempfile::tempdir().unwrap();
+    let cargo_toml = dir.path().join("Cargo.toml");
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"example\"\nversion = \"0.1.0\"\n\n[dependencies]\nmulti = \"1.4\"\n",
+    )
+    .unwrap();
+
+    // Only the version whose patched ranges don't intersect is left unfixed
+    let plan = Fixer::new(&cargo_toml).unwrap().plan(&[
+        vulnerability("RUSTSEC-2020-0001", "\"^1.4.2\"", "1.4.0"),
+        vulnerability("RUSTSEC-2020-0002", "\">= 2.0.0\"", "1.4.0"),
+        vulnerability("RUSTSEC-2020-0003", "\">= 2.0.0\"", "1.3.0"),
+    ]);
+    assert_eq!(plan.remediations.len(), 1);
+    assert_eq!(plan.remediations[0].from, "1.3.0".parse().unwrap());
+
+    assert_eq!(plan.unfixable.len(), 1);
+    let (package, err) = &plan.unfixable[0];
+    assert_eq!(package.as_str(), "multi");
+    assert_eq!(err.kind(), rustsec::ErrorKind::Fix);
+    assert!(err.to_string().contains(
+        "no version of multi is patched against all of RUSTSEC-2020-0001, RUSTSEC-2020-0002"
+    ));
+}
+
+/// Published versions of `multi`, mapped to whether they're yanked
+fn published_versions(versions: &[(&str, bool)]) -> HashMap<String, bool> {
+    versions
+        .iter()
+        .map(|(version, yanked)| (version.to_string(), *yanked))
+        .collect()
+}
+
+#[test]
+fn skip_yanked_patched_version() {
+    let vulnerability = vulnerability("RUSTSEC-2020-0001", "\">= 2.0.0\", \"^1.4.2\"", "1.4.0");
+    let versions = published_versions(&[
+        ("1.4.0", false),
+        ("1.4.1", false),
+        ("1.4.2", true),
+        ("1.4.3", false),
+        ("2.0.0", false),
+    ]);
+
+    let remediation = Remediation::for_published_versions(&vulnerability, &versions).unwrap();
+    assert_eq!(remediation.to, "1.4.3".parse().unwrap());
+    assert_eq!(
+        remediation.yanked,
+        ["1.4.2".parse::<rustsec::Version>().unwrap()]
+    );
+    assert!(!remediation.breaking);
+
+    // Nothing is yanked
+    let versions = published_versions(&[("1.4.2", false), ("1.5.0-rc.1", false)]);
+    let remediation = Remediation::for_published_versions(&vulnerability, &versions).unwrap();
+    assert_eq!(remediation.to, "1.4.2".parse().unwrap());
+    assert!(remediation.yanked.is_empty());
+
+    // The only fixes are yanked, or pre-releases
+    let versions = published_versions(&[("1.4.2", true), ("2.0.0-rc.1", false)]);
+    assert_eq!(
+        Remediation::for_published_versions(&vulnerability, &versions),
+        None
+    );
+}
+
+#[cfg(feature = "git-index")]
+#[test]
+fn plan_with_index() {
+    Lazy::force(&CARGO_HOME);
+
+    let index_dir = tempfile::tempdir().unwrap();
+    let url = create_git_index(
+        index_dir.path(),
+        &[(
+            "multi",
+            &[("1.4.0", false), ("1.4.2", true), ("1.4.3", false)],
+        )],
+    );
+    let cache_dir = tempfile::tempdir().unwrap();
+    let index = CachedIndex::builder()
+        .url(&url)
+        .cache_dir(cache_dir.path())
+        .fetch(Duration::from_secs(0))
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let cargo_toml = dir.path().join("Cargo.toml");
+    fs::write(
+        &cargo_toml,
+        "[package]\nname = \"example\"\nversion = \"0.1.0\"\n\n[dependencies]\nmulti = \"1.4\"\n",
+    )
+    .unwrap();
+    let fixer = Fixer::new(&cargo_toml).unwrap();
+
+    // Without the index, the yanked patched version is proposed
+    let vulnerabilities = [vulnerability("RUSTSEC-2020-0001", "\"^1.4.2\"", "1.4.0")];
+    assert_eq!(
+        fixer.plan(&vulnerabilities).remediations[0].to,
+        "1.4.2".parse().unwrap()
+    );
+
+    let plan = fixer.plan_with_index(&vulnerabilities, &index);
+    assert!(plan.unfixable.is_empty());
+    assert_eq!(
+        plan.remediations,
+        [Remediation {
+            package: "multi".parse().unwrap(),
+            from: "1.4.0".parse().unwrap(),
+            to: "1.4.3".parse().unwrap(),
+            breaking: false,
+            yanked: vec!["1.4.2".parse().unwrap()],
+        }]
+    );
+}
+
+/// Write a workspace whose `app` member inherits `multi` from the root
+/// manifest and depends on `local` by path
+fn write_workspace(root: &Path) {
+    let write = |path: &str, contents: &str| {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    };
+
+    write(
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.dependencies]\nmulti = \"1.4\" # keep in sync\n",
+    );
+    write(
+        "crates/app/Cargo.toml",
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nmulti = { workspace = true }\nlocal = { path = \"../local\", version = \"1.4\" }\n",
+    );
+    write(
+        "crates/local/Cargo.toml",
+        "[package]\nname = \"local\"\nversion = \"1.4.0\"\n",
+    );
+}
+
+#[test]
+fn fix_inherited_workspace_dependency() {
+    let dir = tempfile::tempdir().unwrap();
+    write_workspace(dir.path());
+
+    let member = dir.path().join("crates/app/Cargo.toml");
+    let member_before = fs::read_to_string(&member).unwrap();
+    let mut fixer = Fixer::new(&member).unwrap();
+
+    let remediation = Remediation {
+        package: "multi".parse().unwrap(),
+        from: "1.4.0".parse().unwrap(),
+        to: "1.4.2".parse().unwrap(),
+        breaking: false,
+        yanked: vec![],
+    };
+
+    // Dry runs leave the manifests alone
+    fixer.apply(&remediation, true).unwrap();
+    let root = dir.path().join("Cargo.toml");
+    assert!(fs::read_to_string(&root)
+        .unwrap()
+        .contains("multi = \"1.4\" # keep in sync"));
+
+    // The dependency is upgraded where it's declared, not in the member
+    fixer.apply(&remediation, false).unwrap();
+    assert!(fs::read_to_string(&root)
+        .unwrap()
+        .contains("multi = \"1.4.2\" # keep in sync"));
+    assert_eq!(fs::read_to_string(&member).unwrap(), member_before);
+}
+
+#[test]
+fn skip_path_dependency() {
+    let dir = tempfile::tempdir().unwrap();
+    write_workspace(dir.path());
+
+    let mut fixer = Fixer::new(dir.path().join("Cargo.toml")).unwrap();
+    let remediation = Remediation {
+        package: "local".parse().unwrap(),
+        from: "1.4.0".parse().unwrap(),
+        to: "1.4.2".parse().unwrap(),
+        breaking: false,
+        yanked: vec![],
+    };
+
+    // The path dependency is reported rather than upgraded
+    let member = dir.path().join("crates/app/Cargo.toml");
+    let member_before = fs::read_to_string(&member).unwrap();
+    let skipped = fixer.apply(&remediation, false).unwrap();
+    assert_eq!(skipped, [member.canonicalize().unwrap()]);
+    assert_eq!(fs::read_to_string(&member).unwrap(), member_before);
+}
+
+#[test]
+fn fix_manifest_outside_workspace() {
+    let dir = tempfile::tempdir().unwrap();
+    write_workspace(dir.path());
+
+    let remediation = Remediation {
+        package: "multi".parse().unwrap(),
+        from: "1.4.0".parse().unwrap(),
+        to: "1.4.2".parse().unwrap(),
+        breaking: false,
+        yanked: vec![],
+    };
+    let standalone = "[package]\nname = \"standalone\"\nversion = \"0.1.0\"\n\n[dependencies]\nmulti = \"1.4\"\n";
+
+    // Crates which are excluded from the workspace, or nested in its directory
+    // without being a member, are fixed on their own
+    let root = dir.path().join("Cargo.toml");
+    let root_manifest = fs::read_to_string(&root).unwrap().replace(
+        "members = [\"crates/*\"]",
+        "members = [\"crates/*\"]\nexclude = [\"crates/excluded\"]",
+    );
+    fs::write(&root, root_manifest).unwrap();
+
+    for path in ["crates/excluded", "tools/standalone"] {
+        let manifest = dir.path().join(path).join("Cargo.toml");
+        fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+        fs::write(&manifest, standalone).unwrap();
+
+        let mut fixer = Fixer::new(&manifest).unwrap();
+        assert!(fixer.apply(&remediation, false).unwrap().is_empty());
+        assert!(fs::read_to_string(&manifest)
+            .unwrap()
+            .contains("multi = \"1.4.2\""));
+    }
+}
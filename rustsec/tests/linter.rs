@@ -99,3 +99,133 @@ fn invalid_example() {
     let invalid_section = lint.errors()[6].to_string();
     assert_eq!(invalid_section, "invalid key `invalid-section` in toplevel");
 }
+
+/// Related advisories must have IDs of a known type
+#[test]
+fn unknown_related_id() {
+    let advisory = std::fs::read_to_string(EXAMPLE_ADVISORY_PATH)
+        .unwrap()
+        .replace(
+        "aliases = [\"CVE-2001-2101\"]",
+        "aliases = [\"CVE-2001-2101\"]\nrelated = [\"RUSTSEC-2001-0001\", \"LULZSEC-2001-0001\"]",
+    );
+
+    let lint = rustsec::advisory::Linter::lint_string(&advisory).unwrap();
+    assert_eq!(lint.errors().len(), 1);
+    assert_eq!(
+        lint.errors()[0].to_string(),
+        "invalid value `\"LULZSEC-2001-0001\"` for key `related` in [advisory]: unknown advisory ID type"
+    );
+}
+
+/// Lint the example advisory after replacing `from` with `to` in it
+fn lint_example(from: &str, to: &str) -> Vec<rustsec::advisory::LintIssue> {
+    let advisory = std::fs::read_to_string(EXAMPLE_ADVISORY_PATH).unwrap();
+    assert!(advisory.contains(from));
+    rustsec::advisory::lint(&advisory.replace(from, to))
+}
+
+/// Messages of the issues of the given severity
+fn messages(
+    issues: &[rustsec::advisory::LintIssue],
+    severity: rustsec::advisory::LintSeverity,
+) -> Vec<&str> {
+    issues
+        .iter()
+        .filter(|issue| issue.severity() == severity)
+        .map(|issue| issue.message())
+        .collect()
+}
+
+#[test]
+fn lint_valid_advisory() {
+    let advisory = std::fs::read_to_string(EXAMPLE_ADVISORY_PATH).unwrap();
+    assert_eq!(rustsec::advisory::lint(&advisory), []);
+}
+
+#[test]
+fn lint_missing_fields() {
+    let issues = lint_example("package = \"base\"\n", "");
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].is_error());
+    assert_eq!(issues[0].message(), "missing required field `package`");
+    assert_eq!(
+        issues[0].to_string(),
+        "error: missing required field `package`"
+    );
+
+    let issues = lint_example("[versions]\npatched = [\">= 1.2.3\"]\n", "[versions]\n");
+    assert_eq!(
+        messages(&issues, rustsec::advisory::LintSeverity::Error),
+        ["missing required field `patched`"]
+    );
+}
+
+#[test]
+fn lint_invalid_date() {
+    let issues = lint_example("date = \"2001-02-03\"", "date = \"2001-13-03\"");
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].is_error());
+    assert!(issues[0].message().starts_with("invalid date `2001-13-03`"));
+}
+
+#[test]
+fn lint_invalid_version_ranges() {
+    let issues = lint_example(
+        "patched = [\">= 1.2.3\"]",
+        "patched = [\">= 1.2.3\", \"1.2.x.4\"]",
+    );
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].is_error());
+    assert!(issues[0]
+        .message()
+        .starts_with("`patched` range `1.2.x.4` isn't a valid semver requirement"));
+
+    let issues = lint_example("unaffected = [\"0.1.2\"]", "unaffected = [\"^0.1\", 2]");
+    assert_eq!(
+        messages(&issues, rustsec::advisory::LintSeverity::Error),
+        ["`unaffected` ranges must be strings"]
+    );
+}
+
+#[test]
+fn lint_missing_description() {
+    let issues = lint_example("You have no chance to survive. Make your time.", "");
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].severity(),
+        rustsec::advisory::LintSeverity::Warning
+    );
+    assert_eq!(issues[0].message(), "advisory has no description");
+}
+
+#[test]
+fn lint_informational_with_cvss() {
+    let issues = lint_example(
+        "aliases = [\"CVE-2001-2101\"]",
+        "aliases = [\"CVE-2001-2101\"]\ninformational = \"unsound\"",
+    );
+    assert_eq!(
+        messages(&issues, rustsec::advisory::LintSeverity::Warning),
+        ["informational advisories shouldn't have a CVSS score"]
+    );
+    assert!(!issues.iter().any(|issue| issue.is_error()));
+
+    let issues = lint_example(
+        "cvss = \"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H\"",
+        "informational = \"unsound\"",
+    );
+    assert_eq!(issues, []);
+}
+
+#[test]
+fn lint_unparseable_advisory() {
+    let issues = rustsec::advisory::lint("[advisory]\nid = \"RUSTSEC-2001-2101\"\n");
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].message().contains("unexpected start of advisory"));
+
+    // Problems the lint rules don't cover are reported from parsing
+    let issues = lint_example("categories = [", "keywords = 1\ncategories = [");
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].is_error());
+}
@@ -0,0 +1,226 @@
+//! Tests for converting advisories from OSV format
+#![cfg(feature = "osv-import")]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use rustsec::{advisory::ReferenceKind, osv, Advisory, ErrorKind};
+use std::fs;
+
+/// Load the OSV export of the example advisory
+fn load_osv() -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string("./tests/support/osv/RUSTSEC-2001-2101.json").unwrap())
+        .unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let original = Advisory::load_file("./tests/support/example_advisory_v3.md").unwrap();
+    let imported = osv::from_osv(&load_osv()).unwrap();
+
+    let (expected, actual) = (&original.metadata, &imported.metadata);
+    assert_eq!(actual.id, expected.id);
+    assert_eq!(actual.package, expected.package);
+    assert_eq!(actual.title, expected.title);
+    assert_eq!(actual.description, expected.description);
+    assert_eq!(actual.date, expected.date);
+    assert_eq!(actual.aliases, expected.aliases);
+    assert_eq!(actual.categories, expected.categories);
+    assert_eq!(actual.cvss, expected.cvss);
+    assert_eq!(actual.license, expected.license);
+    assert_eq!(actual.withdrawn, None);
+    assert!(actual.references.contains(
+        &"https://www.youtube.com/watch?v=jQE66WA2s-A"
+            .parse()
+            .unwrap()
+    ));
+
+    let (expected, actual) = (
+        original.affected.as_ref().unwrap(),
+        imported.affected.as_ref().unwrap(),
+    );
+    assert_eq!(actual.arch, expected.arch);
+    assert_eq!(actual.os, expected.os);
+    assert!(actual.functions.keys().eq(expected.functions.keys()));
+
+    // The version requirements differ textually, but cover the same versions
+    for version in [
+        "0.0.1", "0.1.1", "0.1.2", "0.1.9", "0.2.0", "1.2.2", "1.2.3", "2.0.0",
+    ] {
+        let version = version.parse().unwrap();
+        assert_eq!(
+            imported.versions.is_affected(&version),
+            original.versions.is_affected(&version),
+            "{version}"
+        );
+    }
+}
+
+#[test]
+fn last_affected_and_open_ranges() {
+    let mut osv = load_osv();
+    osv["affected"][0]["ranges"] = serde_json::json!([
+        {
+            "type": "ECOSYSTEM",
+            "events": [
+                { "introduced": "0.3.0" },
+                { "last_affected": "0.4.1" },
+                { "introduced": "1.0.0" }
+            ]
+        }
+    ]);
+
+    let versions = osv::from_osv(&osv).unwrap().versions;
+    for (version, affected) in [
+        ("0.2.9", false),
+        ("0.3.0", true),
+        ("0.4.1", true),
+        ("0.4.2", false),
+        ("1.0.0", true),
+        ("3.0.0", true),
+    ] {
+        assert_eq!(
+            versions.is_affected(&version.parse().unwrap()),
+            affected,
+            "{version}"
+        );
+    }
+}
+
+#[test]
+fn duplicate_and_overlapping_ranges() {
+    // The same ranges may be listed both as `SEMVER` and `ECOSYSTEM` ranges
+    let events = serde_json::json!([
+        { "introduced": "0" },
+        { "fixed": "1.0.0" },
+        { "introduced": "1.0.0" },
+        { "fixed": "1.2.0" },
+        { "introduced": "2.0.0" },
+        { "fixed": "2.1.0" }
+    ]);
+    let mut osv = load_osv();
+    osv["affected"][0]["ranges"] = serde_json::json!([
+        { "type": "SEMVER", "events": events },
+        { "type": "ECOSYSTEM", "events": events }
+    ]);
+
+    let versions = osv::from_osv(&osv).unwrap().versions;
+    assert_eq!(versions.patched().len(), 2);
+    for (version, affected) in [
+        ("0.1.0", true),
+        ("1.1.0", true),
+        ("1.2.0", false),
+        ("2.0.0", true),
+        ("2.1.0", false),
+    ] {
+        assert_eq!(
+            versions.is_affected(&version.parse().unwrap()),
+            affected,
+            "{version}"
+        );
+    }
+
+    // Ranges which differ but overlap can't be converted
+    for events in [
+        serde_json::json!([{ "introduced": "0" }, { "fixed": "1.5.0" }]),
+        serde_json::json!([{ "introduced": "0.0.0-0" }, { "fixed": "2.0.0" }]),
+        serde_json::json!([{ "introduced": "0.5.0" }, { "fixed": "3.0.0" }]),
+    ] {
+        osv["affected"][0]["ranges"] = serde_json::json!([
+            { "type": "SEMVER", "events": [{ "introduced": "0" }, { "fixed": "1.0.0" }] },
+            { "type": "ECOSYSTEM", "events": events }
+        ]);
+
+        let err = osv::from_osv(&osv).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BadParam, "{events}");
+    }
+}
+
+#[test]
+fn timestamps() {
+    // Dates without a time of day are exported as noon UTC
+    let mut osv = load_osv();
+    let imported = osv::from_osv(&osv).unwrap();
+    assert_eq!(imported.metadata.date.as_str(), "2001-02-03");
+    assert_eq!(imported.metadata.date.time(), None);
+
+    let timestamp = "2001-02-03T04:05:06.789+01:00";
+    osv["published"] = timestamp.into();
+    osv["withdrawn"] = "2001-02-04T00:00:00Z".into();
+
+    let imported = osv::from_osv(&osv).unwrap();
+    assert_eq!(imported.metadata.date.as_str(), timestamp);
+    assert_eq!(imported.metadata.date.time(), Some("04:05:06.789+01:00"));
+    assert_eq!(
+        imported.metadata.withdrawn.as_ref().unwrap().as_str(),
+        "2001-02-04T00:00:00Z"
+    );
+
+    // The timestamp survives being written to an advisory file
+    let toml = toml::to_string(&imported.metadata).unwrap();
+    assert!(toml.contains(&format!("date = \"{timestamp}\"")), "{toml}");
+
+    osv["published"] = "2001-02-03T25:00:00Z".into();
+    assert_eq!(osv::from_osv(&osv).unwrap_err().kind(), ErrorKind::Parse);
+}
+
+#[test]
+fn cvss_v4() {
+    let vector = "CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N";
+    let mut osv = load_osv();
+    osv["severity"] = serde_json::json!([{ "type": "CVSS_V4", "score": vector }]);
+
+    let cvss = osv::from_osv(&osv).unwrap().metadata.cvss.unwrap();
+    assert_eq!(cvss.to_string(), vector);
+    assert_eq!(cvss.severity(), rustsec::advisory::Severity::Critical);
+}
+
+#[test]
+fn reference_types() {
+    let mut osv = load_osv();
+    osv["references"] = serde_json::json!([
+        { "type": "FIX", "url": "https://github.com/example/base/pull/2" },
+        { "type": "ADVISORY", "url": "https://example.com/base" },
+        { "type": "DISCUSSION", "url": "https://example.com/forum" }
+    ]);
+
+    let references = osv::from_osv(&osv).unwrap().metadata.references;
+    let kinds: Vec<_> = references.iter().map(|reference| reference.kind).collect();
+
+    // Types RustSec doesn't distinguish become web pages
+    assert_eq!(
+        kinds,
+        [
+            ReferenceKind::Fix,
+            ReferenceKind::Advisory,
+            ReferenceKind::Web
+        ]
+    );
+}
+
+#[test]
+fn unsupported_ecosystem() {
+    let mut osv = load_osv();
+    osv["affected"][0]["package"]["ecosystem"] = "npm".into();
+
+    let err = osv::from_osv(&osv).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BadParam);
+    assert!(err.to_string().contains("npm"));
+}
+
+#[test]
+fn unsupported_range_type() {
+    let mut osv = load_osv();
+    osv["affected"][0]["ranges"][0]["type"] = "GIT".into();
+
+    let err = osv::from_osv(&osv).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BadParam);
+    assert!(err.to_string().contains("GIT"));
+}
+
+#[test]
+fn malformed() {
+    let mut osv = load_osv();
+    osv.as_object_mut().unwrap().remove("id");
+
+    let err = osv::from_osv(&osv).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Parse);
+}
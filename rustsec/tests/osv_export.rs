@@ -0,0 +1,317 @@
+//! Tests for exporting advisories to OSV format
+#![cfg(feature = "osv-export")]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use rustsec::{
+    osv::{self, OsvAdvisory},
+    repository::git::{GitModificationTimes, GitPath, Repository},
+    Advisory, Database,
+};
+use std::{fs, path::Path};
+
+mod support;
+
+/// Commit date of the advisories, which becomes their modification time
+const COMMIT_DATE: &str = "2001-02-03T12:00:00Z";
+
+/// Run `git` in the given directory, panicking if it fails
+fn git(dir: &Path, args: &[&str]) {
+    support::git_with_env(
+        dir,
+        args,
+        &[
+            ("GIT_AUTHOR_DATE", COMMIT_DATE),
+            ("GIT_COMMITTER_DATE", COMMIT_DATE),
+        ],
+    );
+}
+
+/// Export the given advisory files after committing them to a repository
+/// at the given paths
+fn export(advisories: &[(&str, &str)]) -> Vec<serde_json::Value> {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+
+    for (source, path) in advisories {
+        let target = dir.path().join(path);
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::copy(source, target).unwrap();
+    }
+
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "Add advisories"]);
+
+    let repo = Repository::open(dir.path()).unwrap();
+    let mod_times = GitModificationTimes::new(&repo).unwrap();
+
+    advisories
+        .iter()
+        .map(|(_, path)| {
+            let advisory = Advisory::load_file(dir.path().join(path)).unwrap();
+            let path = Path::new(path);
+            let osv =
+                OsvAdvisory::from_rustsec(advisory, &mod_times, GitPath::new(&repo, path).unwrap());
+            serde_json::to_value(osv).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn export_snapshot() {
+    let exported = export(&[
+        (
+            "./tests/support/example_advisory_v3.md",
+            "crates/base/RUSTSEC-2001-2101.md",
+        ),
+        (
+            "./tests/support/advisory-db/crates/abandoned/RUSTSEC-2005-0001.md",
+            "crates/abandoned/RUSTSEC-2005-0001.md",
+        ),
+    ]);
+
+    let expected: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string("./tests/support/osv/RUSTSEC-2001-2101.json").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        exported[0],
+        expected,
+        "{}",
+        serde_json::to_string_pretty(&exported[0]).unwrap()
+    );
+    assert_eq!(
+        exported[0]["severity"][0]["score"],
+        "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"
+    );
+
+    // Informational advisories without a CVSS vector
+    let informational = &exported[1];
+    assert!(informational.get("severity").is_none());
+    assert_eq!(
+        informational["database_specific"]["informational"],
+        "unmaintained"
+    );
+}
+
+#[test]
+fn export_timestamps() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("RUSTSEC-2001-2101.md");
+    fs::write(
+        &source,
+        fs::read_to_string("./tests/support/example_advisory_v3.md")
+            .unwrap()
+            .replace(
+                "date = \"2001-02-03\"",
+                "date = \"2001-02-03T04:05:06-07:00\"",
+            ),
+    )
+    .unwrap();
+
+    let exported = export(&[
+        (source.to_str().unwrap(), "crates/base/RUSTSEC-2001-2101.md"),
+        (
+            "./tests/support/example_advisory_v3.md",
+            "crates/base/RUSTSEC-2001-2102.md",
+        ),
+    ]);
+
+    // Full timestamps are exported as they are, and dates as noon UTC
+    assert_eq!(exported[0]["published"], "2001-02-03T04:05:06-07:00");
+    assert_eq!(exported[1]["published"], "2001-02-03T12:00:00Z");
+}
+
+/// Export the example advisory with the given `[versions]` table, returning
+/// the exported `affected[].ranges`
+fn export_ranges(versions: &str) -> serde_json::Value {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("RUSTSEC-2001-2101.md");
+    fs::write(
+        &source,
+        fs::read_to_string("./tests/support/example_advisory_v3.md")
+            .unwrap()
+            .replace(
+                "patched = [\">= 1.2.3\"]\nunaffected = [\"0.1.2\"]",
+                versions,
+            ),
+    )
+    .unwrap();
+
+    let exported = export(&[(source.to_str().unwrap(), "crates/base/RUSTSEC-2001-2101.md")]);
+    exported[0]["affected"][0]["ranges"].clone()
+}
+
+#[test]
+fn export_single_range() {
+    assert_eq!(
+        export_ranges("patched = [\">= 1.2.3\"]"),
+        serde_json::json!([{
+            "type": "SEMVER",
+            "events": [{ "introduced": "0" }, { "fixed": "1.2.3" }],
+        }])
+    );
+
+    // Without a patched version, everything after "introduced" is affected
+    assert_eq!(
+        export_ranges("patched = []\nunaffected = [\"< 0.2.0\"]"),
+        serde_json::json!([{
+            "type": "SEMVER",
+            "events": [{ "introduced": "0.2.0" }],
+        }])
+    );
+}
+
+#[test]
+fn export_multiple_ranges() {
+    assert_eq!(
+        export_ranges("patched = [\">= 1.2.3\"]\nunaffected = [\"> 0.1.2, < 0.2.0\"]"),
+        serde_json::json!([
+            {
+                "type": "SEMVER",
+                "events": [{ "introduced": "0" }, { "last_affected": "0.1.2" }],
+            },
+            {
+                "type": "SEMVER",
+                "events": [{ "introduced": "0.2.0" }, { "fixed": "1.2.3" }],
+            },
+        ])
+    );
+}
+
+/// Ranges following `unaffected` versions are introduced at the first
+/// affected release rather than at a pre-release
+#[test]
+fn export_introduced_after_unaffected() {
+    assert_eq!(
+        export_ranges("patched = [\">= 1.2.3\"]\nunaffected = [\"<= 0.1.9\"]"),
+        serde_json::json!([{
+            "type": "SEMVER",
+            "events": [{ "introduced": "0.1.10" }, { "fixed": "1.2.3" }],
+        }])
+    );
+
+    assert_eq!(
+        export_ranges("patched = [\"^1.2.3\"]\nunaffected = [\"< 0.1.0\", \"~0.3.1\"]"),
+        serde_json::json!([
+            {
+                "type": "SEMVER",
+                "events": [{ "introduced": "0.1.0" }, { "fixed": "0.3.1" }],
+            },
+            {
+                "type": "SEMVER",
+                "events": [{ "introduced": "0.4.0" }, { "fixed": "1.2.3" }],
+            },
+            {
+                "type": "SEMVER",
+                "events": [{ "introduced": "2.0.0" }],
+            },
+        ])
+    );
+}
+
+/// Copy the fixture database into a new git repository
+fn fixture_repository() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+
+    let fixture = Path::new("./tests/support/advisory-db/crates");
+    for package in fs::read_dir(fixture).unwrap() {
+        let package = package.unwrap();
+        let target = dir.path().join("crates").join(package.file_name());
+        fs::create_dir_all(&target).unwrap();
+
+        for advisory in fs::read_dir(package.path()).unwrap() {
+            let advisory = advisory.unwrap();
+            fs::copy(advisory.path(), target.join(advisory.file_name())).unwrap();
+        }
+    }
+
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "Add advisories"]);
+    dir
+}
+
+fn read_json(path: &Path) -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn export_database() {
+    let repo = fixture_repository();
+    let db = Database::open(repo.path()).unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let out_dir = out.path().join("osv");
+    let stale = out_dir.join("RUSTSEC-2000-0001.json");
+
+    osv::export_all(&db, &out_dir).unwrap();
+    fs::write(&stale, "{}").unwrap();
+    fs::write(out_dir.join("README.md"), "OSV feed").unwrap();
+
+    let index = read_json(&out_dir.join("index.json"));
+    let ids = [
+        "RUSTSEC-2001-2101",
+        "RUSTSEC-2002-0001",
+        "RUSTSEC-2003-0001",
+        "RUSTSEC-2004-0001",
+        "RUSTSEC-2005-0001",
+    ];
+    let expected: Vec<_> = ids
+        .iter()
+        .map(|id| serde_json::json!({ "id": id, "modified": COMMIT_DATE }))
+        .collect();
+    assert_eq!(index, serde_json::Value::Array(expected));
+
+    for id in ids {
+        let advisory = read_json(&out_dir.join(format!("{id}.json")));
+        assert_eq!(advisory["id"], id);
+    }
+
+    // Withdrawn advisories are exported as such
+    let withdrawn = read_json(&out_dir.join("RUSTSEC-2004-0001.json"));
+    assert_eq!(withdrawn["withdrawn"], "2004-02-01T12:00:00Z");
+
+    // Files of advisories which aren't in the database anymore are only
+    // removed when pruning, and other files are left alone
+    osv::export_all(&db, &out_dir).unwrap();
+    assert!(stale.exists());
+
+    osv::export_all_and_prune(&db, &out_dir).unwrap();
+    assert!(!stale.exists());
+    assert!(out_dir.join("README.md").exists());
+    assert!(out_dir.join("index.json").exists());
+    assert!(out_dir.join("RUSTSEC-2001-2101.json").exists());
+}
+
+/// Advisories of collections unknown to this crate are exported as well
+#[test]
+fn export_other_collection() {
+    let repo = fixture_repository();
+    let package_dir = repo.path().join("tooling").join("rustup");
+    fs::create_dir_all(&package_dir).unwrap();
+    fs::write(
+        package_dir.join("RUSTSEC-2001-0003.md"),
+        "```toml\n[advisory]\nid = \"RUSTSEC-2001-0003\"\npackage = \"rustup\"\ndate = \"2001-01-01\"\n\n[versions]\npatched = []\n```\n\n# Vulnerability in rustup\n\nDetails.\n",
+    )
+    .unwrap();
+    git(repo.path(), &["add", "."]);
+    git(repo.path(), &["commit", "-q", "-m", "Add tooling advisory"]);
+
+    let db = Database::open_with_collections(repo.path(), &["crates", "tooling"]).unwrap();
+    let out = tempfile::tempdir().unwrap();
+    osv::export_all(&db, out.path()).unwrap();
+
+    let advisory = read_json(&out.path().join("RUSTSEC-2001-0003.json"));
+    assert_eq!(advisory["id"], "RUSTSEC-2001-0003");
+    assert_eq!(advisory["modified"], COMMIT_DATE);
+}
+
+#[test]
+fn export_requires_git_repository() {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    // The fixture database is part of this repository, but not at its root
+    let err = osv::export_all(&db, out.path()).unwrap_err();
+    assert_eq!(err.kind(), rustsec::ErrorKind::Repo);
+}
@@ -0,0 +1,60 @@
+//! Tests for looking up crates in a git registry index from several threads at once
+#![cfg(feature = "parallel-index")]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use rustsec::{registry::CachedIndex, Lockfile};
+use std::time::Duration;
+
+mod support;
+
+/// Number of crates in the index, every other one of which is yanked
+const CRATES: usize = 500;
+
+/// Parallel lookups find the same yanked crates as serial ones
+#[test]
+fn parallel_lookups_match_serial_lookups() {
+    // Every other crate has its first version yanked
+    let names: Vec<_> = (0..CRATES).map(|n| format!("crate-{n:03}")).collect();
+    let versions: Vec<_> = (0..CRATES)
+        .map(|n| [("1.0.0", n % 2 == 1), ("1.0.1", false)])
+        .collect();
+    let crates: Vec<_> = names
+        .iter()
+        .zip(&versions)
+        .map(|(name, versions)| (name.as_str(), &versions[..]))
+        .collect();
+    let index_dir = tempfile::tempdir().unwrap();
+    let url = support::create_git_index(index_dir.path(), &crates);
+
+    let mut lockfile = String::from("version = 3\n");
+    for n in 0..CRATES {
+        lockfile.push_str(&format!(
+            "\n[[package]]\nname = \"crate-{n:03}\"\nversion = \"1.0.0\"\nsource = \"registry+{url}\"\n"
+        ));
+    }
+    let lockfile: Lockfile = lockfile.parse().unwrap();
+
+    let find_yanked = |parallel: bool| -> Vec<String> {
+        // Start from an empty cache directory, so that no lookup is answered
+        // from the local cache of an earlier one
+        let cache_dir = tempfile::tempdir().unwrap();
+        let mut index = CachedIndex::builder()
+            .url(&url)
+            .cache_dir(cache_dir.path())
+            .fetch(Duration::from_secs(0))
+            .unwrap();
+        index.set_parallel_git_lookups(parallel);
+
+        index
+            .find_yanked(&lockfile.packages)
+            .into_iter()
+            .map(|res| res.unwrap().name.to_string())
+            .collect()
+    };
+
+    let serial = find_yanked(false);
+    assert_eq!(serial.len(), CRATES / 2);
+    assert_eq!(serial[0], "crate-001");
+
+    assert_eq!(find_yanked(true), serial);
+}
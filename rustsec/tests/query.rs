@@ -2,13 +2,39 @@
 
 #![warn(rust_2018_idioms, unused_qualifications)]
 
-use rustsec::{advisory::Severity, database::Query, package};
+use platforms::{
+    target::{Arch, OS},
+    Platform,
+};
+use rustsec::{
+    advisory::{Cwe, Date, Informational, Severity},
+    database::{Query, QueryBuilder},
+    package::{self, Package},
+    Database, SourceId,
+};
+use std::{fs, path::Path};
 
 /// Load example advisory from the filesystem
 fn load_advisory() -> rustsec::Advisory {
     rustsec::Advisory::load_file("./tests/support/example_advisory_v3.md").unwrap()
 }
 
+/// Load the fixture database from the filesystem
+fn load_database() -> Database {
+    Database::open(Path::new("./tests/support/advisory-db")).unwrap()
+}
+
+/// IDs of the advisories in the fixture database which match the given query
+fn query_ids(query: &Query) -> Vec<String> {
+    let mut ids: Vec<_> = load_database()
+        .query(query)
+        .into_iter()
+        .map(|advisory| advisory.id().to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
 #[test]
 fn matches_name() {
     let advisory = load_advisory();
@@ -40,3 +66,593 @@ fn matches_severity() {
     let query_matches = Query::new().severity(Severity::Critical);
     assert!(query_matches.matches(&advisory));
 }
+
+#[test]
+fn matches_cwe() {
+    assert_eq!(query_ids(&Query::default().cwe(416)), ["RUSTSEC-2002-0001"]);
+    assert_eq!(query_ids(&Query::default().cwe(825)), ["RUSTSEC-2002-0001"]);
+    assert_eq!(
+        query_ids(&Query::default().cwe(Cwe::new(94))),
+        ["RUSTSEC-2001-2101"]
+    );
+
+    // Advisories without CWE annotations never match
+    assert!(query_ids(&Query::default().cwe(20)).is_empty());
+    assert_eq!(query_ids(&Query::default()).len(), 3);
+}
+
+#[test]
+fn matches_affected_function() {
+    let advisory = load_advisory();
+    let functions: Vec<_> = advisory
+        .affected
+        .as_ref()
+        .unwrap()
+        .functions
+        .keys()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(functions, ["base::belongs::All"]);
+
+    // Exact path, module prefix, and methods of an affected type
+    for path in [
+        "base::belongs::All",
+        "base::belongs",
+        "base::belongs::All::new",
+    ] {
+        assert!(Query::new().affected_function(path).matches(&advisory));
+    }
+
+    // Prefixes only match whole segments
+    for path in [
+        "base::belongs::Al",
+        "base::bel",
+        "base::other",
+        "other::belongs::All",
+    ] {
+        assert!(!Query::new().affected_function(path).matches(&advisory));
+    }
+
+    // Advisories without affected functions always match
+    assert_eq!(
+        query_ids(&Query::default().affected_function("other::Thing::drop")),
+        ["RUSTSEC-2002-0001", "RUSTSEC-2003-0001"]
+    );
+    assert_eq!(
+        query_ids(&Query::default().affected_function("base::belongs")),
+        [
+            "RUSTSEC-2001-2101",
+            "RUSTSEC-2002-0001",
+            "RUSTSEC-2003-0001"
+        ]
+    );
+}
+
+#[test]
+fn excludes_withdrawn() {
+    let withdrawn = "RUSTSEC-2004-0001".to_owned();
+
+    assert!(!query_ids(&Query::crate_scope()).contains(&withdrawn));
+    assert!(!query_ids(&Query::default().cwe(416)).contains(&withdrawn));
+    assert_eq!(
+        query_ids(&Query::crate_scope().include_withdrawn(true)),
+        [
+            "RUSTSEC-2001-2101",
+            "RUSTSEC-2002-0001",
+            "RUSTSEC-2003-0001",
+            "RUSTSEC-2004-0001"
+        ]
+    );
+    assert_eq!(
+        query_ids(&Query::new().withdrawn(true)),
+        ["RUSTSEC-2004-0001"]
+    );
+}
+
+#[test]
+fn matches_date_range() {
+    let date = |date: &str| -> Date { date.parse().unwrap() };
+
+    assert_eq!(
+        query_ids(&Query::new().after(date("2002-01-01"))),
+        [
+            "RUSTSEC-2003-0001",
+            "RUSTSEC-2004-0001",
+            "RUSTSEC-2005-0001"
+        ]
+    );
+    assert_eq!(
+        query_ids(&Query::new().before(date("2003-01-01"))),
+        ["RUSTSEC-2001-2101", "RUSTSEC-2002-0001"]
+    );
+    assert_eq!(
+        query_ids(
+            &Query::new()
+                .after(date("2001-12-31"))
+                .before(date("2004-06-30"))
+        ),
+        [
+            "RUSTSEC-2002-0001",
+            "RUSTSEC-2003-0001",
+            "RUSTSEC-2004-0001"
+        ]
+    );
+
+    // The time of day is ignored
+    assert_eq!(
+        query_ids(&Query::new().after(date("2004-12-31T23:00:00-05:00"))),
+        ["RUSTSEC-2005-0001"]
+    );
+    assert!(query_ids(&Query::new().after(date("2005-01-01T00:00:00Z"))).is_empty());
+
+    // Withdrawn advisories are still excluded by the default scope
+    assert_eq!(
+        query_ids(&Query::crate_scope().after(date("2003-06-01"))),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        query_ids(
+            &Query::crate_scope()
+                .include_withdrawn(true)
+                .after(date("2003-06-01"))
+        ),
+        ["RUSTSEC-2004-0001"]
+    );
+
+    // Only withdrawn advisories have a date of withdrawal
+    assert_eq!(
+        query_ids(
+            &Query::new()
+                .by_withdrawn_date(true)
+                .after(date("2004-01-15"))
+        ),
+        ["RUSTSEC-2004-0001"]
+    );
+    assert!(query_ids(
+        &Query::new()
+            .by_withdrawn_date(true)
+            .after(date("2004-02-01"))
+    )
+    .is_empty());
+}
+
+#[test]
+fn find_by_alias() {
+    let db = load_database();
+
+    let mut ids: Vec<_> = db
+        .find_by_alias("CVE-2002-1234")
+        .into_iter()
+        .map(|advisory| advisory.id().to_string())
+        .collect();
+    ids.sort();
+    assert_eq!(ids, ["RUSTSEC-2002-0001", "RUSTSEC-2004-0001"]);
+
+    let ghsa = db.find_by_alias("GHSA-7p6r-xq2p-6mvh");
+    assert_eq!(ghsa.len(), 1);
+    assert_eq!(ghsa[0].id().as_str(), "RUSTSEC-2002-0001");
+
+    assert!(db.find_by_alias("CVE-1999-0001").is_empty());
+    // Advisories aren't aliases of themselves
+    assert!(db.find_by_alias("RUSTSEC-2002-0001").is_empty());
+}
+
+/// Write an advisory against the `multi` package to the given database
+fn write_advisory(db_dir: &Path, id: &str, extra: &str, patched: &str) {
+    let advisory = format!(
+        "```toml\n[advisory]\nid = \"{id}\"\npackage = \"multi\"\ndate = \"2020-01-01\"\n{extra}\n[versions]\npatched = [{patched}]\n```\n\n# Vulnerability in multi\n\nDetails.\n"
+    );
+
+    let dir = db_dir.join("crates").join("multi");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(format!("{}.md", id)), advisory).unwrap();
+}
+
+#[test]
+fn max_severity() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let medium = "cvss = \"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:L/I:N/A:N\"";
+    let high = "cvss = \"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N\"";
+    let critical = "cvss = \"CVSS:4.0/AV:N/AC:L/AT:N/PR:N/UI:N/VC:H/VI:H/VA:H/SC:N/SI:N/SA:N\"";
+
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0001", medium, "");
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0002", high, "\">= 1.0.0\"");
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0003", "", "");
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0004",
+        &format!("{critical}\nwithdrawn = \"2020-02-01\""),
+        "",
+    );
+
+    let db = Database::open(db_dir.path()).unwrap();
+    let package = |name: &str, version: &str| Package {
+        name: name.parse().unwrap(),
+        version: version.parse().unwrap(),
+        source: Some(SourceId::default()),
+        checksum: None,
+        dependencies: vec![],
+        replace: None,
+    };
+
+    // The unscored advisory doesn't mask the scored ones, and the withdrawn
+    // critical advisory is ignored
+    assert_eq!(
+        db.max_severity(&package("multi", "1.0.0")),
+        Some(Severity::Medium)
+    );
+    assert_eq!(
+        db.max_severity(&package("multi", "0.9.0")),
+        Some(Severity::High)
+    );
+    assert_eq!(db.max_severity(&package("other", "1.0.0")), None);
+
+    // Only unscored advisories affect the package
+    fs::remove_file(db_dir.path().join("crates/multi/RUSTSEC-2020-0001.md")).unwrap();
+    let db = Database::open(db_dir.path()).unwrap();
+    assert_eq!(db.max_severity(&package("multi", "1.0.0")), None);
+}
+
+#[test]
+fn matches_source() {
+    let db_dir = tempfile::tempdir().unwrap();
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0001", "", "");
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0002",
+        "source = \"registry+https://example.com/index\"",
+        "",
+    );
+
+    let db = Database::open(db_dir.path()).unwrap();
+    let advisory = db.get(&"RUSTSEC-2020-0001".parse().unwrap()).unwrap();
+    assert!(advisory.source().is_default_registry());
+    let advisory = db.get(&"RUSTSEC-2020-0002".parse().unwrap()).unwrap();
+    assert_eq!(
+        advisory.source().url().as_str(),
+        "https://example.com/index"
+    );
+
+    let query_ids = |source: &str| -> Vec<String> {
+        let package = Package {
+            name: "multi".parse().unwrap(),
+            version: "1.0.0".parse().unwrap(),
+            source: Some(source.parse().unwrap()),
+            checksum: None,
+            dependencies: vec![],
+            replace: None,
+        };
+
+        db.query(&Query::crate_scope().package(&package))
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect()
+    };
+
+    // crates.io is matched through either of its indexes
+    assert_eq!(
+        query_ids("registry+https://github.com/rust-lang/crates.io-index"),
+        ["RUSTSEC-2020-0001"]
+    );
+    assert_eq!(
+        query_ids("sparse+https://index.crates.io/"),
+        ["RUSTSEC-2020-0001"]
+    );
+
+    // Same-named packages from other sources only match their own advisories
+    assert_eq!(
+        query_ids("registry+https://example.com/index"),
+        ["RUSTSEC-2020-0002"]
+    );
+    assert!(query_ids("registry+https://other.example.com/index").is_empty());
+    assert!(query_ids("git+https://github.com/example/multi#0123456789abcdef").is_empty());
+}
+
+#[test]
+fn unknown_informational_kind() {
+    let db_dir = tempfile::tempdir().unwrap();
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0001",
+        "informational = \"deprecated\"",
+        "",
+    );
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0002", "", "");
+
+    // Unknown kinds are kept as-is instead of failing to load the database
+    let db = Database::open(db_dir.path()).unwrap();
+    let advisory = db.get(&"RUSTSEC-2020-0001".parse().unwrap()).unwrap();
+    let informational = advisory.metadata.informational.as_ref().unwrap();
+    assert_eq!(
+        informational,
+        &Informational::Other("deprecated".to_owned())
+    );
+    assert_eq!(informational.to_string(), "deprecated");
+    assert!(informational.warning_kind().is_none());
+
+    // The advisory is still informational, so it's excluded from the crate scope
+    let ids = |query: &Query| -> Vec<_> {
+        db.query(query)
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect()
+    };
+    assert_eq!(ids(&Query::crate_scope()), ["RUSTSEC-2020-0002"]);
+    assert_eq!(
+        ids(&Query::crate_scope().informational(true)),
+        ["RUSTSEC-2020-0001"]
+    );
+}
+
+#[test]
+fn matches_fixable() {
+    let db_dir = tempfile::tempdir().unwrap();
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0001", "", "\">= 1.2.3\"");
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0002",
+        "",
+        "\">= 2.0.0\", \"^1.9.1\"",
+    );
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0003", "", "");
+
+    let db = Database::open(db_dir.path()).unwrap();
+    let ids = |query: Query| -> Vec<_> {
+        let mut ids: Vec<_> = db
+            .query(&query)
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(
+        ids(Query::crate_scope().fixable(true)),
+        ["RUSTSEC-2020-0001", "RUSTSEC-2020-0002"]
+    );
+    assert_eq!(
+        ids(Query::crate_scope().unfixable(true)),
+        ["RUSTSEC-2020-0003"]
+    );
+    assert_eq!(
+        ids(Query::crate_scope().fixable(false)),
+        ids(Query::crate_scope().unfixable(true))
+    );
+    assert_eq!(ids(Query::crate_scope()).len(), 3);
+
+    // Combined with the package version, only advisories affecting it match
+    let version = "1.0.0".parse().unwrap();
+    assert_eq!(
+        ids(Query::crate_scope().package_version(version).fixable(true)),
+        ["RUSTSEC-2020-0001", "RUSTSEC-2020-0002"]
+    );
+    let version = "1.5.0".parse().unwrap();
+    assert_eq!(
+        ids(Query::crate_scope().package_version(version).fixable(true)),
+        ["RUSTSEC-2020-0002"]
+    );
+}
+
+#[test]
+fn matches_prereleases() {
+    let db_dir = tempfile::tempdir().unwrap();
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0001", "", "\">= 1.2.3\"");
+    let db = Database::open(db_dir.path()).unwrap();
+
+    let matches = |version: &str, include_prereleases: bool| {
+        let query = Query::crate_scope()
+            .package_version(version.parse().unwrap())
+            .include_prereleases(include_prereleases);
+        !db.query(&query).is_empty()
+    };
+
+    // Pre-releases of patched versions are patched unless excluded
+    assert!(!matches("1.3.0-beta", true));
+    assert!(matches("1.3.0-beta", false));
+
+    // Pre-releases of the patched version itself are never patched
+    assert!(matches("1.2.3-rc.1", true));
+    assert!(matches("1.2.3-rc.1", false));
+    assert!(!matches("1.2.3", false));
+}
+
+#[test]
+fn matches_target() {
+    let db_dir = tempfile::tempdir().unwrap();
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0001",
+        "\n[affected]\nos = [\"windows\"]",
+        "",
+    );
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0002",
+        "\n[affected]\narch = [\"x86_64\"]\nos = [\"linux\", \"windows\"]",
+        "",
+    );
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0003", "", "");
+
+    let db = Database::open(db_dir.path()).unwrap();
+    let ids = |query: Query| -> Vec<_> {
+        let mut ids: Vec<_> = db
+            .query(&query)
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+    let target = |triple: &str| Query::crate_scope().target(Platform::find(triple).unwrap());
+
+    // Windows-only advisories are filtered out when targeting Linux
+    assert_eq!(
+        ids(target("x86_64-unknown-linux-gnu")),
+        ["RUSTSEC-2020-0002", "RUSTSEC-2020-0003"]
+    );
+    assert_eq!(
+        ids(target("aarch64-unknown-linux-gnu")),
+        ["RUSTSEC-2020-0003"]
+    );
+    assert_eq!(
+        ids(target("x86_64-pc-windows-msvc")),
+        [
+            "RUSTSEC-2020-0001",
+            "RUSTSEC-2020-0002",
+            "RUSTSEC-2020-0003"
+        ]
+    );
+    assert_eq!(
+        ids(Query::crate_scope().target_os(OS::Windows)),
+        [
+            "RUSTSEC-2020-0001",
+            "RUSTSEC-2020-0002",
+            "RUSTSEC-2020-0003"
+        ]
+    );
+}
+
+#[test]
+fn matches_target_arch() {
+    let db_dir = tempfile::tempdir().unwrap();
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0001",
+        "\n[affected]\narch = [\"x86\", \"arm\"]",
+        "",
+    );
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0002", "", "");
+
+    let db = Database::open(db_dir.path()).unwrap();
+    let advisory = db.get(&"RUSTSEC-2020-0001".parse().unwrap()).unwrap();
+    assert_eq!(
+        advisory.affected.as_ref().unwrap().arch,
+        [Arch::X86, Arch::Arm]
+    );
+
+    let ids = |arch: Arch| -> Vec<_> {
+        let mut ids: Vec<_> = db
+            .query(&Query::crate_scope().target_arch(arch))
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    // Advisories without an arch restriction affect all architectures
+    assert_eq!(ids(Arch::X86), ["RUSTSEC-2020-0001", "RUSTSEC-2020-0002"]);
+    assert_eq!(ids(Arch::Arm), ["RUSTSEC-2020-0001", "RUSTSEC-2020-0002"]);
+    assert_eq!(ids(Arch::X86_64), ["RUSTSEC-2020-0002"]);
+}
+
+#[test]
+fn query_builder() {
+    // Without any filters, builders match their starting points
+    assert_eq!(
+        query_ids(&QueryBuilder::new().build()),
+        query_ids(&Query::new())
+    );
+    assert_eq!(
+        query_ids(
+            &QueryBuilder::crate_scope()
+                .severity(None)
+                .year(None)
+                .build()
+        ),
+        query_ids(&Query::crate_scope())
+    );
+
+    // Filters which are given match like the chained API
+    let query = QueryBuilder::crate_scope()
+        .package_name(Some("base".parse().unwrap()))
+        .severity(Some(Severity::High))
+        .target_os(None)
+        .build();
+    assert_eq!(query_ids(&query), ["RUSTSEC-2001-2101"]);
+    assert_eq!(
+        query_ids(&query),
+        query_ids(
+            &Query::crate_scope()
+                .package_name("base".parse().unwrap())
+                .severity(Severity::High)
+        )
+    );
+
+    let query = QueryBuilder::new()
+        .year(Some(2002))
+        .withdrawn(None)
+        .informational(None)
+        .build();
+    assert_eq!(query_ids(&query), ["RUSTSEC-2002-0001"]);
+
+    // Filters which aren't given don't clear those of the starting point
+    let query = QueryBuilder::crate_scope()
+        .collection(None)
+        .withdrawn(None)
+        .informational(None)
+        .build();
+    assert_eq!(query_ids(&query), query_ids(&Query::crate_scope()));
+
+    // Filters of an existing query can be cleared explicitly
+    let query = Query::crate_scope()
+        .year(2004)
+        .into_builder()
+        .clear_year()
+        .clear_withdrawn()
+        .build();
+    assert_eq!(
+        query_ids(&query),
+        query_ids(&Query::crate_scope().include_withdrawn(true))
+    );
+}
+
+#[test]
+fn related_advisories() {
+    let db_dir = tempfile::tempdir().unwrap();
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0001",
+        "related = [\"RUSTSEC-2020-0002\", \"CVE-2020-0003\"]",
+        "",
+    );
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0002", "", "");
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0003",
+        "aliases = [\"CVE-2020-0003\"]",
+        "",
+    );
+    write_advisory(
+        db_dir.path(),
+        "RUSTSEC-2020-0004",
+        "related = [\"RUSTSEC-2020-0002\"]",
+        "",
+    );
+    write_advisory(db_dir.path(), "RUSTSEC-2020-0005", "", "");
+
+    let db = Database::open(db_dir.path()).unwrap();
+    let related = |id: &str| -> Vec<_> {
+        let mut ids: Vec<_> = db
+            .related(&id.parse().unwrap())
+            .into_iter()
+            .map(|advisory| advisory.id().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    // Relations are found in both directions, and through aliases
+    assert_eq!(
+        related("RUSTSEC-2020-0001"),
+        ["RUSTSEC-2020-0002", "RUSTSEC-2020-0003"]
+    );
+    assert_eq!(
+        related("RUSTSEC-2020-0002"),
+        ["RUSTSEC-2020-0001", "RUSTSEC-2020-0004"]
+    );
+    assert_eq!(related("RUSTSEC-2020-0003"), ["RUSTSEC-2020-0001"]);
+    assert!(related("RUSTSEC-2020-0005").is_empty());
+    assert!(related("RUSTSEC-2020-0006").is_empty());
+}
@@ -0,0 +1,902 @@
+//! Tests for generating reports against the fixture advisory database
+
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use rustsec::{
+    advisory::{Id, Informational, Severity},
+    cvss, report, Database, Lockfile, Report, Warning, WarningKind,
+};
+use std::{fs, path::Path};
+
+/// Generate a report for the fixture lockfile against the fixture database
+fn generate_report(settings: &report::Settings) -> Report {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    Report::generate(&db, &load_lockfile(), settings)
+}
+
+fn load_lockfile() -> Lockfile {
+    Lockfile::load("./tests/support/advisory_db_cargo.lock").unwrap()
+}
+
+/// Report with vulnerabilities as well as warnings with and without an advisory
+fn full_report() -> Report {
+    let settings = report::Settings {
+        informational_warnings: vec![Informational::Unmaintained],
+        ..Default::default()
+    };
+    let mut report = generate_report(&settings);
+
+    let lockfile = load_lockfile();
+    let clean = lockfile
+        .packages
+        .iter()
+        .find(|pkg| pkg.name.as_str() == "clean")
+        .unwrap();
+    report
+        .warnings
+        .entry(WarningKind::Yanked)
+        .or_default()
+        .push(Warning::new(WarningKind::Yanked, clean, None, None, None));
+
+    report
+}
+
+/// Compare JSON output against a golden file in `tests/support`
+fn assert_json_matches_golden(actual: serde_json::Value, golden: &str) {
+    let path = Path::new("./tests/support").join(golden);
+    let expected: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(
+        actual,
+        expected,
+        "output differs from {}:\n{}",
+        path.display(),
+        serde_json::to_string_pretty(&actual).unwrap()
+    );
+}
+
+#[test]
+fn deterministic_serialization() {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let settings = report::Settings {
+        informational_warnings: vec![Informational::Unmaintained],
+        ..Default::default()
+    };
+
+    let mut lockfile = load_lockfile();
+    let report = Report::generate(&db, &lockfile, &settings);
+    let json = serde_json::to_string(&report).unwrap();
+    assert_eq!(serde_json::to_string(&report).unwrap(), json);
+
+    // The order of packages in the lockfile doesn't matter
+    lockfile.packages.reverse();
+    let mut reversed = Report::generate(&db, &lockfile, &settings);
+    assert_eq!(serde_json::to_string(&reversed).unwrap(), json);
+
+    let ids: Vec<_> = report
+        .vulnerabilities
+        .list
+        .iter()
+        .map(|vuln| vuln.advisory.id.to_string())
+        .collect();
+    assert_eq!(ids, ["RUSTSEC-2001-2101", "RUSTSEC-2002-0001"]);
+
+    // Yanked packages are sorted among the existing warnings
+    let yanked: Vec<_> = lockfile
+        .packages
+        .iter()
+        .filter(|pkg| ["base", "clean"].contains(&pkg.name.as_str()))
+        .collect();
+    reversed.add_yanked(yanked);
+    let names: Vec<_> = reversed
+        .yanked()
+        .iter()
+        .map(|warning| warning.package.name.as_str())
+        .collect();
+    assert_eq!(names, ["base", "clean"]);
+}
+
+#[test]
+fn withdrawn_advisories_are_not_reported() {
+    let report = generate_report(&report::Settings::default());
+
+    let mut ids: Vec<_> = report
+        .vulnerabilities
+        .list
+        .iter()
+        .map(|vuln| vuln.advisory.id.to_string())
+        .collect();
+    ids.sort();
+
+    assert_eq!(ids, ["RUSTSEC-2001-2101", "RUSTSEC-2002-0001"]);
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn yanked_and_vulnerable() {
+    let lockfile = load_lockfile();
+    let mut report = generate_report(&report::Settings::default());
+    let base = lockfile
+        .packages
+        .iter()
+        .find(|pkg| pkg.name.as_str() == "base")
+        .unwrap();
+    let clean = lockfile
+        .packages
+        .iter()
+        .find(|pkg| pkg.name.as_str() == "clean")
+        .unwrap();
+    report.add_yanked([base, clean]);
+
+    // Yanked packages are listed separately from vulnerabilities, and a
+    // package which is both is listed in both
+    let yanked: Vec<_> = report
+        .yanked()
+        .iter()
+        .map(|warning| warning.package.name.as_str())
+        .collect();
+    assert_eq!(yanked, ["base", "clean"]);
+    assert!(report
+        .vulnerabilities
+        .list
+        .iter()
+        .any(|vuln| vuln.package.name.as_str() == "base"));
+    assert!(!report
+        .vulnerabilities
+        .list
+        .iter()
+        .any(|vuln| vuln.package.name.as_str() == "clean"));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["warnings"]["yanked"][0]["package"]["name"], "base");
+    assert_eq!(json["warnings"]["yanked"][1]["package"]["name"], "clean");
+    assert_eq!(json["vulnerabilities"]["count"], 2);
+}
+
+#[test]
+fn sarif() {
+    let sarif = report::sarif::to_sarif(&full_report());
+    assert_json_matches_golden(serde_json::to_value(sarif).unwrap(), "report.sarif");
+}
+
+#[test]
+fn osv_scan() {
+    let scan = report::to_osv_scan(&full_report());
+    assert_json_matches_golden(serde_json::to_value(scan).unwrap(), "report.osv-scan.json");
+
+    // Without vulnerabilities, no source is listed
+    let report = generate_report(&report::Settings {
+        ignore: vec![
+            "RUSTSEC-2001-2101".parse::<Id>().unwrap().into(),
+            "RUSTSEC-2002-0001".parse::<Id>().unwrap().into(),
+        ],
+        ..Default::default()
+    });
+    assert!(report.vulnerabilities.list.is_empty());
+    assert_eq!(
+        serde_json::to_value(report::to_osv_scan(&report)).unwrap(),
+        serde_json::json!({ "results": [] })
+    );
+}
+
+/// Get the value of an attribute of the first occurrence of the given XML element
+fn xml_attribute<'a>(xml: &'a str, element: &str, attribute: &str) -> &'a str {
+    let start = xml.find(&format!("<{} ", element)).unwrap();
+    let tag = &xml[start..start + xml[start..].find('>').unwrap()];
+    let value_start = tag.find(&format!(" {}=\"", attribute)).unwrap() + attribute.len() + 3;
+    &tag[value_start..value_start + tag[value_start..].find('"').unwrap()]
+}
+
+#[test]
+fn junit() {
+    let mut report = full_report();
+    let xml = report::junit::to_junit(&report, &load_lockfile());
+    let golden = fs::read_to_string("./tests/support/report.junit.xml").unwrap();
+    assert_eq!(xml, golden);
+
+    // The suite's counters must agree with its test cases
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuite "));
+    assert!(xml.ends_with("</testsuite>\n"));
+    assert_eq!(
+        xml_attribute(&xml, "testsuite", "tests"),
+        xml.matches("<testcase ").count().to_string()
+    );
+    assert_eq!(xml_attribute(&xml, "testsuite", "failures"), "2");
+    assert_eq!(xml_attribute(&xml, "testsuite", "skipped"), "2");
+    assert_eq!(xml.matches("<skipped ").count(), 2);
+    assert_eq!(
+        xml.matches("<failure ").count(),
+        xml.matches("</failure>").count()
+    );
+
+    // Denied warnings fail their test case instead of skipping it
+    report.settings.deny_warnings = vec![WarningKind::Yanked];
+    let xml = report::junit::to_junit(&report, &load_lockfile());
+    assert_eq!(xml_attribute(&xml, "testsuite", "failures"), "3");
+    assert_eq!(xml_attribute(&xml, "testsuite", "skipped"), "1");
+    assert!(xml.contains("<failure type=\"yanked\" message=\"yanked package\"></failure>"));
+}
+
+#[cfg(feature = "dependency-tree")]
+#[test]
+fn dependency_paths() {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let lockfile = Lockfile::load("./tests/support/dependency_tree_cargo.lock").unwrap();
+    let report = Report::generate(&db, &lockfile, &report::Settings::default());
+
+    let paths = |package: &str| {
+        let vuln = report
+            .vulnerabilities
+            .list
+            .iter()
+            .find(|vuln| vuln.package.name.as_str() == package)
+            .unwrap();
+
+        vuln.paths
+            .iter()
+            .map(|path| path.iter().map(|name| name.as_str()).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        paths("base"),
+        [vec!["app", "middle", "base"], vec!["tool", "base"]]
+    );
+    assert_eq!(paths("other"), [vec!["app", "other"]]);
+}
+
+/// CVSS vectors of advisories at each severity, by the package they affect
+const SEVERITY_VECTORS: &[(&str, Option<&str>)] = &[
+    ("none", Some("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N")),
+    ("low", Some("CVSS:3.1/AV:P/AC:H/PR:H/UI:R/S:U/C:L/I:N/A:N")),
+    (
+        "medium",
+        Some("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:L/I:N/A:N"),
+    ),
+    ("high", Some("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:N/A:N")),
+    (
+        "critical",
+        Some("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+    ),
+    ("unscored", None),
+];
+
+/// Generate a report against a database with an advisory for every entry in
+/// `SEVERITY_VECTORS`, and return the packages which failed and were below threshold
+fn severity_report(settings: &report::Settings) -> (Vec<String>, Vec<String>) {
+    let db_dir = tempfile::tempdir().unwrap();
+    let mut lockfile = String::from("version = 3\n");
+
+    for (n, (package, cvss)) in SEVERITY_VECTORS.iter().enumerate() {
+        let id = format!("RUSTSEC-2020-{:04}", n + 1);
+        let cvss = cvss
+            .map(|v| format!("cvss = \"{}\"\n", v))
+            .unwrap_or_default();
+        let advisory = format!(
+            "```toml\n[advisory]\nid = \"{id}\"\npackage = \"{package}\"\ndate = \"2020-01-01\"\n{cvss}\n[versions]\npatched = []\n```\n\n# Vulnerability in {package}\n\nDetails.\n"
+        );
+
+        let dir = db_dir.path().join("crates").join(package);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(format!("{}.md", id)), advisory).unwrap();
+
+        lockfile.push_str(&format!(
+            "\n[[package]]\nname = \"{package}\"\nversion = \"1.0.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n"
+        ));
+    }
+
+    let db = Database::open(db_dir.path()).unwrap();
+    let lockfile: Lockfile = lockfile.parse().unwrap();
+    let report = Report::generate(&db, &lockfile, settings);
+
+    let names = |vulns: &[rustsec::Vulnerability]| {
+        let mut names: Vec<_> = vulns
+            .iter()
+            .map(|vuln| vuln.package.name.to_string())
+            .collect();
+        names.sort();
+        names
+    };
+
+    assert_eq!(
+        report.vulnerabilities.count,
+        report.vulnerabilities.list.len()
+    );
+    assert_eq!(
+        report.vulnerabilities.found,
+        !report.vulnerabilities.list.is_empty()
+    );
+
+    (
+        names(&report.vulnerabilities.list),
+        names(&report.vulnerabilities.below_threshold),
+    )
+}
+
+#[test]
+fn severity_threshold() {
+    // Without a threshold everything fails
+    let (failing, below) = severity_report(&report::Settings::default());
+    assert_eq!(failing.len(), SEVERITY_VECTORS.len());
+    assert!(below.is_empty());
+
+    let thresholds = [
+        (Severity::None, vec![]),
+        (Severity::Low, vec!["none"]),
+        (Severity::Medium, vec!["low", "none"]),
+        (Severity::High, vec!["low", "medium", "none"]),
+        (Severity::Critical, vec!["high", "low", "medium", "none"]),
+    ];
+
+    for (threshold, expected_below) in thresholds {
+        for exclude_unscored in [false, true] {
+            let settings = report::Settings {
+                severity_threshold: Some(threshold),
+                exclude_unscored,
+                ..Default::default()
+            };
+            let (failing, below) = severity_report(&settings);
+
+            let mut expected_below = expected_below.clone();
+            if exclude_unscored {
+                expected_below.push("unscored");
+            }
+
+            assert_eq!(below, expected_below, "threshold {}", threshold);
+            assert_eq!(
+                failing.len() + below.len(),
+                SEVERITY_VECTORS.len(),
+                "threshold {}",
+                threshold
+            );
+            assert_eq!(failing.contains(&"unscored".to_owned()), !exclude_unscored);
+        }
+    }
+}
+
+/// IDs of the vulnerabilities in a report
+fn vulnerability_ids(report: &Report) -> Vec<String> {
+    let mut ids: Vec<_> = report
+        .vulnerabilities
+        .list
+        .iter()
+        .map(|vuln| vuln.advisory.id.to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn ignore_with_expiry() {
+    let ignore = |id: &str, expires: Option<&str>| report::IgnoredAdvisory {
+        id: id.parse().unwrap(),
+        expires: expires.map(|date| date.parse().unwrap()),
+    };
+
+    // Never-expiring and active ignores suppress vulnerabilities
+    let report = generate_report(&report::Settings {
+        ignore: vec![
+            ignore("RUSTSEC-2001-2101", None),
+            ignore("RUSTSEC-2002-0001", Some("2099-12-31")),
+        ],
+        ..Default::default()
+    });
+    assert!(vulnerability_ids(&report).is_empty());
+    assert!(report.warnings.is_empty());
+
+    // Expired ignores don't, and result in a warning
+    let report = generate_report(&report::Settings {
+        ignore: vec![
+            ignore("RUSTSEC-2001-2101", None),
+            ignore("RUSTSEC-2002-0001", Some("2001-01-01")),
+        ],
+        ..Default::default()
+    });
+    assert_eq!(vulnerability_ids(&report), ["RUSTSEC-2002-0001"]);
+
+    let expired = &report.warnings[&WarningKind::IgnoreExpired];
+    assert_eq!(expired.len(), 1);
+    assert!(expired[0].is_ignore_expired());
+    assert_eq!(expired[0].package.name.as_str(), "other");
+    assert_eq!(
+        expired[0].advisory.as_ref().unwrap().id.as_str(),
+        "RUSTSEC-2002-0001"
+    );
+
+    // Ignores expiring at a point in time last through the day they expire on
+    let today: rustsec::advisory::Date = "2030-01-01".parse().unwrap();
+    assert!(!ignore("RUSTSEC-2002-0001", Some("2030-01-01T00:00:00Z")).is_expired(&today));
+    assert!(ignore("RUSTSEC-2002-0001", Some("2029-12-31T23:00:00Z")).is_expired(&today));
+    assert!(ignore("RUSTSEC-2002-0001", Some("2030-01-01T03:00:00+05:00")).is_expired(&today));
+}
+
+#[test]
+fn vulnerabilities_with_settings() {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let lockfile = load_lockfile();
+    let ids = |settings: &report::Settings| -> Vec<_> {
+        let mut ids: Vec<_> = db
+            .vulnerabilities_with_settings(&lockfile, settings)
+            .into_iter()
+            .map(|vuln| format!("{} {}", vuln.advisory.id, vuln.package.name))
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(
+        ids(&report::Settings::default()),
+        ["RUSTSEC-2001-2101 base", "RUSTSEC-2002-0001 other"]
+    );
+
+    let settings = report::Settings {
+        ignore: vec!["RUSTSEC-2002-0001".parse::<Id>().unwrap().into()],
+        ..Default::default()
+    };
+    assert_eq!(ids(&settings), ["RUSTSEC-2001-2101 base"]);
+}
+
+#[test]
+fn deny_warnings() {
+    // Only the unmaintained `abandoned` package is warned about, as the
+    // vulnerable packages are ignored
+    let report = |deny_warnings: &[WarningKind]| {
+        generate_report(&report::Settings {
+            ignore_package: vec![
+                "base".parse::<rustsec::package::Name>().unwrap().into(),
+                "other".parse::<rustsec::package::Name>().unwrap().into(),
+            ],
+            informational_warnings: vec![Informational::Unmaintained, Informational::Unsound],
+            deny_warnings: deny_warnings.to_vec(),
+            ..Default::default()
+        })
+    };
+
+    for (deny_warnings, failure) in [
+        (&[][..], false),
+        (&[WarningKind::Unsound][..], false),
+        (&[WarningKind::Unmaintained][..], true),
+        (&[WarningKind::Unmaintained, WarningKind::Unsound][..], true),
+    ] {
+        let report = report(deny_warnings);
+        assert!(!report.vulnerabilities.found);
+        assert_eq!(report.is_failure(), failure, "{deny_warnings:?}");
+        assert_eq!(
+            report.denied_warnings().count(),
+            usize::from(failure),
+            "{deny_warnings:?}"
+        );
+    }
+
+    // Unsound packages fail the audit while unmaintained ones only warn
+    let mut report = report(&[WarningKind::Unsound]);
+    let lockfile = load_lockfile();
+    let clean = lockfile
+        .packages
+        .iter()
+        .find(|pkg| pkg.name.as_str() == "clean")
+        .unwrap();
+    report
+        .warnings
+        .entry(WarningKind::Unsound)
+        .or_default()
+        .push(Warning::new(WarningKind::Unsound, clean, None, None, None));
+    assert!(report.is_failure());
+    assert_eq!(report.denied_warnings().next().unwrap().package, *clean);
+
+    // Vulnerabilities fail the audit regardless of denied warnings
+    assert!(generate_report(&report::Settings::default()).is_failure());
+}
+
+#[test]
+fn ignore_package() {
+    let ignore = |name: &str, req: Option<&str>| report::IgnoredPackage {
+        name: name.parse().unwrap(),
+        version: req.map(|req| req.parse().unwrap()),
+    };
+
+    // Whole packages are ignored, including their informational warnings
+    let report = generate_report(&report::Settings {
+        ignore_package: vec![ignore("base", None), ignore("abandoned", None)],
+        informational_warnings: vec![Informational::Unmaintained],
+        ..Default::default()
+    });
+    assert_eq!(vulnerability_ids(&report), ["RUSTSEC-2002-0001"]);
+    assert!(report.warnings.is_empty());
+
+    // Version-scoped ignores only apply to matching versions
+    let report = generate_report(&report::Settings {
+        ignore_package: vec![ignore("base", Some("^1")), ignore("other", Some(">= 2"))],
+        ..Default::default()
+    });
+    assert_eq!(vulnerability_ids(&report), ["RUSTSEC-2002-0001"]);
+
+    // Package and ID-based ignores combine, and expired ignores of ignored
+    // packages aren't warned about
+    let report = generate_report(&report::Settings {
+        ignore: vec![report::IgnoredAdvisory {
+            id: "RUSTSEC-2002-0001".parse().unwrap(),
+            expires: Some("2001-01-01".parse().unwrap()),
+        }],
+        ignore_package: vec![ignore("base", None), ignore("other", Some("< 1.1"))],
+        ..Default::default()
+    });
+    assert!(vulnerability_ids(&report).is_empty());
+    assert!(report.warnings.is_empty());
+
+    // Ignores without a version requirement can be written to configuration files
+    let settings = report::Settings {
+        ignore_package: vec![ignore("base", None), ignore("other", Some("< 1.1"))],
+        ..Default::default()
+    };
+    let toml = toml::to_string(&settings).unwrap();
+    let parsed: report::Settings = toml::from_str(&toml).unwrap();
+    assert_eq!(parsed.ignore_package, settings.ignore_package);
+    assert_eq!(
+        serde_json::to_value(&settings.ignore_package).unwrap(),
+        serde_json::json!([{ "name": "base" }, { "name": "other", "version": "<1.1" }])
+    );
+}
+
+#[test]
+fn cvss_environment() {
+    let base_id: Id = "RUSTSEC-2001-2101".parse().unwrap();
+    let base_severity = |report: &Report| {
+        let vuln = report
+            .vulnerabilities
+            .list
+            .iter()
+            .find(|vuln| vuln.advisory.id == base_id)
+            .unwrap();
+        (vuln.effective_severity, vuln.advisory.cvss.clone())
+    };
+
+    // The advisory is critical as published
+    let report = generate_report(&report::Settings::default());
+    let (effective, cvss) = base_severity(&report);
+    assert_eq!(effective, None);
+    assert_eq!(cvss.as_ref().unwrap().severity(), Severity::Critical);
+
+    // Requiring physical access without affecting other components makes it
+    // medium, while the advisory's vector is left intact
+    let environment: cvss::v3::Environmental = "MAV:P/MS:U".parse().unwrap();
+    let settings = report::Settings {
+        cvss_environment: Some(environment.clone()),
+        ..Default::default()
+    };
+    let report = generate_report(&settings);
+    let (effective, adjusted_cvss) = base_severity(&report);
+    assert_eq!(effective, Some(Severity::Medium));
+    assert_eq!(adjusted_cvss, cvss);
+
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let advisory = &db.get(&base_id).unwrap().metadata;
+    assert_eq!(
+        settings.effective_severity(advisory),
+        Some(Severity::Medium)
+    );
+
+    // Which is below a high severity threshold...
+    let report = generate_report(&report::Settings {
+        severity_threshold: Some(Severity::High),
+        ..settings.clone()
+    });
+    assert_eq!(vulnerability_ids(&report), ["RUSTSEC-2002-0001"]);
+    assert_eq!(
+        report.vulnerabilities.below_threshold[0].advisory.id,
+        base_id
+    );
+
+    // ...and is left out by a minimum severity, unlike the unscored advisory
+    let report = generate_report(&report::Settings {
+        severity: Some(Severity::High),
+        ..settings.clone()
+    });
+    assert_eq!(vulnerability_ids(&report), ["RUSTSEC-2002-0001"]);
+
+    // Metrics of particular advisories take precedence
+    let report = generate_report(&report::Settings {
+        severity: Some(Severity::High),
+        cvss_overrides: [(base_id.clone(), "E:X".parse().unwrap())].into(),
+        ..settings
+    });
+    assert_eq!(
+        vulnerability_ids(&report),
+        ["RUSTSEC-2001-2101", "RUSTSEC-2002-0001"]
+    );
+    assert_eq!(base_severity(&report).0, Some(Severity::Critical));
+}
+
+#[test]
+fn severity_overrides() {
+    let base_id: Id = "RUSTSEC-2001-2101".parse().unwrap();
+    let other_id: Id = "RUSTSEC-2002-0001".parse().unwrap();
+    let adjusted = |report: &Report| -> Vec<(String, Option<report::SeverityOverride>)> {
+        report
+            .policy_adjusted()
+            .map(|vuln| (vuln.advisory.id.to_string(), vuln.severity_override))
+            .collect()
+    };
+
+    // Downgrade the critical advisory and escalate the unscored one
+    let dir = tempfile::tempdir().unwrap();
+    let policy = dir.path().join("policy.toml");
+    fs::write(
+        &policy,
+        "\"RUSTSEC-2001-2101\" = \"low\"\n\"RUSTSEC-2002-0001\" = \"critical\"\n",
+    )
+    .unwrap();
+
+    let mut settings = report::Settings {
+        severity_threshold: Some(Severity::High),
+        ..Default::default()
+    };
+    settings.load_severity_overrides(&policy).unwrap();
+    assert_eq!(
+        settings.severity_overrides[&base_id],
+        report::SeverityOverride::Severity(Severity::Low)
+    );
+
+    let report = generate_report(&settings);
+    assert_eq!(vulnerability_ids(&report), ["RUSTSEC-2002-0001"]);
+    assert_eq!(
+        report.vulnerabilities.list[0].effective_severity,
+        Some(Severity::Critical)
+    );
+    let below = &report.vulnerabilities.below_threshold;
+    assert_eq!(below[0].advisory.id, base_id);
+    assert_eq!(below[0].effective_severity, Some(Severity::Low));
+    assert_eq!(
+        adjusted(&report),
+        [
+            (
+                "RUSTSEC-2002-0001".to_owned(),
+                Some(report::SeverityOverride::Severity(Severity::Critical))
+            ),
+            (
+                "RUSTSEC-2001-2101".to_owned(),
+                Some(report::SeverityOverride::Severity(Severity::Low))
+            ),
+        ]
+    );
+
+    // Ignored advisories aren't found, but still listed
+    let settings = report::Settings {
+        severity_overrides: [(other_id.clone(), report::SeverityOverride::Ignore)].into(),
+        ..Default::default()
+    };
+    let report = generate_report(&settings);
+    assert_eq!(vulnerability_ids(&report), ["RUSTSEC-2001-2101"]);
+    assert_eq!(report.vulnerabilities.count, 1);
+    assert_eq!(
+        report.vulnerabilities.ignored_by_policy[0].advisory.id,
+        other_id
+    );
+    assert_eq!(
+        adjusted(&report),
+        [(
+            "RUSTSEC-2002-0001".to_owned(),
+            Some(report::SeverityOverride::Ignore)
+        )]
+    );
+
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    assert_eq!(
+        db.vulnerabilities_with_settings(&load_lockfile(), &settings)
+            .len(),
+        1
+    );
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(
+        json["settings"]["severity_overrides"]["RUSTSEC-2002-0001"],
+        "ignore"
+    );
+    assert_eq!(
+        json["vulnerabilities"]["ignored-by-policy"][0]["severity_override"],
+        "ignore"
+    );
+
+    // Overrides other than severities and `ignore` are rejected
+    fs::write(&policy, "\"RUSTSEC-2001-2101\" = \"downgrade\"\n").unwrap();
+    let err = report::Settings::default()
+        .load_severity_overrides(&policy)
+        .unwrap_err();
+    assert_eq!(err.kind(), rustsec::ErrorKind::Parse);
+}
+
+#[test]
+fn collapse_cve_aliases() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let dir = db_dir.path().join("crates/dup");
+    fs::create_dir_all(&dir).unwrap();
+
+    for (id, cve) in [
+        ("RUSTSEC-2020-0002", "CVE-2020-1111"),
+        ("RUSTSEC-2020-0001", "CVE-2020-1111"),
+        ("RUSTSEC-2020-0003", "CVE-2020-2222"),
+    ] {
+        fs::write(
+            dir.join(format!("{id}.md")),
+            format!(
+                "```toml\n[advisory]\nid = \"{id}\"\npackage = \"dup\"\ndate = \"2020-01-01\"\naliases = [\"{cve}\"]\n\n[versions]\npatched = []\n```\n\n# Vulnerability in dup\n\nDetails.\n"
+            ),
+        )
+        .unwrap();
+    }
+
+    let db = Database::open(db_dir.path()).unwrap();
+    let lockfile: Lockfile = "version = 3\n\n[[package]]\nname = \"dup\"\nversion = \"1.0.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n"
+        .parse()
+        .unwrap();
+
+    // Every advisory is reported by default
+    let report = Report::generate(&db, &lockfile, &report::Settings::default());
+    assert_eq!(report.vulnerabilities.count, 3);
+    assert!(report
+        .vulnerabilities
+        .list
+        .iter()
+        .all(|vuln| vuln.duplicates.is_empty()));
+
+    // Advisories about the same CVE are collapsed into the first one
+    let settings = report::Settings {
+        collapse_cve_aliases: true,
+        ..Default::default()
+    };
+    let report = Report::generate(&db, &lockfile, &settings);
+    assert_eq!(
+        vulnerability_ids(&report),
+        ["RUSTSEC-2020-0001", "RUSTSEC-2020-0003"]
+    );
+    assert_eq!(report.vulnerabilities.count, 2);
+
+    let list = &report.vulnerabilities.list;
+    assert_eq!(
+        list[0].duplicates,
+        ["RUSTSEC-2020-0002".parse::<Id>().unwrap()]
+    );
+    assert!(list[1].duplicates.is_empty());
+}
+
+#[test]
+fn mitigation() {
+    let db_dir = tempfile::tempdir().unwrap();
+    let dir = db_dir.path().join("crates/unpatched");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("RUSTSEC-2020-0001.md"),
+        "```toml\n[advisory]\nid = \"RUSTSEC-2020-0001\"\npackage = \"unpatched\"\ndate = \"2020-01-01\"\nmitigation = \"Disable the `unsafe` feature.\"\n\n[versions]\npatched = []\n```\n\n# Vulnerability in unpatched\n\nDetails.\n",
+    )
+    .unwrap();
+
+    let db = Database::open(db_dir.path()).unwrap();
+    assert!(db.load_errors().is_empty());
+    let lockfile: Lockfile = "version = 3\n\n[[package]]\nname = \"unpatched\"\nversion = \"1.0.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n"
+        .parse()
+        .unwrap();
+
+    let report = Report::generate(&db, &lockfile, &report::Settings::default());
+    let advisory = &report.vulnerabilities.list[0].advisory;
+    assert_eq!(advisory.description, "Details.");
+    assert_eq!(
+        advisory.mitigation.as_deref(),
+        Some("Disable the `unsafe` feature.")
+    );
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(
+        json["vulnerabilities"]["list"][0]["advisory"]["mitigation"],
+        "Disable the `unsafe` feature."
+    );
+
+    // Advisories without a mitigation don't serialize one
+    let report = full_report();
+    let json = serde_json::to_value(&report).unwrap();
+    assert!(json["vulnerabilities"]["list"][0]["advisory"]
+        .get("mitigation")
+        .is_none());
+}
+
+#[test]
+fn parse_ignore() {
+    #[derive(serde::Deserialize, serde::Serialize)]
+    struct Config {
+        ignore: Vec<report::IgnoredAdvisory>,
+    }
+
+    let toml =
+        r#"ignore = ["RUSTSEC-2001-2101", { id = "RUSTSEC-2002-0001", expires = "2030-01-01" }]"#;
+    let config: Config = toml::from_str(toml).unwrap();
+
+    assert_eq!(config.ignore[0].id.as_str(), "RUSTSEC-2001-2101");
+    assert_eq!(config.ignore[0].expires, None);
+    assert_eq!(config.ignore[1].id.as_str(), "RUSTSEC-2002-0001");
+    assert_eq!(
+        config.ignore[1].expires.as_ref().unwrap().as_str(),
+        "2030-01-01"
+    );
+
+    let reparsed: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(reparsed.ignore, config.ignore);
+}
+
+#[test]
+fn source_not_registry() {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let lockfile = Lockfile::load("./tests/support/git_source_cargo.lock").unwrap();
+
+    // Off by default
+    let report = Report::generate(&db, &lockfile, &report::Settings::default());
+    assert!(!report
+        .warnings
+        .contains_key(&WarningKind::SourceNotRegistry));
+
+    let settings = report::Settings {
+        warn_source_not_registry: true,
+        ..Default::default()
+    };
+    let report = Report::generate(&db, &lockfile, &settings);
+
+    // Only the git dependency is reported, not the registry dependency or
+    // the workspace member without any source
+    let warnings = &report.warnings[&WarningKind::SourceNotRegistry];
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].package.name.as_str(), "forked");
+    assert!(warnings[0].is_source_not_registry());
+    assert!(warnings[0].advisory.is_none());
+}
+
+/// Streamed findings are the same as those of a report generated at once
+#[test]
+fn generate_streaming() {
+    let db = Database::open(Path::new("./tests/support/advisory-db")).unwrap();
+    let lockfile = load_lockfile();
+    let settings = report::Settings {
+        informational_warnings: vec![Informational::Unmaintained],
+        collapse_cve_aliases: true,
+        ..Default::default()
+    };
+    let report = Report::generate(&db, &lockfile, &settings);
+
+    let mut vulnerabilities = vec![];
+    let mut warnings: report::WarningInfo = Default::default();
+    Report::generate_streaming(&db, &lockfile, &settings, |finding| match finding {
+        report::Finding::Vulnerability(vuln) => vulnerabilities.push(vuln),
+        report::Finding::Warning(warning) => {
+            warnings.entry(warning.kind).or_default().push(warning)
+        }
+    });
+
+    assert!(!vulnerabilities.is_empty());
+    assert!(!warnings.is_empty());
+
+    vulnerabilities.sort_by(|a, b| {
+        (&a.advisory.id, &a.package.name, &a.package.version).cmp(&(
+            &b.advisory.id,
+            &b.package.name,
+            &b.package.version,
+        ))
+    });
+    assert_eq!(vulnerabilities, report.vulnerabilities.list);
+
+    for warnings in warnings.values_mut() {
+        warnings.sort_by(|a, b| {
+            let key = |warning: &Warning| {
+                (
+                    warning
+                        .advisory
+                        .as_ref()
+                        .map(|advisory| advisory.id.clone()),
+                    warning.package.name.clone(),
+                    warning.package.version.clone(),
+                )
+            };
+            key(a).cmp(&key(b))
+        });
+    }
+    assert_eq!(
+        serde_json::to_value(&warnings).unwrap(),
+        serde_json::to_value(&report.warnings).unwrap()
+    );
+}
@@ -0,0 +1,162 @@
+//! Tests for verifying signatures on commits to the advisory DB
+#![cfg(feature = "git")]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use rustsec::{
+    repository::{
+        git::Repository,
+        signature::{Signature, TrustedKey},
+    },
+    Database, ErrorKind,
+};
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// Directory containing the signature fixtures
+const FIXTURES: &str = "./tests/support/signatures";
+
+/// Run `git` in the given directory with the given standard input,
+/// returning its trimmed output
+fn git(dir: &Path, args: &[&str], stdin: &[u8]) -> String {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("git is installed");
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+/// Create a repository whose `HEAD` is the given fixture commit
+fn repository(commit: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"], b"");
+
+    // The fixture commits point to the empty tree
+    git(
+        dir.path(),
+        &["hash-object", "-t", "tree", "-w", "--stdin"],
+        b"",
+    );
+
+    set_head(dir.path(), commit);
+    dir
+}
+
+/// Point `HEAD` of the repository in the given directory at the given fixture commit
+fn set_head(dir: &Path, commit: &str) {
+    let commit = fs::read(Path::new(FIXTURES).join(commit)).unwrap();
+    let id = git(
+        dir,
+        &["hash-object", "-t", "commit", "-w", "--stdin"],
+        &commit,
+    );
+    git(dir, &["update-ref", "HEAD", &id], b"");
+}
+
+fn trusted_key(name: &str) -> TrustedKey {
+    fs::read_to_string(Path::new(FIXTURES).join(name))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn parse_trusted_key() {
+    let key = trusted_key("trusted.pub");
+    assert_eq!(
+        key.fingerprint(),
+        "SHA256:5n8XWL2LTwWIRkGDSBrmL4qmw0LWKWLN7nKAqZeIEnA"
+    );
+    assert_eq!(key, key.to_string().parse().unwrap());
+
+    let err = "ssh-ed25519 invalid".parse::<TrustedKey>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Parse);
+}
+
+#[test]
+fn signed_commit() {
+    let dir = repository("signed.commit");
+    let repo = Repository::open(dir.path()).unwrap();
+    let commit = repo.latest_commit().unwrap();
+
+    commit
+        .verify_signature(&[trusted_key("untrusted.pub"), trusted_key("trusted.pub")])
+        .unwrap();
+    assert!(Database::load_from_repo_verified(&repo, &[trusted_key("trusted.pub")]).is_ok());
+
+    // Signatures by keys which aren't trusted are rejected
+    let err = commit
+        .verify_signature(&[trusted_key("untrusted.pub")])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Signature);
+
+    let err = Database::load_from_repo_verified(&repo, &[]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Signature);
+}
+
+#[test]
+fn signed_data_is_verified() {
+    let dir = repository("signed.commit");
+    let repo = Repository::open(dir.path()).unwrap();
+    let commit = repo.latest_commit().unwrap();
+    let signature = commit.signature.as_ref().unwrap();
+    let signed_data = commit.raw_signed_bytes().unwrap();
+    let trusted_keys = [trusted_key("trusted.pub")];
+
+    signature.verify(signed_data, &trusted_keys).unwrap();
+
+    let mut tampered = signed_data.to_vec();
+    tampered.extend_from_slice(b"\nTampered");
+    let err = signature.verify(&tampered, &trusted_keys).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Signature);
+
+    // OpenPGP signatures can't be verified
+    let pgp = Signature::from_bytes(b"-----BEGIN PGP SIGNATURE-----").unwrap();
+    let err = pgp.verify(signed_data, &trusted_keys).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Signature);
+}
+
+#[test]
+fn unsigned_commit() {
+    let dir = repository("unsigned.commit");
+    let repo = Repository::open(dir.path()).unwrap();
+    assert!(repo.latest_commit().unwrap().signature.is_none());
+
+    // Loading refuses to proceed, while loading without verification works
+    let err = Database::load_from_repo_verified(&repo, &[trusted_key("trusted.pub")]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Signature);
+    assert!(Database::load_from_repo(&repo).is_ok());
+}
+
+/// A database loaded with verification refuses to be updated to an unsigned commit
+#[test]
+fn update_to_unsigned_commit() {
+    let dir = repository("signed.commit");
+    let repo = Repository::open(dir.path()).unwrap();
+    let mut db = Database::load_from_repo_verified(&repo, &[trusted_key("trusted.pub")]).unwrap();
+    let signed = db.latest_commit().unwrap().commit_id;
+
+    set_head(dir.path(), "unsigned.commit");
+    let repo = Repository::open(dir.path()).unwrap();
+    let err = db.update_from_repo(&repo).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Signature);
+    assert_eq!(db.latest_commit().unwrap().commit_id, signed);
+
+    // Without verification, the update goes ahead
+    let dir = repository("signed.commit");
+    let mut db = Database::load_from_repo(&Repository::open(dir.path()).unwrap()).unwrap();
+    set_head(dir.path(), "unsigned.commit");
+    db.update_from_repo(&Repository::open(dir.path()).unwrap())
+        .unwrap();
+    assert_ne!(db.latest_commit().unwrap().commit_id, signed);
+}
@@ -0,0 +1,82 @@
+//! Helpers shared by the integration tests, next to their fixtures
+//!
+//! Every test binary includes this module with `mod support;` and only uses
+//! some of the helpers.
+#![allow(dead_code)]
+
+use once_cell::sync::Lazy;
+use std::{env, fs, path::Path, process::Command};
+
+/// An empty Cargo home, i.e. no advisory databases or registry indices
+/// downloaded yet, shared by all tests of a binary as it's set for the whole
+/// process. Tests relying on it have to force it before doing anything else.
+pub static CARGO_HOME: Lazy<tempfile::TempDir> = Lazy::new(|| {
+    let cargo_home = tempfile::tempdir().unwrap();
+    env::set_var("CARGO_HOME", cargo_home.path());
+    cargo_home
+});
+
+/// Run `git` in the given directory, panicking if it fails
+pub fn git(dir: &Path, args: &[&str]) {
+    git_with_env(dir, args, &[]);
+}
+
+/// Run `git` in the given directory with additional environment variables,
+/// e.g. to fix the dates of commits, panicking if it fails
+pub fn git_with_env(dir: &Path, args: &[&str], envs: &[(&str, &str)]) {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=RustSec",
+            "-c",
+            "user.email=rustsec@example.com",
+        ])
+        .args(args)
+        .envs(envs.iter().copied())
+        .current_dir(dir)
+        .status()
+        .expect("git is installed");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+/// Create a git registry index in the given directory containing the given
+/// crates, see [`write_index_crate`], returning its URL
+pub fn create_git_index(dir: &Path, crates: &[(&str, &[(&str, bool)])]) -> String {
+    fs::write(
+        dir.join("config.json"),
+        r#"{"dl":"https://example.com/api/v1/crates"}"#,
+    )
+    .unwrap();
+    for (name, versions) in crates {
+        write_index_crate(dir, name, versions);
+    }
+
+    git(dir, &["init", "-q"]);
+    git(dir, &["add", "."]);
+    git(dir, &["commit", "-q", "-m", "Index"]);
+
+    format!("file://{}", dir.display())
+}
+
+/// Write the entry of the crate `name` to the registry index in the given
+/// directory, with the given versions and whether they're yanked
+pub fn write_index_crate(dir: &Path, name: &str, versions: &[(&str, bool)]) {
+    let path = match name.len() {
+        1 => dir.join("1"),
+        2 => dir.join("2"),
+        3 => dir.join("3").join(&name[..1]),
+        _ => dir.join(&name[..2]).join(&name[2..4]),
+    };
+    fs::create_dir_all(&path).unwrap();
+
+    let entries: String = versions
+        .iter()
+        .map(|(version, yanked)| {
+            format!(
+                "{{\"name\":\"{name}\",\"vers\":\"{version}\",\"deps\":[],\"cksum\":\"{}\",\"features\":{{}},\"yanked\":{yanked}}}\n",
+                "0".repeat(64)
+            )
+        })
+        .collect();
+    fs::write(path.join(name), entries).unwrap();
+}